@@ -2,8 +2,9 @@
 static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
 extern crate wake;
-use polars::prelude::DataFrame;
+use polars::prelude::{CsvWriter, DataFrame, SerWriter};
 use std::env;
+use wake::forecast::backtest::{record_snapshots, Backtest};
 use wake::graph::*;
 
 // TODO: UNCOMMENT THE IMPORT STATEMENTS BELOW AS YOU IMPLEMENT THESE QUERIES.
@@ -18,10 +19,11 @@ mod utils;
 
 fn main() {
     // Arguments:
-    // 0: Whether to run query or test. Required. query/test.
-    // 1: Query Number. Required.
+    // 0: Whether to run query, test or backtest. Required. query/test/backtest.
+    // 1: Query Number, or the path of a .sql file. Required.
     // 2: Scale of the TPC-H Dataset. Optional. Default: 1.
     // 3: Directory containing the dataset. Optional. Default: resources/tpc-h/data/scale=1/partition=10/
+    // 4: For backtest, the CSV file to write the report to. Optional. Default: standard output.
 
     env_logger::Builder::from_default_env()
         .format_timestamp_micros()
@@ -31,10 +33,13 @@ fn main() {
     match args[0].as_str() {
         "test" => tests::test_tpch_query(args[1].as_str()),
         "query" => run_query(args.into_iter().skip(1).collect::<Vec<String>>()),
+        "backtest" => run_backtest(args.into_iter().skip(1).collect::<Vec<String>>()),
         _ => panic!(
             "Invalid Argument to the cargo run command.
         Run: `cargo run --release --example tpch_polars -- query q1` to run query q1.
-        Run `cargo run --release --example tpch_polars -- test` to run test for query q1."
+        Run `cargo run --release --example tpch_polars -- query query.sql` to run a SQL query.
+        Run `cargo run --release --example tpch_polars -- test` to run test for query q1.
+        Run `cargo run --release --example tpch_polars -- backtest q1 1 <data> report.csv` to backtest forecasts on q1."
         ),
     }
 }
@@ -54,27 +59,83 @@ fn run_query(args: Vec<String>) {
     } else {
         args[2].as_str()
     };
+    if query_no.ends_with(".sql") {
+        let sql = std::fs::read_to_string(query_no).unwrap();
+        if let Ok(wake::sql::Statement::Explain { analyze, .. }) = wake::sql::parse_statement(&sql)
+        {
+            let catalog = utils::load_tables(data_directory, scale);
+            let planner = wake::sql::SqlPlanner::new(&catalog);
+            let explained = if analyze {
+                planner.explain_analyze(&sql)
+            } else {
+                planner.explain(&sql)
+            };
+            println!("{}", explained.unwrap());
+            return;
+        }
+    }
     let mut output_reader = NodeReader::empty();
     let mut query_service = get_query_service(query_no, scale, data_directory, &mut output_reader);
     log::info!("Running Query: {}", query_no);
     utils::run_query(&mut query_service, &mut output_reader);
 }
 
+/// Runs a query, and reports how far forecasts from its snapshots are from its final answer
+/// over time, for several estimators (see `wake::forecast::backtest::Backtest`).
+fn run_backtest(args: Vec<String>) {
+    if args.len() == 0 {
+        panic!("Query not specified. Run like: cargo run --release --example tpch_polars -- backtest q1")
+    }
+    let query_no = args[0].as_str();
+    let scale = if args.len() <= 1 {
+        1
+    } else {
+        args[1].parse::<usize>().unwrap()
+    };
+    let data_directory = if args.len() <= 2 {
+        "resources/tpc-h/data/scale=1/partition=10"
+    } else {
+        args[2].as_str()
+    };
+    let mut output_reader = NodeReader::empty();
+    let mut query_service = get_query_service(query_no, scale, data_directory, &mut output_reader);
+    log::info!("Backtesting Query: {}", query_no);
+    query_service.run();
+    let snapshots = record_snapshots(&output_reader);
+    query_service.join();
+
+    let mut report = Backtest::new().run(&snapshots);
+    let output: Box<dyn std::io::Write> = match args.get(3) {
+        Some(path) => Box::new(std::fs::File::create(path).unwrap()),
+        None => Box::new(std::io::stdout()),
+    };
+    CsvWriter::new(output)
+        .has_header(true)
+        .finish(&mut report)
+        .unwrap();
+}
+
 pub fn get_query_service(
     query_no: &str,
     scale: usize,
     data_directory: &str,
     output_reader: &mut NodeReader<DataFrame>,
 ) -> ExecutionService<DataFrame> {
-    let table_input = utils::load_tables(data_directory, scale);
+    let catalog = utils::load_tables(data_directory, scale);
     // TODO: UNCOMMENT THE MATCH STATEMENTS BELOW AS YOU IMPLEMENT THESE QUERIES.
     let query_service = match query_no {
-        "q1" => q1::query(table_input, output_reader),
-        "q14" => q14::query(table_input, output_reader),
-        "qa" => qa::query(table_input, output_reader),
-        "qb" => qb::query(table_input, output_reader),
-        "qc" => qc::query(table_input, output_reader),
-        "qd" => qd::query(table_input, output_reader),
+        "q1" => q1::query(&catalog, output_reader),
+        "q14" => q14::query(&catalog, output_reader),
+        "qa" => qa::query(&catalog, output_reader),
+        "qb" => qb::query(&catalog, output_reader),
+        "qc" => qc::query(&catalog, output_reader),
+        "qd" => qd::query(&catalog, output_reader),
+        sql_file if sql_file.ends_with(".sql") => {
+            let sql = std::fs::read_to_string(sql_file).unwrap();
+            wake::sql::SqlPlanner::new(&catalog)
+                .plan(&sql, output_reader)
+                .unwrap()
+        }
         _ => panic!("Invalid Query Parameter"),
     };
     query_service
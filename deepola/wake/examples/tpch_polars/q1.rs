@@ -5,6 +5,7 @@ use polars::prelude::DataFrame;
 use polars::prelude::NamedFrom;
 use polars::series::ChunkCompare;
 use polars::series::Series;
+use wake::catalog::Catalog;
 use wake::graph::*;
 use wake::polars_operations::*;
 
@@ -35,7 +36,7 @@ use std::collections::HashMap;
 // limit -1;
 
 pub fn query(
-    tableinput: HashMap<String, TableInput>,
+    catalog: &Catalog,
     output_reader: &mut NodeReader<polars::prelude::DataFrame>,
 ) -> ExecutionService<polars::prelude::DataFrame> {
     // Create a HashMap that stores table name and the columns in that query.
@@ -54,8 +55,7 @@ pub fn query(
     )]);
 
     // CSVReaderNode would be created for this table.
-    let lineitem_csvreader_node =
-        build_csv_reader_node("lineitem".into(), &tableinput, &table_columns);
+    let lineitem_csvreader_node = build_csv_reader_node("lineitem".into(), catalog, &table_columns);
 
     // WHERE Node
     let where_node = AppenderNode::<DataFrame, MapAppender>::new()
@@ -144,24 +144,32 @@ pub fn query(
                 ),
                 Series::new("count_order", df.column("l_orderkey_count").unwrap()),
             ];
-            DataFrame::new(columns)
-                .unwrap()
-                .sort(&["l_returnflag", "l_linestatus"], vec![false, false])
-                .unwrap()
+            DataFrame::new(columns).unwrap()
         })))
         .build();
 
+    // ORDER BY Node
+    let sort_node = SortNode::new()
+        .by(vec![
+            ("l_returnflag".into(), false),
+            ("l_linestatus".into(), false),
+        ])
+        .mode(SortMode::PerSnapshot)
+        .build();
+
     // Connect nodes with subscription
     where_node.subscribe_to_node(&lineitem_csvreader_node, 0);
     expression_node.subscribe_to_node(&where_node, 0);
     groupby_node.subscribe_to_node(&expression_node, 0);
     select_node.subscribe_to_node(&groupby_node, 0);
+    sort_node.subscribe_to_node(&select_node, 0);
 
     // Output reader subscribe to output node.
-    output_reader.subscribe_to_node(&select_node, 0);
+    output_reader.subscribe_to_node(&sort_node, 0);
 
     // Add all the nodes to the service
     let mut service = ExecutionService::<polars::prelude::DataFrame>::create();
+    service.add(sort_node);
     service.add(select_node);
     service.add(groupby_node);
     service.add(expression_node);
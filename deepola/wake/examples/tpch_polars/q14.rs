@@ -1,12 +1,10 @@
 use crate::utils::*;
 extern crate wake;
-use itertools::Itertools;
 use polars::prelude::DataFrame;
-use polars::prelude::NamedFrom;
-use polars::series::ChunkCompare;
-use polars::series::Series;
+use wake::catalog::Catalog;
 use wake::graph::*;
 use wake::polars_operations::*;
+use wake::sql::parse_expr;
 use std::collections::HashMap;
 
 /// This node implements the following SQL query
@@ -25,7 +23,7 @@ use std::collections::HashMap;
 // 	and l_shipdate < date '1995-09-01' + interval '1' month;
 
 pub fn query(
-    tableinput: HashMap<String, TableInput>,
+    catalog: &Catalog,
     output_reader: &mut NodeReader<polars::prelude::DataFrame>,
 ) -> ExecutionService<polars::prelude::DataFrame> {
     // Create a HashMap that stores table name and the columns in that query.
@@ -39,17 +37,18 @@ pub fn query(
 
     // CSVReaderNode would be created for this table.
     let lineitem_csvreader_node =
-        build_csv_reader_node("lineitem".into(), &tableinput, &table_columns);
-    let part_csvreader_node = build_csv_reader_node("part".into(), &tableinput, &table_columns);
+        build_csv_reader_node("lineitem".into(), catalog, &table_columns);
+    let part_csvreader_node = build_csv_reader_node("part".into(), catalog, &table_columns);
 
     // WHERE Node
-    let where_node = AppenderNode::<DataFrame, MapAppender>::new()
-        .appender(MapAppender::new(Box::new(|df: &DataFrame| {
-            let a = df.column("l_shipdate").unwrap();
-            let mask = a.gt_eq("1995-09-01").unwrap() & a.lt("1995-10-01").unwrap();
-            let result = df.filter(&mask).unwrap();
-            result
-        })))
+    let where_node = FilterNode::new()
+        .predicate(
+            parse_expr(
+                "l_shipdate >= date '1995-09-01' \
+                 AND l_shipdate < date '1995-09-01' + interval '1' month",
+            )
+            .unwrap(),
+        )
         .build();
 
     // HASH JOIN Node
@@ -59,36 +58,21 @@ pub fn query(
         .build();
 
     // EXPRESSION Node
-    let expression_node = AppenderNode::<DataFrame, MapAppender>::new()
-        .appender(MapAppender::new(Box::new(|df: &DataFrame| {
-            let a = df.column("p_type").unwrap();
-            let mask = a
-                .utf8()
-                .unwrap()
-                .into_iter()
-                .map(|x| x.unwrap().starts_with("PROMO") as i32)
-                .collect_vec();
-            let extended_price = df.column("l_extendedprice").unwrap();
-            let discount = df.column("l_discount").unwrap();
-            let columns = vec![
-                Series::new(
-                    "denominator_promo_revenue",
-                    extended_price
-                        .cast(&polars::datatypes::DataType::Float64)
-                        .unwrap()
-                        * (discount * -1f64 + 1f64),
-                ),
-                Series::new(
-                    "numerator_promo_revenue",
-                    extended_price
-                        .cast(&polars::datatypes::DataType::Float64)
-                        .unwrap()
-                        * (discount * -1f64 + 1f64)
-                        * Series::new("promo", &mask),
-                ),
-            ];
-            df.hstack(&columns).unwrap()
-        })))
+    let expression_node = ProjectNode::new()
+        .expressions(vec![
+            (
+                parse_expr("l_extendedprice * (1 - l_discount)").unwrap(),
+                "denominator_promo_revenue".into(),
+            ),
+            (
+                parse_expr(
+                    "case when p_type like 'PROMO%' \
+                     then l_extendedprice * (1 - l_discount) else 0 end",
+                )
+                .unwrap(),
+                "numerator_promo_revenue".into(),
+            ),
+        ])
         .build();
 
     // AGGREGATE Node
@@ -98,13 +82,11 @@ pub fn query(
         .build();
 
     // SELECT Node
-    let select_node = AppenderNode::<DataFrame, MapAppender>::new()
-        .appender(MapAppender::new(Box::new(|df: &DataFrame| {
-            let num = df.column("numerator_promo_revenue").unwrap();
-            let den = df.column("denominator_promo_revenue").unwrap();
-            let res = Series::new("promo_revenue", (num / den) * 100f64);
-            DataFrame::new(vec![res]).unwrap()
-        })))
+    let select_node = ProjectNode::new()
+        .expressions(vec![(
+            parse_expr("100.00 * numerator_promo_revenue / denominator_promo_revenue").unwrap(),
+            "promo_revenue".into(),
+        )])
         .build();
 
     // Connect nodes with subscription
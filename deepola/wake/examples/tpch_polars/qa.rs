@@ -1,13 +1,11 @@
 use crate::utils::*;
 
 extern crate wake;
-use polars::datatypes::BooleanChunked;
 use polars::prelude::DataFrame;
-use polars::prelude::NamedFrom;
-use polars::series::ChunkCompare;
-use polars::series::Series;
+use wake::catalog::Catalog;
 use wake::graph::*;
 use wake::polars_operations::*;
+use wake::sql::{parse_expr, Expr};
 
 use std::collections::HashMap;
 
@@ -23,7 +21,7 @@ use std::collections::HashMap;
 //     AND p_mfgr LIKE 'Manufacturer#1%';
 
 pub fn query(
-    tableinput: HashMap<String, TableInput>,
+    catalog: &Catalog,
     output_reader: &mut NodeReader<polars::prelude::DataFrame>,
 ) -> ExecutionService<polars::prelude::DataFrame> {
     // Create a HashMap that stores table name and the columns in that query.
@@ -32,37 +30,25 @@ pub fn query(
     ]);
 
     // CSVReaderNode would be created for this table.
-    let part_csvreader_node = build_csv_reader_node("part".into(), &tableinput, &table_columns);
+    let part_csvreader_node = build_csv_reader_node("part".into(), catalog, &table_columns);
     
     // WHERE Node
-    let where_node = AppenderNode::<DataFrame, MapAppender>::new()
-        .appender(MapAppender::new(Box::new(|df: &DataFrame| {
-            println!("WHERE node filtering: {:?}", df.shape());
-
-            let price_mask = df.column("p_retailprice").unwrap().gt(1000.0).unwrap();
-            let size_mask = df.column("p_size").unwrap()
-                .equal(5).unwrap()
-                | df.column("p_size").unwrap().equal(10).unwrap()
-                | df.column("p_size").unwrap().equal(15).unwrap()
-                | df.column("p_size").unwrap().equal(20).unwrap();
-            let container_mask = df.column("p_container").unwrap()
-                .utf8().unwrap().not_equal("JUMBO JAR");
-            let mfgr_mask = df.column("p_mfgr").unwrap()
-                .utf8().unwrap().into_iter()
-                .map(|opt_s| opt_s.map_or(false, |s| s.starts_with("Manufacturer#1")))
-                .collect::<BooleanChunked>();
-
-            let mask = price_mask & size_mask & container_mask & mfgr_mask;
-            df.filter(&mask).unwrap()
-        })))
+    let where_node = FilterNode::new()
+        .predicate(
+            parse_expr(
+                "p_retailprice > 1000 AND p_size IN (5, 10, 15, 20) \
+                 AND p_container <> 'JUMBO JAR' AND p_mfgr LIKE 'Manufacturer#1%'",
+            )
+            .unwrap(),
+        )
         .build();
 
     // Dummy Group Key (For AGGREGATE Node)
-    let expression_node = AppenderNode::<DataFrame, MapAppender>::new()
-        .appender(MapAppender::new(Box::new(|df: &DataFrame| {
-            let dummy_column = Series::new("dummy_group_key", vec![1; df.height()]);
-            df.hstack(&[dummy_column]).unwrap()
-        })))
+    let expression_node = ProjectNode::new()
+        .expressions(vec![
+            (Expr::column("p_retailprice"), "p_retailprice".into()),
+            (parse_expr("1").unwrap(), "dummy_group_key".into()),
+        ])
         .build();
     
     // AGGREGATE Node
@@ -78,11 +64,8 @@ pub fn query(
         .build();
     
     // SELECT Node
-    let select_node = AppenderNode::<DataFrame, MapAppender>::new()
-        .appender(MapAppender::new(Box::new(|df: &DataFrame| {
-            let res = Series::new("total", df.column("p_retailprice_sum").unwrap());
-            DataFrame::new(vec![res]).unwrap()
-        })))
+    let select_node = ProjectNode::new()
+        .expressions(vec![(Expr::column("p_retailprice_sum"), "total".into())])
         .build();
 
     // Connect nodes with subscription
@@ -6,12 +6,13 @@ use polars::prelude::DataFrame;
 use polars::prelude::NamedFrom;
 use polars::series::ChunkCompare;
 use polars::series::Series;
+use wake::catalog::Catalog;
 use wake::graph::*;
 use wake::polars_operations::*;
 use std::collections::HashMap;
 
 pub fn query(
-    tableinput: HashMap<String, TableInput>,
+    catalog: &Catalog,
     output_reader: &mut NodeReader<polars::prelude::DataFrame>,
 ) -> ExecutionService<polars::prelude::DataFrame> {
     // Define table columns for supplier, nation, region, and orders
@@ -23,10 +24,10 @@ pub fn query(
     ]);
 
     // CSVReaderNodes for the four tables
-    let supplier_csvreader_node = build_csv_reader_node("supplier".into(), &tableinput, &table_columns);
-    let nation_csvreader_node = build_csv_reader_node("nation".into(), &tableinput, &table_columns);
-    let region_csvreader_node = build_csv_reader_node("region".into(), &tableinput, &table_columns);
-    let orders_csvreader_node = build_csv_reader_node("orders".into(), &tableinput, &table_columns);
+    let supplier_csvreader_node = build_csv_reader_node("supplier".into(), catalog, &table_columns);
+    let nation_csvreader_node = build_csv_reader_node("nation".into(), catalog, &table_columns);
+    let region_csvreader_node = build_csv_reader_node("region".into(), catalog, &table_columns);
+    let orders_csvreader_node = build_csv_reader_node("orders".into(), catalog, &table_columns);
 
     // WHERE Node for region filtering by 'EUROPE'
     let where_node = AppenderNode::<DataFrame, MapAppender>::new()
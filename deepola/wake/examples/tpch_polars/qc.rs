@@ -5,12 +5,13 @@ use polars::prelude::DataFrame;
 use polars::prelude::NamedFrom;
 use polars::series::ChunkCompare;
 use polars::series::Series;
+use wake::catalog::Catalog;
 use wake::graph::*;
 use wake::polars_operations::*;
 use std::collections::HashMap;
 
 pub fn query(
-    tableinput: HashMap<String, TableInput>,
+    catalog: &Catalog,
     output_reader: &mut NodeReader<polars::prelude::DataFrame>,
 ) -> ExecutionService<polars::prelude::DataFrame> {
     // Define table columns for customer, orders, and lineitem
@@ -21,9 +22,9 @@ pub fn query(
     ]);
 
     // CSVReaderNodes for the three tables
-    let customer_csvreader_node = build_csv_reader_node("customer".into(), &tableinput, &table_columns);
-    let orders_csvreader_node = build_csv_reader_node("orders".into(), &tableinput, &table_columns);
-    let lineitem_csvreader_node = build_csv_reader_node("lineitem".into(), &tableinput, &table_columns);
+    let customer_csvreader_node = build_csv_reader_node("customer".into(), catalog, &table_columns);
+    let orders_csvreader_node = build_csv_reader_node("orders".into(), catalog, &table_columns);
+    let lineitem_csvreader_node = build_csv_reader_node("lineitem".into(), catalog, &table_columns);
 
     // WHERE Node
     let where_node = AppenderNode::<DataFrame, MapAppender>::new()
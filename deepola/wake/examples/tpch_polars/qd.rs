@@ -2,10 +2,11 @@ use crate::utils::*;
 extern crate wake;
 use polars::prelude::DataFrame;
 use polars::prelude::NamedFrom;
-use polars::series::ChunkCompare;
 use polars::series::Series;
+use wake::catalog::Catalog;
 use wake::graph::*;
 use wake::polars_operations::*;
+use wake::sql::{parse_expr, Expr};
 use std::collections::HashMap;
 
 /// This node implements the following SQL query
@@ -24,7 +25,7 @@ use std::collections::HashMap;
 ///   );
 
 pub fn query(
-    tableinput: HashMap<String, TableInput>,
+    catalog: &Catalog,
     output_reader: &mut NodeReader<polars::prelude::DataFrame>,
 ) -> ExecutionService<polars::prelude::DataFrame> {
     // Define which columns we need from each table
@@ -40,16 +41,12 @@ pub fn query(
     ]);
 
     // Create CSVReaderNode for the two tables
-    let lineitem_csvreader_node = build_csv_reader_node("lineitem".into(), &tableinput, &table_columns);
-    let part_csvreader_node = build_csv_reader_node("part".into(), &tableinput, &table_columns);
+    let lineitem_csvreader_node = build_csv_reader_node("lineitem".into(), catalog, &table_columns);
+    let part_csvreader_node = build_csv_reader_node("part".into(), catalog, &table_columns);
 
     // WHERE node to filter `l_shipinstruct = 'DELIVER IN PERSON'`
-    let where_node = AppenderNode::<DataFrame, MapAppender>::new()
-        .appender(MapAppender::new(Box::new(|df: &DataFrame| {
-            let shipinstruct = df.column("l_shipinstruct").unwrap();
-            let mask = shipinstruct.equal("DELIVER IN PERSON").unwrap();
-            df.filter(&mask).unwrap()
-        })))
+    let where_node = FilterNode::new()
+        .predicate(parse_expr("l_shipinstruct = 'DELIVER IN PERSON'").unwrap())
         .build();
 
     // HASH JOIN node to join `lineitem` and `part` on `l_partkey = p_partkey`
@@ -59,51 +56,26 @@ pub fn query(
         .build();
 
     // Expression node to apply the filtering logic for `p_brand`, `l_quantity`, and `p_size`
-    let expression_node = AppenderNode::<DataFrame, MapAppender>::new()
-        .appender(MapAppender::new(Box::new(|df: &DataFrame| {
-            let p_brand = df.column("p_brand").unwrap();
-            let l_quantity = df.column("l_quantity").unwrap();
-            let p_size = df.column("p_size").unwrap();
-
-            // Create the masks for each brand condition
-            let mask = (p_brand.equal("Brand#12").unwrap()
-                & l_quantity.gt_eq(1).unwrap() & l_quantity.lt_eq(11).unwrap()
-                & p_size.gt_eq(1).unwrap() & p_size.lt_eq(5).unwrap())
-                | (p_brand.equal("Brand#23").unwrap()
-                & l_quantity.gt_eq(10).unwrap() & l_quantity.lt_eq(20).unwrap()
-                & p_size.gt_eq(1).unwrap() & p_size.lt_eq(10).unwrap())
-                | (p_brand.equal("Brand#34").unwrap()
-                & l_quantity.gt_eq(20).unwrap() & l_quantity.lt_eq(30).unwrap()
-                & p_size.gt_eq(1).unwrap() & p_size.lt_eq(15).unwrap());
-
-            // Combine the masks using OR condition
-            //let mask = brand12_mask | brand23_mask | brand34_mask;
-            df.filter(&mask).unwrap()
-        })))
+    let expression_node = FilterNode::new()
+        .predicate(
+            parse_expr(
+                "(p_brand = 'Brand#12' AND l_quantity BETWEEN 1 AND 11 AND p_size BETWEEN 1 AND 5) \
+                 OR (p_brand = 'Brand#23' AND l_quantity BETWEEN 10 AND 20 AND p_size BETWEEN 1 AND 10) \
+                 OR (p_brand = 'Brand#34' AND l_quantity BETWEEN 20 AND 30 AND p_size BETWEEN 1 AND 15)",
+            )
+            .unwrap(),
+        )
         .build();
 
     // Add a node to calculate `l_extendedprice * (1 - l_discount)` for the final sum
-    let revenue_calculation_node = AppenderNode::<DataFrame, MapAppender>::new()
-        .appender(MapAppender::new(Box::new(|df: &DataFrame| {
-            // let extended_price = df.column("l_extendedprice").unwrap().cast(&polars::datatypes::DataType::Float64).unwrap();
-            // let discount = df.column("l_discount").unwrap().cast(&polars::datatypes::DataType::Float64).unwrap();
-            // let revenue_series = extended_price * (discount * -1f64 + 1f64);
-            // df.hstack(&[Series::new("revenue", revenue_series)]).unwrap()
-
-            let extended_price = df.column("l_extendedprice").unwrap();
-            let discount = df.column("l_discount").unwrap();
-            let columns = vec![
-                Series::new(
-                    "disc_price",
-                    extended_price
-                        .cast(&polars::datatypes::DataType::Float64)
-                        .unwrap()
-                        * (discount * -1f64 + 1f64),
-                )
-            ];
-            df.hstack(&columns).unwrap()
-
-        })))
+    let revenue_calculation_node = ProjectNode::new()
+        .expressions(vec![
+            (Expr::column("l_partkey"), "l_partkey".into()),
+            (
+                parse_expr("l_extendedprice * (1 - l_discount)").unwrap(),
+                "disc_price".into(),
+            ),
+        ])
         .build();
 
     // AGGREGATE node to sum the revenue
@@ -0,0 +1,100 @@
+use serde::Deserialize;
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::data::{Column, DataType, Schema};
+
+use super::table_catalog::TableDefinition;
+
+/// The file formats a manifest can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Toml,
+    Json,
+}
+
+impl ManifestFormat {
+    pub fn from_path(path: &Path) -> Result<ManifestFormat, Box<dyn Error>> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Ok(ManifestFormat::Toml),
+            Some("json") => Ok(ManifestFormat::Json),
+            _ => Err(format!("Unknown manifest format: {:?}", path).into()),
+        }
+    }
+}
+
+/// The on-disk layout of a manifest. See [super::Catalog] for an example.
+#[derive(Debug, Deserialize)]
+pub(super) struct Manifest {
+    #[serde(default = "default_scale")]
+    pub scale: usize,
+    pub tables: Vec<TableManifest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct TableManifest {
+    name: String,
+    columns: Vec<ColumnManifest>,
+    #[serde(default = "default_delimiter")]
+    delimiter: char,
+    #[serde(default)]
+    has_headers: bool,
+    files: Vec<String>,
+    #[serde(default)]
+    partition_row_counts: Option<Vec<usize>>,
+    #[serde(default)]
+    row_count: Option<usize>,
+    #[serde(default)]
+    scaled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ColumnManifest {
+    name: String,
+    #[serde(rename = "type")]
+    dtype: String,
+    #[serde(default)]
+    key: bool,
+}
+
+fn default_scale() -> usize {
+    1
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+impl Manifest {
+    pub fn parse(manifest: &str, format: ManifestFormat) -> Result<Manifest, Box<dyn Error>> {
+        let manifest = match format {
+            ManifestFormat::Toml => toml::from_str(manifest)?,
+            ManifestFormat::Json => serde_json::from_str(manifest)?,
+        };
+        Ok(manifest)
+    }
+}
+
+impl TableManifest {
+    pub fn into_definition(self) -> Result<TableDefinition, Box<dyn Error>> {
+        let mut columns = vec![];
+        for column in self.columns {
+            let dtype = DataType::from_str(&column.dtype)?;
+            columns.push(if column.key {
+                Column::from_key_field(column.name, dtype)
+            } else {
+                Column::from_field(column.name, dtype)
+            });
+        }
+        Ok(TableDefinition {
+            schema: Schema::new(self.name, columns),
+            delimiter: self.delimiter,
+            has_headers: self.has_headers,
+            files: self.files,
+            partition_row_counts: self.partition_row_counts,
+            row_count: self.row_count,
+            scaled: self.scaled,
+        })
+    }
+}
@@ -0,0 +1,8 @@
+//! Table definitions (schema, file format, partition files, row counts) loaded from
+//! manifest files, so that datasets can be queried without hard-coding their metadata.
+mod manifest;
+mod reader;
+mod table_catalog;
+
+pub use manifest::*;
+pub use table_catalog::*;
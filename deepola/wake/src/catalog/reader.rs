@@ -0,0 +1,92 @@
+use polars::prelude::*;
+
+use crate::data::*;
+use crate::graph::ExecutionNode;
+use crate::polars_operations::CSVReaderBuilder;
+
+use super::table_catalog::Catalog;
+
+impl Catalog {
+    /// Creates a CSVReader node that reads all the partition files of the table. The file
+    /// names (and EOF) are already written to the node's input channel.
+    ///
+    /// @arg columns The columns to read. All columns are read if None.
+    pub fn build_csv_reader_node(
+        &self,
+        table: &str,
+        columns: Option<&[&str]>,
+    ) -> ExecutionNode<DataFrame> {
+        let definition = self
+            .table(table)
+            .unwrap_or_else(|| panic!("Table Not Defined: {}", table));
+        let schema = &definition.schema;
+
+        let mut projected_cols_index = None;
+        let mut projected_cols_names = None;
+        if let Some(columns) = columns {
+            let mut cols_index = columns.iter().map(|x| schema.index(x)).collect::<Vec<usize>>();
+            cols_index.sort_unstable();
+            cols_index.dedup();
+            let col_names = cols_index
+                .iter()
+                .map(|x| schema.get_column_from_index(*x).name)
+                .collect::<Vec<String>>();
+            projected_cols_index = Some(cols_index);
+            projected_cols_names = Some(col_names);
+        }
+        // Without headers, polars names columns as column_1, column_2, ...
+        if projected_cols_names.is_none() && !definition.has_headers {
+            projected_cols_names = Some(schema.columns.iter().map(|c| c.name.clone()).collect());
+        }
+
+        let csvreader = CSVReaderBuilder::new()
+            .delimiter(definition.delimiter)
+            .has_headers(definition.has_headers)
+            .column_names(projected_cols_names)
+            .projected_cols(projected_cols_index)
+            .build();
+
+        let mut metadata = MetaCell::Schema(schema.clone()).into_meta_map();
+        metadata.insert(
+            DATABLOCK_TOTAL_RECORDS.to_string(),
+            MetaCell::Float(self.total_records(table) as f64),
+        );
+        let input_files = df!("col" => &self.input_files(table)).unwrap();
+        let dblock = DataBlock::new(input_files, metadata);
+        csvreader.write_to_self(0, DataMessage::from(dblock));
+        csvreader.write_to_self(0, DataMessage::eof());
+        csvreader.set_label(&format!("CSVReader: {}", table));
+        csvreader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::NodeReader;
+
+    #[test]
+    fn can_read_catalog_table() {
+        let mut catalog =
+            Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        catalog.set_base_dir("resources/tpc-h/data/scale=0.05/partition=5");
+        let csvreader = catalog.build_csv_reader_node("part", Some(&["p_size", "p_partkey"]));
+        let reader_node = NodeReader::new(&csvreader);
+        csvreader.run();
+
+        let mut total_len = 0;
+        let mut message_count = 0;
+        loop {
+            let message = reader_node.read();
+            if message.is_eof() {
+                break;
+            }
+            let data = message.datablock().data();
+            assert_eq!(data.get_column_names(), vec!["p_partkey", "p_size"]);
+            total_len += data.height();
+            message_count += 1;
+        }
+        assert_eq!(message_count, 5);
+        assert_eq!(total_len, 10000);
+    }
+}
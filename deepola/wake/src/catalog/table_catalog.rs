@@ -0,0 +1,294 @@
+use glob::glob;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::data::Schema;
+
+use super::manifest::*;
+
+/// Everything needed to read a table: its schema, how its files are formatted, where the
+/// partition files are, and how many records they contain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDefinition {
+    pub schema: Schema,
+    pub delimiter: char,
+    pub has_headers: bool,
+    /// Glob patterns of the partition files. Relative patterns are resolved against the
+    /// base directory of the [Catalog].
+    pub files: Vec<String>,
+    /// Number of records in each partition file, in the order of [Catalog::input_files].
+    pub partition_row_counts: Option<Vec<usize>>,
+    /// Total number of records. Used if `partition_row_counts` is not given.
+    pub row_count: Option<usize>,
+    /// Whether `row_count` grows with the scale of the dataset (e.g., TPC-H lineitem).
+    pub scaled: bool,
+}
+
+impl TableDefinition {
+    pub fn name(&self) -> &str {
+        &self.schema.table
+    }
+}
+
+/// A set of table definitions, typically loaded from a manifest file (TOML or JSON).
+///
+/// Example manifest (TOML):
+/// ```toml
+/// scale = 1
+///
+/// [[tables]]
+/// name = "region"
+/// delimiter = "|"
+/// files = ["region.tbl*"]
+/// row_count = 5
+/// columns = [
+///     { name = "r_regionkey", type = "Integer", key = true },
+///     { name = "r_name", type = "Text" },
+///     { name = "r_comment", type = "Text" },
+/// ]
+/// ```
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    base_dir: PathBuf,
+    scale: usize,
+    tables: HashMap<String, TableDefinition>,
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog {
+            base_dir: PathBuf::from("."),
+            scale: 1,
+            tables: HashMap::new(),
+        }
+    }
+
+    /// Loads a manifest file. The format is detected from the extension (`.toml` or `.json`),
+    /// and relative file globs are resolved against the directory containing the manifest.
+    pub fn from_manifest_file<P: AsRef<Path>>(path: P) -> Result<Catalog, Box<dyn Error>> {
+        let path = path.as_ref();
+        let format = ManifestFormat::from_path(path)?;
+        let manifest = std::fs::read_to_string(path)?;
+        let mut catalog = Self::from_manifest_str(&manifest, format)?;
+        if let Some(parent) = path.parent() {
+            catalog.set_base_dir(parent);
+        }
+        Ok(catalog)
+    }
+
+    /// Parses a manifest. Relative file globs are resolved against the current directory
+    /// unless [Catalog::set_base_dir] is called.
+    pub fn from_manifest_str(
+        manifest: &str,
+        format: ManifestFormat,
+    ) -> Result<Catalog, Box<dyn Error>> {
+        let manifest = Manifest::parse(manifest, format)?;
+        let mut catalog = Catalog::new();
+        catalog.set_scale(manifest.scale);
+        for table in manifest.tables {
+            catalog.add_table(table.into_definition()?);
+        }
+        Ok(catalog)
+    }
+
+    pub fn add_table(&mut self, table: TableDefinition) {
+        self.tables.insert(table.name().to_string(), table);
+    }
+
+    pub fn set_base_dir<P: AsRef<Path>>(&mut self, base_dir: P) -> &mut Self {
+        self.base_dir = base_dir.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    pub fn set_scale(&mut self, scale: usize) -> &mut Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    pub fn table(&self, table: &str) -> Option<&TableDefinition> {
+        self.tables.get(table)
+    }
+
+    /// Names of all the tables, sorted.
+    pub fn table_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.tables.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn schema(&self, table: &str) -> Result<Schema, Box<dyn Error>> {
+        match self.table(table) {
+            Some(definition) => Ok(definition.schema.clone()),
+            None => Err(format!("Table Not Defined: {}", table).into()),
+        }
+    }
+
+    /// The partition files of the table, sorted taking into account the partition numbers.
+    pub fn input_files(&self, table: &str) -> Vec<String> {
+        let mut input_files = vec![];
+        let definition = match self.table(table) {
+            Some(definition) => definition,
+            None => return input_files,
+        };
+        for pattern in &definition.files {
+            let pattern = if Path::new(pattern).is_absolute() {
+                PathBuf::from(pattern)
+            } else {
+                self.base_dir.join(pattern)
+            };
+            for entry in glob(pattern.to_str().unwrap()).expect("Failed to read glob pattern") {
+                match entry {
+                    Ok(path) => input_files.push(path.to_str().unwrap().to_string()),
+                    Err(e) => log::warn!("{:?}", e),
+                }
+            }
+        }
+        alphanumeric_sort::sort_str_slice(&mut input_files);
+        input_files
+    }
+
+    /// Total number of records of the table; 0 if unknown.
+    pub fn total_records(&self, table: &str) -> usize {
+        match self.table(table) {
+            Some(TableDefinition {
+                partition_row_counts: Some(counts),
+                ..
+            }) => counts.iter().sum(),
+            Some(TableDefinition {
+                row_count: Some(count),
+                scaled,
+                ..
+            }) => {
+                if *scaled {
+                    count * self.scale
+                } else {
+                    *count
+                }
+            }
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataType;
+
+    static EXAMPLE_TOML: &str = r#"
+        scale = 2
+
+        [[tables]]
+        name = "part"
+        delimiter = "|"
+        files = ["part.tbl*"]
+        row_count = 200000
+        scaled = true
+        columns = [
+            { name = "p_partkey", type = "Integer", key = true },
+            { name = "p_name", type = "Text" },
+            { name = "p_retailprice", type = "Float" },
+        ]
+
+        [[tables]]
+        name = "nation"
+        delimiter = "|"
+        files = ["nation.tbl"]
+        partition_row_counts = [25]
+        columns = [
+            { name = "n_nationkey", type = "Integer", key = true },
+            { name = "n_name", type = "Text" },
+        ]
+    "#;
+
+    static EXAMPLE_JSON: &str = r#"{
+        "tables": [
+            {
+                "name": "region",
+                "has_headers": true,
+                "files": ["/data/region.csv"],
+                "columns": [
+                    { "name": "r_regionkey", "type": "Integer", "key": true },
+                    { "name": "r_name", "type": "Text" }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn can_load_toml_manifest() {
+        let catalog = Catalog::from_manifest_str(EXAMPLE_TOML, ManifestFormat::Toml).unwrap();
+        assert_eq!(catalog.table_names(), vec!["nation", "part"]);
+        assert_eq!(catalog.scale(), 2);
+
+        let part = catalog.table("part").unwrap();
+        assert_eq!(part.delimiter, '|');
+        assert!(!part.has_headers);
+        assert_eq!(part.schema.col_count(), 3);
+        assert_eq!(part.schema.keys(), &vec![0usize]);
+        assert_eq!(part.schema.dtype("p_retailprice"), DataType::Float);
+
+        assert_eq!(catalog.total_records("part"), 400000);
+        assert_eq!(catalog.total_records("nation"), 25);
+        assert_eq!(catalog.total_records("customer"), 0);
+        assert!(catalog.schema("customer").is_err());
+    }
+
+    #[test]
+    fn can_load_json_manifest() {
+        let catalog = Catalog::from_manifest_str(EXAMPLE_JSON, ManifestFormat::Json).unwrap();
+        let region = catalog.table("region").unwrap();
+        assert_eq!(region.delimiter, ',');
+        assert!(region.has_headers);
+        assert_eq!(region.schema.get_column("r_name").dtype, DataType::Text);
+        assert_eq!(catalog.total_records("region"), 0);
+    }
+
+    #[test]
+    fn rejects_invalid_manifest() {
+        let invalid_type = r#"{"tables": [{"name": "t", "files": [], "columns": [
+            {"name": "c", "type": "Decimal"}
+        ]}]}"#;
+        assert!(Catalog::from_manifest_str(invalid_type, ManifestFormat::Json).is_err());
+        assert!(Catalog::from_manifest_str("tables = 1", ManifestFormat::Toml).is_err());
+        assert!(ManifestFormat::from_path(Path::new("tables.yaml")).is_err());
+    }
+
+    #[test]
+    fn can_glob_partition_files() {
+        let mut catalog = Catalog::from_manifest_str(EXAMPLE_TOML, ManifestFormat::Toml).unwrap();
+        catalog.set_base_dir("resources/tpc-h/data/scale=0.05/partition=5");
+        let files = catalog.input_files("part");
+        assert_eq!(files.len(), 5);
+        assert!(files[0].ends_with("part.tbl.1"));
+        assert!(files[4].ends_with("part.tbl.5"));
+        assert_eq!(catalog.input_files("nation").len(), 1);
+    }
+
+    #[test]
+    fn can_load_tpch_manifest() {
+        let mut catalog =
+            Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        assert_eq!(catalog.table_names().len(), 8);
+        assert_eq!(catalog.schema("lineitem").unwrap().col_count(), 16);
+        assert_eq!(catalog.schema("partsupp").unwrap().keys(), &vec![0usize, 1]);
+        catalog.set_scale(10);
+        assert_eq!(catalog.total_records("lineitem"), 60_000_000);
+        assert_eq!(catalog.total_records("nation"), 25);
+    }
+}
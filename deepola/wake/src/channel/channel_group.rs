@@ -1,13 +1,30 @@
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::data::DataMessage;
+use crate::data::{DataMessage, Payload};
+use crate::graph::NodeStats;
 
 use super::single_channel::*;
 
 /// A group of different input channels.
+///
+/// The reader keeps track of the channels that have sent EOF. This state is shared by its
+/// clones, so a channel read to EOF in `pre_process` is known to be finished in
+/// `process_stream`.
 #[derive(Debug)]
 pub struct MultiChannelReader<T: Send> {
     pub readers: Vec<Rc<ChannelReader<T>>>,
+
+    /// Whether the i-th channel has sent EOF.
+    eof: Rc<RefCell<Vec<bool>>>,
+
+    /// The channel [MultiChannelReader::read_any] polls first, so that no input is starved.
+    next: Rc<Cell<usize>>,
+
+    /// Notified by the writers of every channel, so that [MultiChannelReader::read_any] can
+    /// wait for any of them.
+    readiness: Arc<Readiness>,
 }
 
 impl<T: Send> Default for MultiChannelReader<T> {
@@ -18,13 +35,20 @@ impl<T: Send> Default for MultiChannelReader<T> {
 
 impl<T: Send> MultiChannelReader<T> {
     pub fn new() -> Self {
-        Self { readers: vec![] }
+        Self {
+            readers: vec![],
+            eof: Rc::new(RefCell::new(vec![])),
+            next: Rc::new(Cell::new(0)),
+            readiness: Arc::new(Readiness::default()),
+        }
     }
 
     /// Add a new reader. Individual channel readers can be created using
     /// the `Channel` struct.
     pub fn push(&mut self, reader: Rc<ChannelReader<T>>) {
-        self.readers.push(reader)
+        reader.notify_writes_to(self.readiness.clone());
+        self.readers.push(reader);
+        self.eof.borrow_mut().push(false);
     }
 
     /// Obtain the seq_no-th reader.
@@ -41,22 +65,87 @@ impl<T: Send> MultiChannelReader<T> {
             reader.channel_id(),
             message
         );
+        if message.is_eof() {
+            self.eof.borrow_mut()[seq_no] = true;
+        }
         message
     }
+
+    /// Read a message from whichever channel has one ready first, and return it with the
+    /// channel's seq_no. Blocks until a message is written to any of the channels.
+    ///
+    /// The channels are polled round-robin, starting after the one read last, so a busy input
+    /// cannot starve the others. EOF messages are returned like the others, after which
+    /// the channel is no longer read. Returns `None` once every channel has sent EOF.
+    pub fn read_any(&self) -> Option<(usize, DataMessage<T>)> {
+        let num_channels = self.readers.len();
+        loop {
+            if self.all_eof() {
+                return None;
+            }
+            // Read before polling, so that a message written meanwhile is not waited for.
+            let writes = self.readiness.writes();
+            for offset in 0..num_channels {
+                let seq_no = (self.next.get() + offset) % num_channels;
+                if self.is_eof(seq_no) {
+                    continue;
+                }
+                if let Some(message) = self.readers[seq_no].try_read() {
+                    log::debug!(
+                        "Read from (channel: {}). {:?}.",
+                        self.readers[seq_no].channel_id(),
+                        message
+                    );
+                    if message.is_eof() {
+                        self.eof.borrow_mut()[seq_no] = true;
+                    }
+                    self.next.set((seq_no + 1) % num_channels);
+                    return Some((seq_no, message));
+                }
+            }
+            self.readiness.wait_for_more(writes);
+        }
+    }
+
+    /// Whether the seq_no-th channel has sent EOF.
+    pub fn is_eof(&self, seq_no: usize) -> bool {
+        self.eof.borrow()[seq_no]
+    }
+
+    /// Whether every channel has sent EOF.
+    pub fn all_eof(&self) -> bool {
+        self.eof.borrow().iter().all(|eof| *eof)
+    }
+
+    /// Closes all the channels (see [ChannelReader::close]), e.g., once a node has all the
+    /// input it needs.
+    pub fn close(&self) {
+        for reader in &self.readers {
+            reader.close();
+        }
+    }
 }
 
 impl<T: Send> Clone for MultiChannelReader<T> {
     fn clone(&self) -> Self {
         Self {
             readers: self.readers.clone(),
+            eof: self.eof.clone(),
+            next: self.next.clone(),
+            readiness: self.readiness.clone(),
         }
     }
 }
 
+/// Where to record the data messages written, and how to count their rows.
+type StatsRecorder<T> = (Arc<NodeStats>, fn(&T) -> usize);
+
 /// A list of channels to broadcast to.
 #[derive(Debug)]
 pub struct MultiChannelBroadcaster<T: Send> {
     writers: Vec<ChannelWriter<T>>,
+
+    stats: Option<StatsRecorder<T>>,
 }
 
 impl<T: Send> Default for MultiChannelBroadcaster<T> {
@@ -67,7 +156,16 @@ impl<T: Send> Default for MultiChannelBroadcaster<T> {
 
 impl<T: Send> MultiChannelBroadcaster<T> {
     pub fn new() -> Self {
-        Self { writers: vec![] }
+        Self {
+            writers: vec![],
+            stats: None,
+        }
+    }
+
+    /// Records every data message written in `stats`.
+    pub fn with_stats(mut self, stats: Arc<NodeStats>, count_rows: fn(&T) -> usize) -> Self {
+        self.stats = Some((stats, count_rows));
+        self
     }
 
     /// Add a new writer.
@@ -82,12 +180,22 @@ impl<T: Send> MultiChannelBroadcaster<T> {
 
     /// Broadcast a message to all writers.
     pub fn write(&self, message: DataMessage<T>) {
+        if let (Some((stats, count_rows)), Payload::Some(dblock)) = (&self.stats, message.payload())
+        {
+            stats.record_message(count_rows(dblock.data()));
+        }
         for w in self.iter() {
             log::debug!("Writes to (channel: {}). {:?}.", w.channel_id(), message);
             w.write(message.clone())
         }
     }
 
+    /// Whether all the readers have closed their channels, so that nothing written would be
+    /// read.
+    pub fn is_closed(&self) -> bool {
+        !self.writers.is_empty() && self.writers.iter().all(|w| w.is_closed())
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &ChannelWriter<T>> + '_ {
         self.writers.iter()
     }
@@ -105,6 +213,62 @@ impl<T: Send> Clone for MultiChannelBroadcaster<T> {
     fn clone(&self) -> Self {
         Self {
             writers: self.writers.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn create_reader(
+        num_channels: usize,
+    ) -> (MultiChannelReader<String>, Vec<ChannelWriter<String>>) {
+        let mut reader = MultiChannelReader::new();
+        let mut writers = vec![];
+        for _ in 0..num_channels {
+            let (writer, channel_reader) = Channel::create::<String>();
+            reader.push(Rc::new(channel_reader));
+            writers.push(writer);
+        }
+        (reader, writers)
+    }
+
+    #[test]
+    fn reads_from_any_ready_channel() {
+        let (reader, writers) = create_reader(3);
+        writers[2].write(DataMessage::from("c".to_string()));
+        writers[1].write(DataMessage::eof());
+
+        let (seq_no, message) = reader.read_any().unwrap();
+        assert_eq!(seq_no, 1);
+        assert!(message.is_eof());
+        let (seq_no, message) = reader.read_any().unwrap();
+        assert_eq!(seq_no, 2);
+        assert_eq!(message.datablock().data(), "c");
+        assert!(reader.is_eof(1));
+        assert!(!reader.all_eof());
+
+        writers[2].write(DataMessage::eof());
+        let (seq_no, message) = reader.read_any().unwrap();
+        assert_eq!(seq_no, 2);
+        assert!(message.is_eof());
+
+        // Blocks until a message is written to the remaining channel.
+        let writer_0 = writers[0].clone();
+        let handle = thread::spawn(move || {
+            writer_0.write(DataMessage::from("a".to_string()));
+            writer_0.write(DataMessage::eof());
+        });
+        let mut seq_nos = vec![];
+        while let Some((seq_no, _)) = reader.clone().read_any() {
+            seq_nos.push(seq_no);
         }
+        handle.join().unwrap();
+        assert_eq!(seq_nos, vec![0, 0]);
+        assert!(reader.all_eof());
     }
 }
@@ -1,29 +1,185 @@
 use getset::Getters;
 use nanoid::nanoid;
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
 
-use crate::data::DataMessage;
+use crate::data::{DataMessage, EstimatedSize, Payload};
 
+/// The default number of messages a channel can hold before its writers block.
 const CHANNEL_SIZE: usize = 1000000;
 
+/// How much a channel can hold before its writers block. This bounds the memory used by the
+/// messages queued between a fast producer (e.g., a CSVReader) and a slow consumer.
+///
+/// A node reading its inputs one after another (e.g., a hash join reading its build side
+/// first) must not have a bounded input fed by the same upstream node as the input it reads
+/// first, since the upstream node would block on the former while the latter waits for EOF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelCapacity {
+    /// The maximum number of queued messages.
+    pub messages: usize,
+
+    /// The maximum estimated size, in bytes, of the queued DataBlocks (see [EstimatedSize]).
+    /// An empty channel accepts any message, so a DataBlock larger than the budget still goes
+    /// through.
+    pub bytes: Option<usize>,
+}
+
+impl Default for ChannelCapacity {
+    fn default() -> Self {
+        ChannelCapacity {
+            messages: CHANNEL_SIZE,
+            bytes: None,
+        }
+    }
+}
+
+impl ChannelCapacity {
+    /// A capacity of at most `bytes` bytes, whatever the number of messages.
+    pub fn bytes(bytes: usize) -> Self {
+        ChannelCapacity {
+            bytes: Some(bytes),
+            ..Default::default()
+        }
+    }
+}
+
 pub struct Channel;
 
 impl Channel {
-    pub fn create<T: Send>() -> (ChannelWriter<T>, ChannelReader<T>) {
-        let (channel_tx, channel_rx) = mpsc::sync_channel::<DataMessage<T>>(CHANNEL_SIZE);
+    pub fn create<T: EstimatedSize + Send + 'static>() -> (ChannelWriter<T>, ChannelReader<T>) {
+        let (channel_tx, channel_rx) = mpsc::channel::<(DataMessage<T>, usize)>();
         let channel_id = nanoid!(CHANNEL_ID_LEN, &CHANNEL_ID_ALPHABET);
+        let closed = Arc::new(AtomicBool::new(false));
+        let backlog = Arc::new(Backlog::default());
         let writer = ChannelWriter {
             channel_id: channel_id.clone(),
             channel_tx,
+            closed: closed.clone(),
+            backlog: backlog.clone(),
+            estimate_bytes: T::estimated_size,
         };
         let reader = ChannelReader {
             channel_id,
             channel_rx,
+            closed,
+            backlog,
         };
         (writer, reader)
     }
 }
 
+/// What is queued in a channel, shared by its reader and writers.
+#[derive(Debug, Default)]
+struct Backlog {
+    state: Mutex<BacklogState>,
+
+    /// Notified whenever a message is read or the capacity changes, to wake blocked writers.
+    changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct BacklogState {
+    messages: usize,
+    bytes: usize,
+    capacity: ChannelCapacity,
+
+    /// Set when the reader is dropped, so that the writers do not wait for it forever.
+    disconnected: bool,
+
+    /// The number of upstream nodes writing to the channel (see
+    /// [ChannelWriter::register_producer]), and the number of EOFs read from them so far.
+    producers: usize,
+    eofs: usize,
+
+    /// Notified after every message written, when the reader is part of a
+    /// [crate::channel::MultiChannelReader].
+    readiness: Option<Arc<Readiness>>,
+}
+
+impl BacklogState {
+    fn is_full(&self, bytes: usize) -> bool {
+        let too_many_bytes = match self.capacity.bytes {
+            Some(budget) => self.bytes > 0 && self.bytes + bytes > budget,
+            None => false,
+        };
+        self.messages >= self.capacity.messages || too_many_bytes
+    }
+}
+
+impl Backlog {
+    /// Blocks until a message of `bytes` bytes fits in the channel, and accounts for it.
+    /// Returns false, without accounting for it, if the channel has been closed meanwhile.
+    fn reserve(&self, bytes: usize, closed: &AtomicBool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        while state.is_full(bytes) && !state.disconnected {
+            if closed.load(Ordering::Relaxed) {
+                return false;
+            }
+            state = self.changed.wait(state).unwrap();
+        }
+        state.messages += 1;
+        state.bytes += bytes;
+        true
+    }
+
+    fn release(&self, bytes: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.messages -= 1;
+        state.bytes -= bytes;
+        self.changed.notify_all();
+    }
+
+    /// Accounts for an EOF read from the channel. Returns whether every producer has sent EOF,
+    /// i.e., whether the EOF must be passed to the reader.
+    fn is_last_eof(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.eofs += 1;
+        state.eofs >= state.producers
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BacklogState)) {
+        f(&mut self.state.lock().unwrap());
+        self.changed.notify_all();
+    }
+
+    /// Tells the reader waiting on the channel, if any, that a message was written.
+    fn notify_written(&self) {
+        let readiness = self.state.lock().unwrap().readiness.clone();
+        if let Some(readiness) = readiness {
+            readiness.notify();
+        }
+    }
+}
+
+/// Counts the messages written to a set of channels, so that the reader of all of them can
+/// block until any has a message (see [crate::channel::MultiChannelReader::read_any]).
+#[derive(Debug, Default)]
+pub(crate) struct Readiness {
+    writes: Mutex<u64>,
+    written: Condvar,
+}
+
+impl Readiness {
+    /// The number of messages written so far.
+    pub(crate) fn writes(&self) -> u64 {
+        *self.writes.lock().unwrap()
+    }
+
+    /// Blocks until more than `seen` messages have been written.
+    pub(crate) fn wait_for_more(&self, seen: u64) {
+        let mut writes = self.writes.lock().unwrap();
+        while *writes <= seen {
+            writes = self.written.wait(writes).unwrap();
+        }
+    }
+
+    fn notify(&self) {
+        *self.writes.lock().unwrap() += 1;
+        self.written.notify_all();
+    }
+}
+
 const CHANNEL_ID_ALPHABET: [char; 16] = [
     '1', '2', '3', '4', '5', '6', '7', '8', '9', '0', 'a', 'b', 'c', 'd', 'e', 'f',
 ];
@@ -35,16 +191,50 @@ pub struct ChannelWriter<T: Send> {
     #[getset(get = "pub")]
     channel_id: String,
 
-    channel_tx: mpsc::SyncSender<DataMessage<T>>,
+    /// The messages, with the estimated size of their DataBlocks.
+    channel_tx: mpsc::Sender<(DataMessage<T>, usize)>,
+
+    /// Set by the reader once it does not need more messages.
+    closed: Arc<AtomicBool>,
+
+    backlog: Arc<Backlog>,
+
+    estimate_bytes: fn(&T) -> usize,
 }
 
 impl<T: Send> ChannelWriter<T> {
+    /// Sends a message, unless the reader has closed the channel. Blocks while the channel is
+    /// full (see [ChannelCapacity]).
     pub fn write(&self, message: DataMessage<T>) {
-        match self.channel_tx.send(message) {
-            Ok(_) => (),
+        if self.is_closed() {
+            return;
+        }
+        let bytes = match message.payload() {
+            Payload::Some(dblock) => (self.estimate_bytes)(dblock.data()),
+            _ => 0,
+        };
+        if !self.backlog.reserve(bytes, &self.closed) {
+            return;
+        }
+        match self.channel_tx.send((message, bytes)) {
+            Ok(_) => self.backlog.notify_written(),
             Err(e) => panic!("{}", e.to_string()),
         }
     }
+
+    /// Whether the reader has closed the channel (see [ChannelReader::close]). Producers can
+    /// check it to stop work whose output would be discarded.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Records that one more upstream node writes to the channel through this writer (see
+    /// [crate::graph::ExecutionNode::subscribe_to_node]). The reader then receives a single
+    /// EOF, once every registered producer has sent its own. Without registered producers, the
+    /// first EOF is passed on.
+    pub fn register_producer(&self) {
+        self.backlog.update(|state| state.producers += 1);
+    }
 }
 
 impl<T: Send> Clone for ChannelWriter<T> {
@@ -52,6 +242,9 @@ impl<T: Send> Clone for ChannelWriter<T> {
         Self {
             channel_id: self.channel_id.clone(),
             channel_tx: self.channel_tx.clone(),
+            closed: self.closed.clone(),
+            backlog: self.backlog.clone(),
+            estimate_bytes: self.estimate_bytes,
         }
     }
 }
@@ -64,33 +257,95 @@ pub struct ChannelReader<T: Send> {
     #[getset(get = "pub")]
     channel_id: String,
 
-    channel_rx: mpsc::Receiver<DataMessage<T>>,
-}
+    channel_rx: mpsc::Receiver<(DataMessage<T>, usize)>,
 
-const EMPTY_CHANNEL_MSG: &str = "receiving on an empty channel";
+    closed: Arc<AtomicBool>,
+
+    backlog: Arc<Backlog>,
+}
 
 impl<T: Send> ChannelReader<T> {
+    /// Reads a message if one is ready, without blocking. Returns `None` if the channel is
+    /// empty.
     pub fn try_read(&self) -> Option<DataMessage<T>> {
-        match self.channel_rx.try_recv() {
-            Ok(v) => Some(v),
-            Err(e) => {
-                if e.to_string().eq(&EMPTY_CHANNEL_MSG.to_string()) {
-                    None
-                } else {
-                    panic!("{}", e.to_string())
+        loop {
+            match self.channel_rx.try_recv() {
+                Ok((message, bytes)) => {
+                    if let Some(message) = self.accept(message, bytes) {
+                        return Some(message);
+                    }
                 }
+                Err(mpsc::TryRecvError::Empty) => return None,
+                Err(e @ mpsc::TryRecvError::Disconnected) => panic!("{}", e),
             }
         }
     }
 
+    /// Reads the next message. When several producers write to the channel, only the EOF of
+    /// the last one to finish is returned (see [ChannelWriter::register_producer]).
     pub fn read(&self) -> DataMessage<T> {
-        match self.channel_rx.recv() {
-            Ok(m) => m,
-            Err(e) => {
-                panic!("{}", e.to_string())
+        loop {
+            match self.channel_rx.recv() {
+                Ok((message, bytes)) => {
+                    if let Some(message) = self.accept(message, bytes) {
+                        return message;
+                    }
+                }
+                Err(e) => {
+                    panic!("{}", e.to_string())
+                }
             }
         }
     }
+
+    /// Accounts for a message taken from the channel, and returns it unless it is the EOF of a
+    /// producer while others are still writing.
+    fn accept(&self, message: DataMessage<T>, bytes: usize) -> Option<DataMessage<T>> {
+        self.backlog.release(bytes);
+        if message.is_eof() && !self.backlog.is_last_eof() {
+            return None;
+        }
+        Some(message)
+    }
+
+    /// Tells the writers that no more messages are needed; the messages written afterwards
+    /// are dropped. This is the only signal going upstream, since messages only go
+    /// downstream. The channel must not be read after it is closed, since an EOF may never
+    /// come.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        // Wakes the writers blocked on a full channel, which then drop their messages.
+        self.backlog.update(|_| {});
+    }
+
+    /// Sets how much the channel can hold before its writers block. Defaults to 1,000,000
+    /// messages, whatever their size.
+    pub fn set_capacity(&self, capacity: ChannelCapacity) {
+        self.backlog.update(|state| state.capacity = capacity);
+    }
+
+    /// Notifies `readiness` of every message written to the channel from now on.
+    pub(crate) fn notify_writes_to(&self, readiness: Arc<Readiness>) {
+        self.backlog
+            .update(|state| state.readiness = Some(readiness));
+    }
+
+    pub fn capacity(&self) -> ChannelCapacity {
+        self.backlog.state.lock().unwrap().capacity
+    }
+
+    /// The number of messages, and the estimated size in bytes of their DataBlocks, written
+    /// but not read yet.
+    pub fn queued(&self) -> (usize, usize) {
+        let state = self.backlog.state.lock().unwrap();
+        (state.messages, state.bytes)
+    }
+}
+
+impl<T: Send> Drop for ChannelReader<T> {
+    fn drop(&mut self) {
+        self.backlog.update(|state| state.disconnected = true);
+    }
 }
 
 #[cfg(test)]
@@ -100,6 +355,59 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn drops_messages_once_closed() {
+        let (writer, reader) = Channel::create::<String>();
+        writer.write(DataMessage::from("a".to_string()));
+        assert!(!writer.is_closed());
+        reader.close();
+        assert!(writer.clone().is_closed());
+        writer.write(DataMessage::from("b".to_string()));
+        assert_eq!(reader.read().datablock().data(), "a");
+        assert!(reader.try_read().is_none());
+    }
+
+    #[test]
+    fn blocks_writers_over_capacity() {
+        let (writer, reader) = Channel::create::<String>();
+        let record_bytes = "a".to_string().estimated_size();
+        reader.set_capacity(ChannelCapacity::bytes(record_bytes + 1));
+        let (written_tx, written_rx) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            for record in ["a", "b", "c"] {
+                writer.write(DataMessage::from(record.to_string()));
+                written_tx.send(record).unwrap();
+            }
+            writer.write(DataMessage::eof());
+        });
+        assert_eq!(written_rx.recv().unwrap(), "a");
+        // The second record does not fit in the byte budget until the first one is read.
+        assert_eq!(reader.queued(), (1, record_bytes));
+        for record in ["a", "b", "c"] {
+            assert_eq!(reader.read().datablock().data(), record);
+        }
+        assert!(reader.read().is_eof());
+        handle.join().unwrap();
+        assert_eq!(reader.queued(), (0, 0));
+
+        let (writer, reader) = Channel::create::<String>();
+        reader.set_capacity(ChannelCapacity {
+            messages: 1,
+            bytes: None,
+        });
+        let (written_tx, written_rx) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            writer.write(DataMessage::from("a".to_string()));
+            written_tx.send(()).unwrap();
+            writer.write(DataMessage::from("b".to_string()));
+        });
+        written_rx.recv().unwrap();
+        // Closing the channel unblocks the writer, which drops its message.
+        reader.close();
+        handle.join().unwrap();
+        assert_eq!(reader.queued(), (1, record_bytes));
+    }
+
     #[test]
     fn can_move_into_threads() {
         let (reader, writer) = Channel::create::<String>();
@@ -24,6 +24,16 @@ impl ArrayRow {
         self.len() == 0
     }
 
+    /// Estimated size in bytes of the row and its values.
+    pub fn estimated_size(&self) -> usize {
+        std::mem::size_of::<ArrayRow>()
+            + self
+                .values
+                .iter()
+                .map(DataCell::estimated_size)
+                .sum::<usize>()
+    }
+
     pub fn slice_indices(&self, indices: &[usize]) -> Vec<DataCell> {
         indices.iter().map(|idx| self[*idx].clone()).collect()
     }
@@ -1,8 +1,9 @@
+use std::cmp::Ordering;
 use std::collections::hash_map::DefaultHasher;
 use std::convert::Infallible;
 use std::error::Error;
 use std::fmt;
-use std::hash::Hasher;
+use std::hash::{Hash, Hasher};
 use std::str;
 use std::str::FromStr;
 
@@ -17,7 +18,10 @@ pub enum DataType {
     Null,
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A value of a record. Unlike f64, cells are Eq, Ord and Hash, e.g., to be used as group keys:
+/// Float cells are equal if their values are, or if both are NaN, which sorts after all the
+/// other floats. Cells of different types are ordered by type.
+#[derive(Debug, Clone)]
 pub enum DataCell {
     Boolean(bool),
     UnsignedInt(usize),
@@ -28,43 +32,89 @@ pub enum DataCell {
     Null(),
 }
 
-impl DataCell {
-    // Hasher for a single DataCell
-    pub fn hash(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
+impl PartialEq for DataCell {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (DataCell::Boolean(a), DataCell::Boolean(b)) => a == b,
+            (DataCell::UnsignedInt(a), DataCell::UnsignedInt(b)) => a == b,
+            (DataCell::Integer(a), DataCell::Integer(b)) => a == b,
+            (DataCell::Float(a), DataCell::Float(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (DataCell::Text(a), DataCell::Text(b)) => a == b,
+            (DataCell::Tuple(a), DataCell::Tuple(b)) => a == b,
+            (DataCell::Null(), DataCell::Null()) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for DataCell {}
+
+impl PartialOrd for DataCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DataCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (DataCell::Boolean(a), DataCell::Boolean(b)) => a.cmp(b),
+            (DataCell::UnsignedInt(a), DataCell::UnsignedInt(b)) => a.cmp(b),
+            (DataCell::Integer(a), DataCell::Integer(b)) => a.cmp(b),
+            (DataCell::Float(a), DataCell::Float(b)) => {
+                normalize_float(*a).total_cmp(&normalize_float(*b))
+            }
+            (DataCell::Text(a), DataCell::Text(b)) => a.cmp(b),
+            (DataCell::Tuple(a), DataCell::Tuple(b)) => a.cmp(b),
+            (DataCell::Null(), DataCell::Null()) => Ordering::Equal,
+            _ => self.dtype_order().cmp(&other.dtype_order()),
+        }
+    }
+}
+
+/// The float with the same bits as all the floats equal to `a`: 0.0 for -0.0, and a positive
+/// NaN for all NaNs, so that comparing and hashing bits is consistent with eq.
+fn normalize_float(a: f64) -> f64 {
+    if a == 0.0 {
+        0.0
+    } else if a.is_nan() {
+        f64::NAN
+    } else {
+        a
+    }
+}
+
+impl Hash for DataCell {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
         match self {
-            DataCell::Boolean(a) => hasher.write_u8(*a as u8),
-            DataCell::UnsignedInt(a) => hasher.write_usize(*a),
-            DataCell::Integer(a) => hasher.write_i32(*a),
-            DataCell::Float(_a) => {}
-            DataCell::Text(a) => hasher.write(a.as_bytes()),
+            DataCell::Boolean(a) => a.hash(state),
+            DataCell::UnsignedInt(a) => a.hash(state),
+            DataCell::Integer(a) => a.hash(state),
+            DataCell::Float(a) => normalize_float(*a).to_bits().hash(state),
+            DataCell::Text(a) => a.hash(state),
             DataCell::Tuple(a) => {
-                hasher.write_u64((*a).0.hash());
-                hasher.write_u64((*a).1.hash());
+                Hash::hash(&a.0, state);
+                Hash::hash(&a.1, state);
             }
-            _ => {}
+            DataCell::Null() => {}
         }
+    }
+}
+
+impl DataCell {
+    // Hasher for a single DataCell
+    pub fn single_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        Hash::hash(self, &mut hasher);
         hasher.finish()
     }
 
-    // Currently doesn't support hashing for f64 fields.
-    // It can be supported by converting f64 into its (exponent, mantissa) form and hashing three of them.
     // Hasher for a vector of DataCell
     pub fn vector_hash(cells: Vec<DataCell>) -> u64 {
         let mut hasher = DefaultHasher::new();
         for cell in cells {
-            match cell {
-                DataCell::Boolean(a) => hasher.write_u8(a as u8),
-                DataCell::UnsignedInt(a) => hasher.write_usize(a),
-                DataCell::Integer(a) => hasher.write_i32(a),
-                DataCell::Float(_a) => {}
-                DataCell::Text(a) => hasher.write(a.as_bytes()),
-                DataCell::Tuple(a) => {
-                    hasher.write_u64((*a).0.hash());
-                    hasher.write_u64((*a).1.hash());
-                }
-                _ => {}
-            }
+            Hash::hash(&cell, &mut hasher);
         }
         hasher.finish()
     }
@@ -262,6 +312,30 @@ impl DataCell {
             DataCell::Null() => DataType::Null,
         }
     }
+
+    /// Position of the cell's type in the declaration of DataCell, to order cells of
+    /// different types.
+    fn dtype_order(&self) -> u8 {
+        match self {
+            DataCell::Boolean(_a) => 0,
+            DataCell::UnsignedInt(_a) => 1,
+            DataCell::Integer(_a) => 2,
+            DataCell::Float(_a) => 3,
+            DataCell::Text(_a) => 4,
+            DataCell::Tuple(_a) => 5,
+            DataCell::Null() => 6,
+        }
+    }
+
+    /// Estimated size in bytes of the cell, including the text or tuple it points to.
+    pub fn estimated_size(&self) -> usize {
+        let heap = match self {
+            DataCell::Text(a) => a.len(),
+            DataCell::Tuple(a) => a.0.estimated_size() + a.1.estimated_size(),
+            _ => 0,
+        };
+        std::mem::size_of::<DataCell>() + heap
+    }
 }
 
 impl FromStr for DataCell {
@@ -271,6 +345,21 @@ impl FromStr for DataCell {
     }
 }
 
+impl FromStr for DataType {
+    type Err = Box<dyn Error>;
+    /// Parses the name of a variant, e.g., "Integer" or "text" (case-insensitive).
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "boolean" => Ok(DataType::Boolean),
+            "unsignedint" => Ok(DataType::UnsignedInt),
+            "integer" => Ok(DataType::Integer),
+            "float" => Ok(DataType::Float),
+            "text" => Ok(DataType::Text),
+            _ => Err(format!("Invalid DataType: {}", value).into()),
+        }
+    }
+}
+
 impl fmt::Display for DataCell {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let result = match self {
@@ -462,11 +551,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_parse_datatype() {
+        assert_eq!(DataType::from_str("Integer").unwrap(), DataType::Integer);
+        assert_eq!(DataType::from_str("text").unwrap(), DataType::Text);
+        assert_eq!(DataType::from_str("FLOAT").unwrap(), DataType::Float);
+        assert!(DataType::from_str("Tuple").is_err());
+    }
+
     #[test]
     fn can_hash_datacell() {
         let cell1 = DataCell::Integer(1);
         let cell2 = DataCell::Integer(1);
-        assert_eq!(cell1.hash(), cell2.hash());
+        assert_eq!(cell1.single_hash(), cell2.single_hash());
         assert_eq!(
             DataCell::vector_hash(vec![DataCell::Integer(1), DataCell::from("hello")]),
             DataCell::vector_hash(vec![DataCell::Integer(1), DataCell::from("hello")])
@@ -475,5 +572,46 @@ mod tests {
             DataCell::vector_hash(vec![DataCell::Integer(1), DataCell::from("hello")]),
             DataCell::vector_hash(vec![DataCell::Integer(1), DataCell::from("hello ")])
         );
+        assert_ne!(
+            DataCell::Float(1.5).single_hash(),
+            DataCell::Float(2.5).single_hash()
+        );
+        assert_eq!(
+            DataCell::Float(0.0).single_hash(),
+            DataCell::Float(-0.0).single_hash()
+        );
+        assert_eq!(DataCell::Float(f64::NAN), DataCell::Float(f64::NAN));
+        assert_eq!(
+            DataCell::Float(f64::NAN).single_hash(),
+            DataCell::Float(-f64::NAN).single_hash()
+        );
+        assert_ne!(DataCell::Integer(1), DataCell::Float(1.0));
+
+        let mut groups = std::collections::HashMap::new();
+        for key in [1.5, 2.5, 1.5, f64::NAN, f64::NAN] {
+            *groups.entry(vec![DataCell::Float(key)]).or_insert(0) += 1;
+        }
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[&vec![DataCell::Float(1.5)]], 2);
+        assert_eq!(groups[&vec![DataCell::Float(f64::NAN)]], 2);
+    }
+
+    #[test]
+    fn can_order_datacell() {
+        let nan = DataCell::Float(f64::NAN);
+        assert_eq!(
+            nan.partial_cmp(&DataCell::Float(-f64::NAN)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            DataCell::Float(0.0).partial_cmp(&DataCell::Float(-0.0)),
+            Some(Ordering::Equal)
+        );
+        assert!(DataCell::Float(f64::INFINITY) < nan);
+        assert!(DataCell::Float(-1.0) < DataCell::Float(0.5));
+        assert!(DataCell::from("a") < DataCell::from("b"));
+        // cells of different types are ordered by type
+        assert!(DataCell::Integer(5) < DataCell::Float(1.0));
+        assert!(DataCell::Float(1.0) < DataCell::Null());
     }
 }
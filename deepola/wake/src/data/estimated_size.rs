@@ -0,0 +1,107 @@
+use polars::prelude::DataFrame;
+
+use super::{ArrayRow, DataCell, KeyValue, KeyValueList};
+
+/// The size of a message payload, as used for node statistics and channel byte limits.
+pub trait EstimatedSize {
+    /// Number of rows held by the value. Most types hold a single record.
+    fn row_count(&self) -> usize {
+        1
+    }
+
+    /// Estimated size in bytes of the value, including the heap data it owns.
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+impl EstimatedSize for DataFrame {
+    fn row_count(&self) -> usize {
+        self.height()
+    }
+
+    fn estimated_size(&self) -> usize {
+        DataFrame::estimated_size(self)
+    }
+}
+
+impl EstimatedSize for ArrayRow {
+    fn estimated_size(&self) -> usize {
+        ArrayRow::estimated_size(self)
+    }
+}
+
+impl EstimatedSize for DataCell {
+    fn estimated_size(&self) -> usize {
+        DataCell::estimated_size(self)
+    }
+}
+
+/// A block of records, e.g., `Vec<ArrayRow>`.
+impl<T: EstimatedSize> EstimatedSize for Vec<T> {
+    fn row_count(&self) -> usize {
+        self.len()
+    }
+
+    fn estimated_size(&self) -> usize {
+        self.iter().map(T::estimated_size).sum()
+    }
+}
+
+impl EstimatedSize for String {
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of::<String>() + self.len()
+    }
+}
+
+impl EstimatedSize for KeyValue {
+    fn estimated_size(&self) -> usize {
+        self.key().estimated_size() + self.value().estimated_size()
+    }
+}
+
+impl EstimatedSize for KeyValueList {
+    fn row_count(&self) -> usize {
+        self.data().len()
+    }
+
+    fn estimated_size(&self) -> usize {
+        self.data().estimated_size()
+    }
+}
+
+impl EstimatedSize for bool {}
+impl EstimatedSize for i32 {}
+impl EstimatedSize for i64 {}
+impl EstimatedSize for u32 {}
+impl EstimatedSize for u64 {}
+impl EstimatedSize for usize {}
+impl EstimatedSize for f64 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    #[test]
+    fn can_count_rows() {
+        let df = df!("a" => &[1, 2, 3]).unwrap();
+        assert_eq!(df.row_count(), 3);
+        assert_eq!(ArrayRow::from_example().row_count(), 2);
+        assert_eq!("record".to_string().row_count(), 1);
+    }
+
+    #[test]
+    fn can_estimate_size() {
+        let df = df!("a" => &[1i64, 2, 3]).unwrap();
+        assert!(EstimatedSize::estimated_size(&df) >= 3 * 8);
+        let rows = ArrayRow::from_example();
+        assert!(rows.estimated_size() > rows[..1].to_vec().estimated_size());
+        assert!(rows.estimated_size() >= 2 * 3 * std::mem::size_of::<DataCell>());
+        assert_eq!(7u32.estimated_size(), 4);
+        assert_eq!(
+            "abc".to_string().estimated_size(),
+            std::mem::size_of::<String>() + 3
+        );
+    }
+}
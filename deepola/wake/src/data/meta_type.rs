@@ -7,6 +7,12 @@ pub const DATABLOCK_TYPE: &str = "reserved.type";
 pub const DATABLOCK_CARDINALITY: &str = "reserved.cardinality";
 pub const DATABLOCK_TOTAL_RECORDS: &str = "reserved.total_blocks";
 
+/// Fraction of its input files, in bytes, that a scan has read when it produced a DataBlock.
+/// Operators deriving DataBlocks from others pass it on (see [super::DataBlock::progress]).
+pub const DATABLOCK_PROGRESS: &str = "reserved.progress";
+/// Number of records a scan has read when it produced a DataBlock, including its own.
+pub const DATABLOCK_RECORDS_READ: &str = "reserved.records_read";
+
 pub const DATABLOCK_TYPE_DM: &str = "dm";
 pub const DATABLOCK_TYPE_DA: &str = "da";
 
@@ -1,6 +1,7 @@
 mod arithmetic;
 mod array_row;
 mod data_type;
+mod estimated_size;
 mod kv;
 mod message;
 mod meta_type;
@@ -9,6 +10,7 @@ mod schema;
 
 pub use array_row::*;
 pub use data_type::*;
+pub use estimated_size::*;
 pub use kv::*;
 pub use message::*;
 pub use meta_type::*;
@@ -1,7 +1,7 @@
 use getset::Getters;
 use std::{collections::HashMap, fmt::Debug, sync::Arc};
 
-use super::{MetaCell, Schema, SCHEMA_META_NAME};
+use super::{MetaCell, Schema, DATABLOCK_PROGRESS, DATABLOCK_RECORDS_READ, SCHEMA_META_NAME};
 
 /// Either actual data (`DataBlock`) or other special signals (e.g., EOF, Signal).
 ///
@@ -99,6 +99,27 @@ impl<T> DataBlock<T> {
     pub fn schema(&self) -> &Schema {
         self.metadata().get(SCHEMA_META_NAME).unwrap().to_schema()
     }
+
+    /// Fraction of the input read when this DataBlock was produced, if known (see
+    /// [DATABLOCK_PROGRESS]).
+    pub fn progress(&self) -> Option<f64> {
+        self.metadata().get(DATABLOCK_PROGRESS).map(f64::from)
+    }
+
+    /// Number of records read when this DataBlock was produced, if known (see
+    /// [DATABLOCK_RECORDS_READ]).
+    pub fn records_read(&self) -> Option<f64> {
+        self.metadata().get(DATABLOCK_RECORDS_READ).map(f64::from)
+    }
+
+    /// The progress entries of the metadata, for the DataBlocks computed from this one.
+    pub fn progress_metadata(&self) -> HashMap<String, MetaCell> {
+        self.metadata()
+            .iter()
+            .filter(|(name, _)| *name == DATABLOCK_PROGRESS || *name == DATABLOCK_RECORDS_READ)
+            .map(|(name, cell)| (name.clone(), cell.clone()))
+            .collect()
+    }
 }
 
 impl<T> From<T> for DataBlock<T> {
@@ -166,4 +187,21 @@ mod tests {
         }
         panic!("{}", "not expected to reach here");
     }
+
+    #[test]
+    fn can_pass_on_progress() {
+        let metadata = HashMap::from([
+            ("key".into(), MetaCell::Text("value".to_string())),
+            (DATABLOCK_PROGRESS.into(), MetaCell::from(0.25)),
+            (DATABLOCK_RECORDS_READ.into(), MetaCell::from(100.0)),
+        ]);
+        let dblock = DataBlock::new(vec![19241], metadata);
+        assert_eq!(dblock.progress(), Some(0.25));
+        assert_eq!(dblock.records_read(), Some(100.0));
+
+        let derived = DataBlock::new(vec![1], dblock.progress_metadata());
+        assert_eq!(derived.metadata().len(), 2);
+        assert_eq!(derived.progress(), Some(0.25));
+        assert_eq!(DataBlock::from(vec![1]).progress(), None);
+    }
 }
@@ -94,6 +94,12 @@ impl Schema {
         self.columns[index].clone()
     }
 
+    /// Parses the `CREATE TABLE` statements of a DDL script. Returns the schemas keyed by
+    /// (lowercased) table name; PRIMARY KEY columns are marked as keys.
+    pub fn from_ddl(ddl: &str) -> Result<HashMap<String, Schema>, Box<dyn Error>> {
+        crate::sql::parse_create_tables(ddl)
+    }
+
     // Function to create and test examples of Schema creation
     pub fn from_example(table: &str) -> Result<Schema, Box<dyn Error>> {
         match table {
@@ -195,4 +201,24 @@ mod tests {
         );
         assert_eq!(schema.keys(), &vec![1usize, 2]);
     }
+
+    #[test]
+    fn can_create_schema_from_ddl() {
+        let ddl = std::fs::read_to_string("resources/tpc-h/queries/tpch-create.sql").unwrap();
+        let schemas = Schema::from_ddl(&ddl).unwrap();
+        assert_eq!(schemas.len(), 8);
+
+        let lineitem = &schemas["lineitem"];
+        let expected = Schema::from_example("lineitem").unwrap();
+        let names: Vec<&String> = lineitem.columns.iter().map(|c| &c.name).collect();
+        let expected_names: Vec<&String> = expected.columns.iter().map(|c| &c.name).collect();
+        assert_eq!(names, expected_names);
+        assert_eq!(lineitem.keys(), &vec![0usize, 3]);
+        assert_eq!(lineitem.dtype("l_extendedprice"), DataType::Float);
+        assert_eq!(lineitem.dtype("l_shipdate"), DataType::Text);
+
+        assert_eq!(schemas["part"].keys(), &vec![0usize]);
+        assert_eq!(schemas["part"].dtype("p_partkey"), DataType::Integer);
+        assert_eq!(schemas["partsupp"].keys(), &vec![0usize, 1]);
+    }
 }
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use polars::prelude::*;
+
+use crate::data::DataBlock;
+use crate::forecast::cell::*;
+use crate::forecast::table::{EstimatorFactory, ForecastKey, TableForecast, TimeAxis};
+use crate::forecast::TimeType;
+use crate::forecast::ValueType;
+use crate::graph::NodeReader;
+
+/// Reads every snapshot from `output_reader` until EOF, with its metadata (e.g., progress).
+pub fn record_snapshots(output_reader: &NodeReader<DataFrame>) -> Vec<DataBlock<DataFrame>> {
+    let mut snapshots = vec![];
+    loop {
+        let message = output_reader.read();
+        if message.is_eof() {
+            break;
+        }
+        snapshots.push(message.datablock().clone());
+    }
+    snapshots
+}
+
+/// The estimators a [Backtest] compares by default.
+pub fn default_estimators() -> Vec<(String, EstimatorFactory)> {
+    let estimators: [(&str, EstimatorFactory); 11] = [
+        ("selector", ForecastSelector::make_with_default_candidates),
+        ("aicc", || {
+            ForecastSelector::make_with_selection(Selection::Aicc)
+        }),
+        ("bic", || {
+            ForecastSelector::make_with_selection(Selection::Bic)
+        }),
+        ("ensemble", || {
+            ForecastSelector::make_with_selection(Selection::Ensemble)
+        }),
+        ("tail", || Box::new(TailEstimator::default())),
+        ("mean", || Box::new(MeanEstimator::default())),
+        ("ses", || {
+            Box::new(SimpleExponentSmoothEstimator::with_base(0.75))
+        }),
+        ("affine", || Box::new(LeastSquareAffineEstimator::default())),
+        ("holt", || Box::new(HoltEstimator::linear(0.5, 0.5))),
+        ("damped", || Box::new(HoltEstimator::damped(0.5, 0.5, 0.9))),
+        ("power_law", || {
+            Box::new(PowerLawEstimator::with_exponent(1.0))
+        }),
+    ];
+    estimators
+        .into_iter()
+        .map(|(name, make)| (name.to_string(), make))
+        .collect()
+}
+
+/// Measures how close forecasts from the snapshots of a query come to its final answer (the
+/// last snapshot) over time. The snapshots are replayed through each estimator, as a
+/// [crate::forecast::table::ForecastNode] would, and through none as a baseline ("observed").
+///
+/// The report has a row per estimator, snapshot and forecast column, with the mean over the
+/// groups of the final answer of the relative error `|forecast - final| / |final|` (the absolute
+/// error where the final value is 0). Groups missing from a snapshot are ignored.
+///
+/// Example:
+/// ```no_run
+/// use wake::forecast::backtest::{record_snapshots, Backtest};
+/// # let output_reader = wake::graph::NodeReader::empty();
+///
+/// let snapshots = record_snapshots(&output_reader);
+/// let report = Backtest::new().run(&snapshots);
+/// ```
+pub struct Backtest {
+    group_key: Vec<String>,
+    value_columns: Vec<String>,
+    time_axis: TimeAxis,
+    final_time: Option<TimeType>,
+    estimators: Vec<(String, EstimatorFactory)>,
+}
+
+impl Default for Backtest {
+    fn default() -> Self {
+        Backtest {
+            group_key: vec![],
+            value_columns: vec![],
+            time_axis: TimeAxis::Progress,
+            final_time: None,
+            estimators: default_estimators(),
+        }
+    }
+}
+
+impl Backtest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The columns identifying a group. Defaults to the non-numeric columns of the final answer.
+    pub fn group_key(&mut self, group_key: Vec<String>) -> &mut Self {
+        self.group_key = group_key;
+        self
+    }
+
+    /// The columns to forecast. Defaults to all the numeric columns not in the group key.
+    pub fn value_columns(&mut self, value_columns: Vec<String>) -> &mut Self {
+        self.value_columns = value_columns;
+        self
+    }
+
+    pub fn time_axis(&mut self, time_axis: TimeAxis) -> &mut Self {
+        self.time_axis = time_axis;
+        self
+    }
+
+    /// The time of the final answer, on the time axis (see
+    /// [TimeAxis::final_time_or_default]).
+    pub fn final_time(&mut self, final_time: TimeType) -> &mut Self {
+        self.final_time = Some(final_time);
+        self
+    }
+
+    /// The estimators to compare, by name. Defaults to [default_estimators].
+    pub fn estimators(&mut self, estimators: Vec<(String, EstimatorFactory)>) -> &mut Self {
+        self.estimators = estimators;
+        self
+    }
+
+    /// Replays `snapshots` through each estimator, and reports their errors (see [Backtest]).
+    pub fn run(&self, snapshots: &[DataBlock<DataFrame>]) -> DataFrame {
+        let final_df = snapshots.last().expect("Backtest: no snapshot").data();
+        let group_key = if self.group_key.is_empty() {
+            final_df
+                .get_columns()
+                .iter()
+                .filter(|series| !series.dtype().is_numeric())
+                .map(|series| series.name().to_string())
+                .collect()
+        } else {
+            self.group_key.clone()
+        };
+        let final_time = self.time_axis.final_time_or_default(self.final_time);
+        let make_table_forecast = |estimator: Option<EstimatorFactory>| {
+            let mut table_forecast = TableForecast::new(
+                group_key.clone(),
+                self.value_columns.clone(),
+                self.time_axis,
+                final_time,
+                None,
+                Selection::default(),
+            );
+            if let Some(make) = estimator {
+                table_forecast.set_estimator(make);
+            }
+            table_forecast
+        };
+
+        // the final answer of each group
+        let final_forecast = make_table_forecast(None);
+        let values_columns = final_forecast.get_values_columns(final_df);
+        let final_values: HashMap<ForecastKey, Vec<ValueType>> = final_forecast
+            .row_keys(final_df)
+            .into_iter()
+            .zip(TableForecast::row_values(final_df, &values_columns))
+            .collect();
+
+        let mut report = Report::default();
+        let candidates = std::iter::once(("observed", None)).chain(
+            self.estimators
+                .iter()
+                .map(|(name, make)| (name.as_str(), Some(*make))),
+        );
+        for (name, estimator) in candidates {
+            let table_forecast = make_table_forecast(estimator);
+            for (i, dblock) in snapshots.iter().enumerate() {
+                let time = table_forecast.get_time(dblock, i + 1);
+                let df = match estimator {
+                    Some(_) => table_forecast.forecast(dblock.data(), time),
+                    None => dblock.data().clone(),
+                };
+                let errors = mean_relative_errors(
+                    values_columns.len(),
+                    table_forecast.row_keys(&df),
+                    TableForecast::row_values(&df, &values_columns),
+                    &final_values,
+                );
+                for (column, error) in values_columns.iter().zip(errors) {
+                    report.estimator.push(name.to_string());
+                    report.snapshot.push(i as u32 + 1);
+                    report.time.push(time);
+                    report.column.push(column.clone());
+                    report.error.push(error);
+                }
+            }
+        }
+        report.into_df()
+    }
+}
+
+/// The mean relative error of each of the `num_values` values over the rows whose group has a
+/// final value (NaN if there is none).
+fn mean_relative_errors(
+    num_values: usize,
+    keys: Vec<ForecastKey>,
+    values: Vec<Vec<ValueType>>,
+    final_values: &HashMap<ForecastKey, Vec<ValueType>>,
+) -> Vec<ValueType> {
+    let mut error_sums = vec![0.0; num_values];
+    let mut num_groups = 0;
+    for (key, row_values) in keys.iter().zip(values) {
+        let final_row_values = match final_values.get(key) {
+            Some(final_row_values) => final_row_values,
+            None => continue,
+        };
+        for (sum, (value, final_value)) in error_sums
+            .iter_mut()
+            .zip(row_values.iter().zip(final_row_values))
+        {
+            let error = (value - final_value).abs();
+            *sum += if *final_value == 0.0 {
+                error
+            } else {
+                error / final_value.abs()
+            };
+        }
+        num_groups += 1;
+    }
+    error_sums
+        .into_iter()
+        .map(|sum| sum / num_groups as ValueType)
+        .collect()
+}
+
+/// The columns of a backtest report.
+#[derive(Default)]
+struct Report {
+    estimator: Vec<String>,
+    snapshot: Vec<u32>,
+    time: Vec<TimeType>,
+    column: Vec<String>,
+    error: Vec<ValueType>,
+}
+
+impl Report {
+    fn into_df(self) -> DataFrame {
+        fn as_strs(strings: &[String]) -> Vec<&str> {
+            strings.iter().map(String::as_str).collect()
+        }
+        df!(
+            "estimator" => as_strs(&self.estimator),
+            "snapshot" => self.snapshot,
+            "time" => self.time,
+            "column" => as_strs(&self.column),
+            "error" => self.error,
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::data::{MetaCell, DATABLOCK_PROGRESS};
+
+    #[test]
+    fn can_backtest_estimators() {
+        // the populations of two cities grow linearly with the progress
+        let progress = [0.2, 0.4, 0.6, 0.8, 1.0];
+        let snapshots: Vec<DataBlock<DataFrame>> = progress
+            .iter()
+            .map(|p| {
+                let df = df!(
+                    "city" => &["Champaign", "Urbana"],
+                    "population" => &[p * 1000.0, p * 500.0],
+                )
+                .unwrap();
+                let metadata = HashMap::from([(DATABLOCK_PROGRESS.into(), MetaCell::from(*p))]);
+                DataBlock::new(df, metadata)
+            })
+            .collect();
+        let estimators: Vec<(String, EstimatorFactory)> = vec![
+            ("affine".into(), || {
+                Box::new(LeastSquareAffineEstimator::default())
+            }),
+            ("tail".into(), || Box::new(TailEstimator::default())),
+        ];
+        let report = Backtest::new().estimators(estimators).run(&snapshots);
+        assert_eq!(report.height(), 3 * progress.len());
+
+        let errors = |estimator: &str| -> Vec<f64> {
+            let mask = report
+                .column("estimator")
+                .unwrap()
+                .equal(estimator)
+                .unwrap();
+            let rows = report.filter(&mask).unwrap();
+            let columns = rows.column("column").unwrap().utf8().unwrap();
+            assert!(columns
+                .into_no_null_iter()
+                .all(|column| column == "population"));
+            rows.column("error")
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect()
+        };
+        // the observed values are short by the fraction left to read
+        let observed = errors("observed");
+        for (error, p) in observed.iter().zip(progress) {
+            assert!((error - (1.0 - p)).abs() < 1e-9, "{} at {}", error, p);
+        }
+        // a line is found from the second snapshot on
+        let affine = errors("affine");
+        assert!((affine[0] - 0.8).abs() < 1e-9);
+        for error in &affine[1..] {
+            assert!(error.abs() < 1e-9);
+        }
+        assert_eq!(errors("tail"), observed);
+    }
+}
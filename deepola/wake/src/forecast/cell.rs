@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fmt::Debug;
 
 use crate::forecast::score::Score;
@@ -6,7 +7,6 @@ use crate::forecast::TimeType;
 use crate::forecast::TimeValue;
 use crate::forecast::ValueType;
 
-
 /* Common traits */
 
 /// Forecastor for single-valued time series
@@ -15,6 +15,18 @@ pub trait CellForecast: Debug {
     fn predict(&self, time: ValueType) -> ValueType;
     /// Get complexity (e.g. number of parameters) of this forecaster
     fn complexity(&self) -> ValueType;
+    /// Variance of the prediction error at specific time, estimated from the residuals of the
+    /// fit. Forecasters without an error model predict with no uncertainty.
+    fn variance(&self, _time: ValueType) -> ValueType {
+        0.0
+    }
+    /// Prediction interval at specific time: `z` standard deviations around the prediction
+    /// (e.g., 1.96 for 95% with normal errors)
+    fn interval(&self, time: ValueType, z: ValueType) -> (ValueType, ValueType) {
+        let prediction = self.predict(time);
+        let margin = z * self.variance(time).sqrt();
+        (prediction - margin, prediction + margin)
+    }
 }
 
 /// Struct that can consume time-value pairs
@@ -35,7 +47,8 @@ pub trait CellEstimator: CellConsumer + Send {
 }
 
 /// Model estimator that iteratively fit and generates CellForecast
-pub trait Averager: CellConsumer + Send {  // mostly to reuse averaging logics
+pub trait Averager: CellConsumer + Send {
+    // mostly to reuse averaging logics
     /// Average of time-series so far
     fn average(&self) -> ValueType;
 }
@@ -43,22 +56,22 @@ pub trait Averager: CellConsumer + Send {  // mostly to reuse averaging logics
 /* More score constructor */
 
 impl Score {
-
     /// construct score for model with i.i.d. normal distributed error
-    pub fn least_square (
-        complexity: ValueType,
-        est: &dyn CellForecast,
-        series: Series
-    ) -> Score {
+    pub fn least_square(complexity: ValueType, est: &dyn CellForecast, series: Series) -> Score {
         // residual sum of squares
-        let rss: ValueType = series.iter().map(|tv| {
-            let v_pred = est.predict(tv.t);
-            (tv.v - v_pred).powi(2)
-        }).sum();
+        let rss: ValueType = series
+            .iter()
+            .map(|tv| {
+                let v_pred = est.predict(tv.t);
+                (tv.v - v_pred).powi(2)
+            })
+            .sum();
 
         // relative log likelihood (ignore model-inpendent terms)
         let n: ValueType = series.len() as ValueType;
-        let log_likelihood = - n * (rss / n).ln() / 2.0;  // assume normal error
+        // floor perfect fits so that their complexities still break ties
+        let mse = (rss / n).max(ValueType::MIN_POSITIVE);
+        let log_likelihood = -n * mse.ln() / 2.0; // assume normal error
         Score::new(complexity, log_likelihood, n)
     }
 }
@@ -69,11 +82,16 @@ impl Score {
 #[derive(Debug)]
 pub struct ConstantForecast {
     mean: ValueType,
+    variance: ValueType,
 }
 
 impl ConstantForecast {
     fn new(mean: ValueType) -> ConstantForecast {
-        ConstantForecast { mean }
+        ConstantForecast::with_variance(mean, 0.0)
+    }
+
+    fn with_variance(mean: ValueType, variance: ValueType) -> ConstantForecast {
+        ConstantForecast { mean, variance }
     }
 }
 
@@ -83,10 +101,13 @@ impl CellForecast for ConstantForecast {
     }
 
     fn complexity(&self) -> ValueType {
-        1.0  // mean
+        1.0 // mean
     }
-}
 
+    fn variance(&self, _time: ValueType) -> ValueType {
+        self.variance
+    }
+}
 
 /* Last value */
 
@@ -113,7 +134,6 @@ impl Averager for TailEstimator {
     }
 }
 
-
 /* Average over all observation */
 
 #[derive(Default, Debug)]
@@ -141,7 +161,6 @@ impl Averager for MeanEstimator {
     }
 }
 
-
 /* Exponentially weighted average */
 // v(T) = (\sum_{i=1}^n alpha^{(T - t_i) / freq} v_i) / (\sum_{i=1}^n alpha^{(T - t_i))
 // TODO: fitting base automatically
@@ -153,6 +172,9 @@ pub struct SimpleExponentSmoothEstimator {
     last_time: TimeType,
     sum: ValueType,
     total_weight: ValueType,
+    // exponentially weighted squared errors of the average before each observation
+    sq_err_sum: ValueType,
+    sq_err_weight: ValueType,
 }
 
 impl SimpleExponentSmoothEstimator {
@@ -166,7 +188,17 @@ impl SimpleExponentSmoothEstimator {
             last_time: 0.0,
             sum: 0.0,
             total_weight: 0.0,
+            sq_err_sum: 0.0,
+            sq_err_weight: 0.0,
+        }
+    }
+
+    /// Exponentially weighted mean of the squared one-step-ahead errors
+    pub fn error_variance(&self) -> ValueType {
+        if self.sq_err_weight == 0.0 {
+            return 0.0;
         }
+        self.sq_err_sum / self.sq_err_weight
     }
 }
 
@@ -177,6 +209,11 @@ impl CellConsumer for SimpleExponentSmoothEstimator {
         }
         let p_delta = (next_tv.t - self.last_time) / self.freq.unwrap();
         let alpha_delta = self.alpha.powf(p_delta);
+        if self.total_weight > 0.0 {
+            let err = next_tv.v - self.average();
+            self.sq_err_sum = self.sq_err_sum * alpha_delta + err * err;
+            self.sq_err_weight = self.sq_err_weight * alpha_delta + 1.0;
+        }
         self.last_time = next_tv.t;
         self.sum = self.sum * alpha_delta + next_tv.v;
         self.total_weight = self.total_weight * alpha_delta + 1.0;
@@ -185,7 +222,10 @@ impl CellConsumer for SimpleExponentSmoothEstimator {
 
 impl CellEstimator for SimpleExponentSmoothEstimator {
     fn produce(&self) -> Box<dyn CellForecast> {
-        Box::new(ConstantForecast::new(self.average()))
+        Box::new(ConstantForecast::with_variance(
+            self.average(),
+            self.error_variance(),
+        ))
     }
 }
 
@@ -199,19 +239,31 @@ impl Averager for SimpleExponentSmoothEstimator {
 
 // TODO: Gaussian process library?
 
-
 /************************/
 /* Predict linear model */
 
 #[derive(Debug)]
 pub struct AffineForecast {
     slope: ValueType,
-    intercept: ValueType
+    intercept: ValueType,
+    error: LeastSquareError,
 }
 
 impl AffineForecast {
     fn new(slope: ValueType, intercept: ValueType) -> AffineForecast {
-        AffineForecast { slope, intercept }
+        AffineForecast::with_error(slope, intercept, LeastSquareError::default())
+    }
+
+    fn with_error(
+        slope: ValueType,
+        intercept: ValueType,
+        error: LeastSquareError,
+    ) -> AffineForecast {
+        AffineForecast {
+            slope,
+            intercept,
+            error,
+        }
     }
 }
 
@@ -221,10 +273,35 @@ impl CellForecast for AffineForecast {
     }
 
     fn complexity(&self) -> ValueType {
-        2.0  // slope + intercept
+        2.0 // slope + intercept
     }
+
+    fn variance(&self, time: ValueType) -> ValueType {
+        self.error.variance(time)
+    }
+}
+
+/* Error of a least-square line */
+// the variance of a new observation grows with its distance to the mean time:
+// s^2 (1 + 1/n + (t - mean_t)^2 / (n var_t))
+
+#[derive(Default, Debug)]
+struct LeastSquareError {
+    residual_variance: ValueType,
+    n: ValueType,
+    mean_t: TimeType,
+    var_t: TimeType,
 }
 
+impl LeastSquareError {
+    fn variance(&self, time: ValueType) -> ValueType {
+        if self.residual_variance == 0.0 {
+            return 0.0;
+        }
+        let dt = time - self.mean_t;
+        self.residual_variance * (1.0 + 1.0 / self.n + dt * dt / (self.n * self.var_t))
+    }
+}
 
 /* Least-square affine estimator */
 // keep track of statistics to compute covariances and means
@@ -233,6 +310,7 @@ impl CellForecast for AffineForecast {
 #[derive(Default, Debug)]
 pub struct LeastSquareAffineEstimator {
     var_t: TimeType,
+    var_v: ValueType,
     cov_tv: ValueType,
     mean_t: TimeType,
     mean_v: ValueType,
@@ -244,9 +322,24 @@ impl LeastSquareAffineEstimator {
         if self.var_t == 0.0 {
             return AffineForecast::new(0.0, self.mean_v);
         }
-        let slope = self.cov_tv / self.var_t;  // (X^T X)^{-1} X^T Y
+        let slope = self.cov_tv / self.var_t; // (X^T X)^{-1} X^T Y
         let intercept = self.mean_v - slope * self.mean_t;
-        AffineForecast::new(slope, intercept)
+        AffineForecast::with_error(slope, intercept, self.make_error())
+    }
+
+    fn make_error(&self) -> LeastSquareError {
+        if self.n <= 2.0 {
+            // a line fits two points exactly
+            return LeastSquareError::default();
+        }
+        // residual sum of squares, over n - 2 degrees of freedom
+        let rss = self.n * (self.var_v - self.cov_tv * self.cov_tv / self.var_t);
+        LeastSquareError {
+            residual_variance: rss.max(0.0) / (self.n - 2.0),
+            n: self.n,
+            mean_t: self.mean_t,
+            var_t: self.var_t,
+        }
     }
 }
 
@@ -258,6 +351,7 @@ impl CellConsumer for LeastSquareAffineEstimator {
         let correction = (self.n - 1.0) / self.n;
         // let correction = 1.0;
         self.var_t += (correction * dt * dt - self.var_t) / self.n;
+        self.var_v += (correction * dv * dv - self.var_v) / self.n;
         self.cov_tv += (correction * dt * dv - self.cov_tv) / self.n;
         self.mean_t += dt / self.n;
         self.mean_v += dv / self.n;
@@ -270,7 +364,6 @@ impl CellEstimator for LeastSquareAffineEstimator {
     }
 }
 
-
 /* Average trend line estimator */
 // v(T) = v_last + average_trend * (T - t_last)
 
@@ -299,9 +392,7 @@ impl AverageTrendAffineEstimator {
     }
 
     pub fn with_ses(alpha: ValueType) -> AverageTrendAffineEstimator {
-        AverageTrendAffineEstimator::with(Box::new(
-            SimpleExponentSmoothEstimator::with_base(alpha)
-        ))
+        AverageTrendAffineEstimator::with(Box::new(SimpleExponentSmoothEstimator::with_base(alpha)))
     }
 
     fn make_affine(&self) -> AffineForecast {
@@ -315,7 +406,10 @@ impl CellConsumer for AverageTrendAffineEstimator {
     fn consume(&mut self, next_tv: &TimeValue) {
         let dt = next_tv.t - self.last_t;
         let dv = next_tv.v - self.last_v;
-        self.trend_estimator.consume(&TimeValue { t: next_tv.t, v: dv / dt });
+        self.trend_estimator.consume(&TimeValue {
+            t: next_tv.t,
+            v: dv / dt,
+        });
         self.last_t = next_tv.t;
         self.last_v = next_tv.v;
     }
@@ -327,9 +421,231 @@ impl CellEstimator for AverageTrendAffineEstimator {
     }
 }
 
+/* Holt's double exponential smoothing */
+// level(t_i) = alpha v_i + (1 - alpha) (level(t_{i-1}) + trend(t_{i-1}) steps(h_i))
+// trend(t_i) = beta (level(t_i) - level(t_{i-1})) / h_i + (1 - beta) phi^h_i trend(t_{i-1})
+// where h_i = (t_i - t_{i-1}) / freq, and steps(h) = phi + ... + phi^h is h when phi = 1
+// The one-step-ahead errors v_i - (level(t_{i-1}) + trend(t_{i-1}) steps(h_i)) estimate the
+// residual variance s^2, and the variance h steps ahead is, as for additive-error models,
+// s^2 (1 + sum_{j=1}^{h-1} (alpha + alpha beta steps(j))^2)
+
+#[derive(Debug)]
+pub struct HoltEstimator {
+    alpha: ValueType,
+    beta: ValueType,
+    phi: ValueType,
+    freq: Option<ValueType>,
+    last_time: TimeType,
+    level: ValueType,
+    trend: Option<ValueType>, // known from the second observation on
+    // squared one-step-ahead errors, from the third observation on
+    sq_err_sum: ValueType,
+    n_err: ValueType,
+}
+
+impl HoltEstimator {
+    /// Holt's linear trend, with the first time as the frequency. Unlike the base of
+    /// [SimpleExponentSmoothEstimator], alpha and beta are the weights of the latest
+    /// observation on the level and on the trend.
+    pub fn linear(alpha: ValueType, beta: ValueType) -> HoltEstimator {
+        HoltEstimator::damped(alpha, beta, 1.0)
+    }
+
+    /// Damped trend: the trend decays by phi per frequency, so forecasts level off at
+    /// level + trend phi / (1 - phi)
+    pub fn damped(alpha: ValueType, beta: ValueType, phi: ValueType) -> HoltEstimator {
+        assert!(0.0 < alpha && alpha <= 1.0);
+        assert!(0.0 < beta && beta <= 1.0);
+        assert!(0.0 < phi && phi <= 1.0);
+        HoltEstimator {
+            alpha,
+            beta,
+            phi,
+            freq: None,
+            last_time: 0.0,
+            level: 0.0,
+            trend: None,
+            sq_err_sum: 0.0,
+            n_err: 0.0,
+        }
+    }
+
+    /// Mean of the squared one-step-ahead errors
+    pub fn error_variance(&self) -> ValueType {
+        if self.n_err == 0.0 {
+            return 0.0;
+        }
+        self.sq_err_sum / self.n_err
+    }
+
+    fn make_forecast(&self) -> DampedTrendForecast {
+        DampedTrendForecast {
+            level: self.level,
+            trend: self.trend.unwrap_or(0.0),
+            alpha: self.alpha,
+            beta: self.beta,
+            phi: self.phi,
+            last_time: self.last_time,
+            freq: self.freq.unwrap_or(1.0),
+            residual_variance: self.error_variance(),
+        }
+    }
+}
+
+impl CellConsumer for HoltEstimator {
+    fn consume(&mut self, next_tv: &TimeValue) {
+        let freq = match self.freq {
+            Some(freq) => freq,
+            None => {
+                // first observation
+                self.freq = Some(next_tv.t);
+                self.last_time = next_tv.t;
+                self.level = next_tv.v;
+                return;
+            }
+        };
+        let h = (next_tv.t - self.last_time) / freq;
+        let last_level = self.level;
+        match self.trend {
+            None => {
+                self.level = next_tv.v;
+                self.trend = Some((self.level - last_level) / h);
+            }
+            Some(trend) => {
+                let pred_v = self.make_forecast().predict(next_tv.t);
+                self.sq_err_sum += (next_tv.v - pred_v).powi(2);
+                self.n_err += 1.0;
+                self.level = self.alpha * next_tv.v + (1.0 - self.alpha) * pred_v;
+                self.trend = Some(
+                    self.beta * (self.level - last_level) / h
+                        + (1.0 - self.beta) * self.phi.powf(h) * trend,
+                );
+            }
+        }
+        self.last_time = next_tv.t;
+    }
+}
+
+impl CellEstimator for HoltEstimator {
+    fn produce(&self) -> Box<dyn CellForecast> {
+        Box::new(self.make_forecast())
+    }
+}
+
+#[derive(Debug)]
+pub struct DampedTrendForecast {
+    level: ValueType,
+    trend: ValueType,
+    alpha: ValueType,
+    beta: ValueType,
+    phi: ValueType,
+    last_time: TimeType,
+    freq: ValueType,
+    residual_variance: ValueType,
+}
+
+impl DampedTrendForecast {
+    fn steps(&self, h: ValueType) -> ValueType {
+        if self.phi == 1.0 {
+            h
+        } else {
+            self.phi * (1.0 - self.phi.powf(h)) / (1.0 - self.phi)
+        }
+    }
+}
+
+impl CellForecast for DampedTrendForecast {
+    fn predict(&self, time: ValueType) -> ValueType {
+        let h = (time - self.last_time) / self.freq;
+        self.level + self.trend * self.steps(h)
+    }
+
+    fn complexity(&self) -> ValueType {
+        if self.phi == 1.0 {
+            2.0 // level + trend
+        } else {
+            3.0 // level + trend + damping
+        }
+    }
 
-// TODO: Richardson's extrapolation on reciprocal coordinate?
+    fn variance(&self, time: ValueType) -> ValueType {
+        if self.residual_variance == 0.0 {
+            return 0.0;
+        }
+        let h = (time - self.last_time) / self.freq;
+        let mut factor = 1.0;
+        let mut j = 1.0;
+        while j < h {
+            factor += (self.alpha + self.alpha * self.beta * self.steps(j)).powi(2);
+            j += 1.0;
+        }
+        self.residual_variance * factor
+    }
+}
 
+/* Power-law convergence */
+// v(t) = limit + scale * t^{-exponent}, i.e., Richardson's extrapolation: a line on the
+// coordinate x = t^{-exponent}, whose intercept is the limit at t = infinity
+
+#[derive(Debug)]
+pub struct PowerLawEstimator {
+    exponent: ValueType,
+    affine_estimator: LeastSquareAffineEstimator,
+}
+
+impl PowerLawEstimator {
+    /// Fit with known exponent, e.g., 1 for means over a growing sample whose bias shrinks as
+    /// 1/n, or 0.5 for their standard error
+    pub fn with_exponent(exponent: ValueType) -> PowerLawEstimator {
+        assert!(exponent > 0.0);
+        PowerLawEstimator {
+            exponent,
+            affine_estimator: LeastSquareAffineEstimator::default(),
+        }
+    }
+}
+
+impl CellConsumer for PowerLawEstimator {
+    fn consume(&mut self, next_tv: &TimeValue) {
+        if next_tv.t <= 0.0 {
+            // no finite coordinate
+            return;
+        }
+        self.affine_estimator.consume(&TimeValue {
+            t: next_tv.t.powf(-self.exponent),
+            v: next_tv.v,
+        });
+    }
+}
+
+impl CellEstimator for PowerLawEstimator {
+    fn produce(&self) -> Box<dyn CellForecast> {
+        Box::new(PowerLawForecast {
+            exponent: self.exponent,
+            affine: self.affine_estimator.make_affine(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PowerLawForecast {
+    exponent: ValueType,
+    affine: AffineForecast,
+}
+
+impl CellForecast for PowerLawForecast {
+    fn predict(&self, time: ValueType) -> ValueType {
+        self.affine.predict(time.powf(-self.exponent))
+    }
+
+    fn complexity(&self) -> ValueType {
+        2.0 // limit + scale
+    }
+
+    fn variance(&self, time: ValueType) -> ValueType {
+        self.affine.variance(time.powf(-self.exponent))
+    }
+}
 
 /******************/
 /* Model selector */
@@ -338,7 +654,7 @@ impl CellEstimator for AverageTrendAffineEstimator {
 struct RollingMAECellEstimator {
     estimator: Box<dyn CellEstimator>,
     // rolling_err: MeanEstimator,  // use Box<dyn Averager> instead?
-    rolling_err: SimpleExponentSmoothEstimator,  // use Box<dyn Averager> instead?
+    rolling_err: SimpleExponentSmoothEstimator, // use Box<dyn Averager> instead?
 }
 
 impl RollingMAECellEstimator {
@@ -356,7 +672,10 @@ impl RollingMAECellEstimator {
         let pred_v = self.estimator.produce().predict(eval_tv.t);
         // let abs_err = (eval_tv.v - pred_v).abs();
         let abs_err = (eval_tv.v - pred_v).powi(2);
-        self.rolling_err.consume(&TimeValue { t: train_tv.t, v: abs_err });
+        self.rolling_err.consume(&TimeValue {
+            t: train_tv.t,
+            v: abs_err,
+        });
     }
 
     fn produce_with_mae(&self) -> (Box<dyn CellForecast>, ValueType) {
@@ -364,27 +683,57 @@ impl RollingMAECellEstimator {
     }
 }
 
+/// How a [ForecastSelector] picks among its candidates
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Selection {
+    /// The candidate with the lowest rolling squared error of its one-step-ahead predictions,
+    /// which adapts quickly when the series changes its shape
+    #[default]
+    RollingError,
+    /// The candidate with the lowest information criterion of its fit on all the samples, which
+    /// penalizes complex candidates against overfitting
+    Aic,
+    Aicc,
+    Bic,
+    /// Average of all the candidates, weighted by the inverse of their rolling errors
+    Ensemble,
+}
+
 #[derive(Default, Debug)]
 pub struct ForecastSelector {
     scored_estimators: Vec<RollingMAECellEstimator>,
-    hot_sample: Option<TimeValue>,  // use queue to hold multiple hot samples
+    hot_sample: Option<TimeValue>, // use queue to hold multiple hot samples
     num_samples: ValueType,
+    selection: Selection,
+    // samples trained on, to score the fits of candidates by information criteria
+    times: Vec<TimeType>,
+    values: Vec<ValueType>,
 }
 
 impl ForecastSelector {
     pub fn include(&mut self, estimator: Box<dyn CellEstimator>) {
-        self.scored_estimators.push(RollingMAECellEstimator::new(estimator))
+        self.scored_estimators
+            .push(RollingMAECellEstimator::new(estimator))
+    }
+
+    pub fn set_selection(&mut self, selection: Selection) {
+        self.selection = selection;
     }
 
     fn default_forecast(&self) -> Box<dyn CellForecast> {
-      match &self.hot_sample {
-        Some(train_tv) => Box::new(ConstantForecast::new(train_tv.v)),
-        None => Box::new(ConstantForecast::new(0.0)),
-      }
+        match &self.hot_sample {
+            Some(train_tv) => Box::new(ConstantForecast::new(train_tv.v)),
+            None => Box::new(ConstantForecast::new(0.0)),
+        }
     }
 
     pub fn make_with_default_candidates() -> Box<dyn CellEstimator> {
+        ForecastSelector::make_with_selection(Selection::default())
+    }
+
+    pub fn make_with_selection(selection: Selection) -> Box<dyn CellEstimator> {
         let mut selector = ForecastSelector::default();
+        selector.set_selection(selection);
         selector.include(Box::new(TailEstimator::default()));
         selector.include(Box::new(MeanEstimator::default()));
         selector.include(Box::new(SimpleExponentSmoothEstimator::with_base(0.75)));
@@ -392,10 +741,139 @@ impl ForecastSelector {
         selector.include(Box::new(AverageTrendAffineEstimator::with_tail()));
         selector.include(Box::new(AverageTrendAffineEstimator::with_mean()));
         selector.include(Box::new(AverageTrendAffineEstimator::with_ses(0.5)));
+        selector.include(Box::new(HoltEstimator::linear(0.5, 0.5)));
+        selector.include(Box::new(HoltEstimator::damped(0.5, 0.5, 0.9)));
+        selector.include(Box::new(PowerLawEstimator::with_exponent(0.5)));
+        selector.include(Box::new(PowerLawEstimator::with_exponent(1.0)));
         Box::new(selector)
     }
 }
 
+/// Forecaster chosen by a [ForecastSelector]. Its variance is at least the rolling squared
+/// error of its one-step-ahead predictions, so that a model fitting its own samples well still
+/// reports the error it made on unseen ones.
+#[derive(Debug)]
+struct SelectedForecast {
+    forecast: Box<dyn CellForecast>,
+    rolling_err: ValueType,
+}
+
+impl CellForecast for SelectedForecast {
+    fn predict(&self, time: ValueType) -> ValueType {
+        self.forecast.predict(time)
+    }
+
+    fn complexity(&self) -> ValueType {
+        self.forecast.complexity()
+    }
+
+    fn variance(&self, time: ValueType) -> ValueType {
+        self.forecast.variance(time).max(self.rolling_err)
+    }
+}
+
+/// Forecaster averaging the forecasts of a [ForecastSelector]'s candidates with weights
+#[derive(Debug)]
+struct EnsembleForecast {
+    forecasts: Vec<(ValueType, Box<dyn CellForecast>)>, // weights summing to 1
+}
+
+impl CellForecast for EnsembleForecast {
+    fn predict(&self, time: ValueType) -> ValueType {
+        self.forecasts
+            .iter()
+            .map(|(weight, f)| weight * f.predict(time))
+            .sum()
+    }
+
+    fn complexity(&self) -> ValueType {
+        self.forecasts
+            .iter()
+            .map(|(_weight, f)| f.complexity())
+            .sum()
+    }
+
+    fn variance(&self, time: ValueType) -> ValueType {
+        self.forecasts
+            .iter()
+            .map(|(weight, f)| weight * f.variance(time))
+            .sum()
+    }
+}
+
+impl ForecastSelector {
+    /// The candidate with the best score, with its rolling error. Ties (e.g., infinite
+    /// criteria with too few samples) go to the lowest rolling error, then to the lowest
+    /// complexity, then to the first candidate. NaN scores and errors rank last.
+    fn select_by(
+        &self,
+        score: impl Fn(&dyn CellForecast, ValueType) -> ValueType,
+    ) -> (Box<dyn CellForecast>, ValueType) {
+        let or_worst = |x: ValueType, worst: ValueType| if x.is_nan() { worst } else { x };
+        self.scored_estimators
+            .iter()
+            .map(|est| {
+                let (f, mae) = est.produce_with_mae();
+                let score = or_worst(score(&*f, mae), ValueType::NEG_INFINITY);
+                (f, mae, score)
+            })
+            .reduce(|best, next| {
+                let (f_best, mae_best, score_best) = &best;
+                let (f_next, mae_next, score_next) = &next;
+                let order = score_next.total_cmp(score_best).then_with(|| {
+                    let mae_best = or_worst(*mae_best, ValueType::INFINITY);
+                    let mae_next = or_worst(*mae_next, ValueType::INFINITY);
+                    mae_best
+                        .total_cmp(&mae_next)
+                        .then_with(|| f_best.complexity().total_cmp(&f_next.complexity()))
+                });
+                if order == Ordering::Greater {
+                    next
+                } else {
+                    best
+                }
+            })
+            .map(|(f, mae, _score)| (f, mae))
+            .expect("No estimator installed with this ForecastSelector")
+    }
+
+    /// Score of a candidate's fit on all the samples trained on
+    fn fit_score(&self, f: &dyn CellForecast) -> Score {
+        let series = Series::new(&self.times, &self.values);
+        Score::least_square(f.complexity(), f, series)
+    }
+
+    fn ensemble(&self) -> (Box<dyn CellForecast>, ValueType) {
+        let forecasts: Vec<_> = self
+            .scored_estimators
+            .iter()
+            .map(|est| est.produce_with_mae())
+            .collect();
+        // candidates without error, if any, take all the weight
+        let weights: Vec<ValueType> = if forecasts.iter().any(|(_f, mae)| *mae == 0.0) {
+            forecasts
+                .iter()
+                .map(|(_f, mae)| if *mae == 0.0 { 1.0 } else { 0.0 })
+                .collect()
+        } else {
+            forecasts.iter().map(|(_f, mae)| 1.0 / mae).collect()
+        };
+        let total_weight: ValueType = weights.iter().sum();
+        let rolling_err = forecasts
+            .iter()
+            .zip(&weights)
+            .map(|((_f, mae), weight)| weight * mae)
+            .sum::<ValueType>()
+            / total_weight;
+        let forecasts = forecasts
+            .into_iter()
+            .zip(weights)
+            .map(|((f, _mae), weight)| (weight / total_weight, f))
+            .collect();
+        (Box::new(EnsembleForecast { forecasts }), rolling_err)
+    }
+}
+
 impl CellConsumer for ForecastSelector {
     fn consume(&mut self, next_tv: &TimeValue) {
         // use hot sample to train
@@ -404,6 +882,13 @@ impl CellConsumer for ForecastSelector {
                 est.consume_eval(train_tv, next_tv);
             }
             self.num_samples += 1.0;
+            if !matches!(
+                self.selection,
+                Selection::RollingError | Selection::Ensemble
+            ) {
+                self.times.push(train_tv.t);
+                self.values.push(train_tv.v);
+            }
         }
 
         // update hot sample
@@ -416,18 +901,20 @@ impl CellEstimator for ForecastSelector {
         if self.num_samples == 0.0 {
             return self.default_forecast();
         }
-        let (best_f, _best_score) = self.scored_estimators.iter().map(|est| {
-            let (f, mae) = est.produce_with_mae();
-            let score = -mae;
-            (f, score)
+        let (forecast, rolling_err) = match self.selection {
+            Selection::RollingError => self.select_by(|_f, mae| -mae),
+            Selection::Aic => self.select_by(|f, _mae| -self.fit_score(f).aic()),
+            Selection::Aicc => self.select_by(|f, _mae| -self.fit_score(f).aicc()),
+            Selection::Bic => self.select_by(|f, _mae| -self.fit_score(f).bic()),
+            Selection::Ensemble => self.ensemble(),
+        };
+        Box::new(SelectedForecast {
+            forecast,
+            rolling_err,
         })
-            .max_by(|(_f_1, score_1), (_f_2, score_2)| score_1.partial_cmp(score_2).unwrap())
-            .expect("No estimator installed with this ForecastSelector");
-        best_f
     }
 }
 
-
 /* Unit tests */
 
 #[cfg(test)]
@@ -435,13 +922,15 @@ mod tests {
     use super::*;
     use rand::Rng;
 
-    fn assert_forecast(f1: &Box<dyn CellForecast>, f2: &Box<dyn CellForecast>) {
+    fn assert_forecast(f1: &dyn CellForecast, f2: &dyn CellForecast) {
         let mut rng = rand::thread_rng();
-        for _ in 0 .. 100 {
+        for _ in 0..100 {
             let t = rng.gen_range(0.0..1000.0);
             assert!(
                 (f1.predict(t) - f2.predict(t)).abs() < 1e-4,
-                "Actual forecaster: {:?}, expected: {:?}", f1, f2,
+                "Actual forecaster: {:?}, expected: {:?}",
+                f1,
+                f2,
             );
         }
     }
@@ -463,7 +952,7 @@ mod tests {
     fn test_constant_mean() {
         let times = vec![1.0, 2.0, 3.0, 5.0];
         let values = vec![1.0, 2.0, -6.0, 27.0];
-        let averages = vec![1.0, 1.5, -1.0, 6.0];
+        let averages = [1.0, 1.5, -1.0, 6.0];
         let series = Series::new(&times, &values);
         let mut est = MeanEstimator::default();
         for (tv, expected_avg) in series.iter().zip(averages.iter()) {
@@ -477,7 +966,7 @@ mod tests {
     fn test_constant_ses() {
         let times = vec![1.0, 2.0, 3.0, 5.0, 10000.0];
         let values = vec![1.0, 4.0, -5.75, 15.25, 100.0];
-        let averages = vec![1.0, 3.0, -2.0, 10.0, 100.0];
+        let averages = [1.0, 3.0, -2.0, 10.0, 100.0];
         let series = Series::new(&times, &values);
         let mut est = SimpleExponentSmoothEstimator::with_base(0.5);
         for (tv, expected_avg) in series.iter().zip(averages.iter()) {
@@ -493,7 +982,7 @@ mod tests {
         let times = vec![1.0, 2.0, 3.0, 5.0, 10.0, 12.0];
         let values: Vec<f64> = times.iter().map(|t| truth.predict(*t)).collect();
         let series = Series::new(&times, &values);
-        assert_forecast(&LeastSquareAffineEstimator::default().fit(series), &truth)
+        assert_forecast(&*LeastSquareAffineEstimator::default().fit(series), &*truth)
     }
 
     #[test]
@@ -502,8 +991,8 @@ mod tests {
         let values = vec![1.0, 2.5, 1.5, 2.5, 3.5, 5.0];
         let series = Series::new(&times, &values);
         assert_forecast(
-            &LeastSquareAffineEstimator::default().fit(series),
-            &(Box::new(AffineForecast::new(1.0, 0.0)) as Box<dyn CellForecast>),
+            &*LeastSquareAffineEstimator::default().fit(series),
+            &AffineForecast::new(1.0, 0.0),
         )
     }
 
@@ -513,8 +1002,8 @@ mod tests {
         let values = vec![11.0, 12.5, 11.5, 12.5, 13.5, 15.0];
         let series = Series::new(&times, &values);
         assert_forecast(
-            &LeastSquareAffineEstimator::default().fit(series),
-            &(Box::new(AffineForecast::new(1.0, 10.0)) as Box<dyn CellForecast>),
+            &*LeastSquareAffineEstimator::default().fit(series),
+            &AffineForecast::new(1.0, 10.0),
         )
     }
 
@@ -522,7 +1011,7 @@ mod tests {
     fn test_affine_trend_tail() {
         let times = vec![1.0, 2.0, 3.0, 5.0];
         let values = vec![1.0, 2.0, -6.0, 27.0];
-        let targets = vec![10.0, 10.0, -62.0, 109.5];  // at t = 10
+        let targets = [10.0, 10.0, -62.0, 109.5]; // at t = 10
         let series = Series::new(&times, &values);
         let mut est = AverageTrendAffineEstimator::with_tail();
         for (tv, expected_pred) in series.iter().zip(targets.iter()) {
@@ -535,7 +1024,7 @@ mod tests {
     fn test_affine_trend_mean() {
         let times = vec![1.0, 2.0, 3.0, 5.0];
         let values = vec![1.0, 1.5, 3.0, 7.0];
-        let targets = vec![10.0, 7.5, 10.0, 13.25];  // at t = 10
+        let targets = [10.0, 7.5, 10.0, 13.25]; // at t = 10
         let series = Series::new(&times, &values);
         let mut est = AverageTrendAffineEstimator::with_mean();
         for (tv, expected_pred) in series.iter().zip(targets.iter()) {
@@ -548,7 +1037,7 @@ mod tests {
     fn test_affine_trend_ses() {
         let times = vec![1.0, 2.0, 3.0, 5.0];
         let values = vec![1.0, 1.25, 1.40, 2.0];
-        let targets = vec![10.0, 5.25, 3.5, 3.5];  // at t = 10
+        let targets = [10.0, 5.25, 3.5, 3.5]; // at t = 10
         let series = Series::new(&times, &values);
         let mut est = AverageTrendAffineEstimator::with_ses(0.5);
         for (tv, expected_pred) in series.iter().zip(targets.iter()) {
@@ -557,11 +1046,127 @@ mod tests {
             assert!(
                 (actual_pred - *expected_pred).abs() < 1e-4,
                 "{} != {}, at est: {:?}, forecaster: {:?}",
-                actual_pred, expected_pred, est, est.produce()
+                actual_pred,
+                expected_pred,
+                est,
+                est.produce()
             );
         }
     }
 
+    #[test]
+    fn test_ses_variance() {
+        let times = [1.0, 2.0, 3.0];
+        let values = [1.0, 3.0, 1.0];
+        let series = Series::new(&times, &values);
+        let f = SimpleExponentSmoothEstimator::with_base(0.5).fit(series);
+        // one-step errors 2 and -4/3, the first weighted by 0.5
+        let expected = (0.5 * 4.0 + 16.0 / 9.0) / 1.5;
+        assert!((f.variance(10.0) - expected).abs() < 1e-9);
+        let (lower, upper) = f.interval(10.0, 2.0);
+        assert!((upper - lower - 4.0 * expected.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_affine_ols_variance() {
+        let times = [1.0, 2.0, 2.0, 3.0, 3.0, 5.0];
+        let values = [1.0, 2.5, 1.5, 2.5, 3.5, 5.0];
+        let series = Series::new(&times, &values);
+        let f = LeastSquareAffineEstimator::default().fit(series);
+        // residuals 0, 0.5, -0.5, -0.5, 0.5, 0 over 4 degrees of freedom
+        let mean_t = 16.0 / 6.0;
+        assert!((f.variance(mean_t) - 0.25 * (1.0 + 1.0 / 6.0)).abs() < 1e-9);
+        assert!(f.variance(10.0) > f.variance(mean_t));
+        let (lower, upper) = f.interval(10.0, 1.96);
+        assert!(lower < f.predict(10.0) && f.predict(10.0) < upper);
+
+        // no uncertainty on a perfect line
+        let values: Vec<f64> = times.iter().map(|t| 2.0 * t + 1.0).collect();
+        let series = Series::new(&times, &values);
+        let f = LeastSquareAffineEstimator::default().fit(series);
+        assert!(f.variance(10.0) < 1e-9);
+    }
+
+    #[test]
+    fn test_holt_linear_perfect() {
+        let truth = Box::new(AffineForecast::new(3.0, -2.0)) as Box<dyn CellForecast>;
+        let times = vec![1.0, 2.0, 3.0, 5.0, 10.0, 12.0];
+        let values: Vec<f64> = times.iter().map(|t| truth.predict(*t)).collect();
+        let series = Series::new(&times, &values);
+        assert_forecast(&*HoltEstimator::linear(0.5, 0.5).fit(series), &*truth)
+    }
+
+    #[test]
+    fn test_holt_linear() {
+        let times = vec![1.0, 2.0, 3.0];
+        let values = vec![1.0, 2.0, 5.0];
+        let series = Series::new(&times, &values);
+        let mut est = HoltEstimator::linear(0.5, 0.5);
+        // level: 0.5 * 5 + 0.5 * 3 = 4, trend: 0.5 * (4 - 2) + 0.5 * 1 = 1.5
+        assert_forecast(&*est.fit(series), &AffineForecast::new(1.5, -0.5))
+    }
+
+    #[test]
+    fn test_holt_damped() {
+        let times = vec![1.0, 2.0];
+        let values = vec![1.0, 2.0];
+        let series = Series::new(&times, &values);
+        let f = HoltEstimator::damped(0.5, 0.5, 0.5).fit(series);
+        // level 2 and trend 1, halving at each step
+        assert!((f.predict(3.0) - 2.5).abs() < 1e-9);
+        assert!((f.predict(4.0) - 2.75).abs() < 1e-9);
+        assert!((f.predict(1000.0) - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_holt_variance() {
+        let times = vec![1.0, 2.0, 3.0];
+        let values = vec![1.0, 2.0, 5.0];
+        let series = Series::new(&times, &values);
+        let f = HoltEstimator::linear(0.5, 0.5).fit(series);
+        // one-step error 5 - 3 at the third observation
+        assert!((f.variance(4.0) - 4.0).abs() < 1e-9);
+        // two steps ahead: 4 (1 + (0.5 + 0.25)^2)
+        assert!((f.variance(5.0) - 4.0 * (1.0 + 0.75 * 0.75)).abs() < 1e-9);
+
+        // noisy growth, with and without damping
+        let times: Vec<f64> = (1..=10).map(|t| t as f64).collect();
+        let values: Vec<f64> = times
+            .iter()
+            .map(|t| 2.0 * t + if *t as i32 % 2 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        for mut est in [
+            HoltEstimator::linear(0.5, 0.5),
+            HoltEstimator::damped(0.5, 0.5, 0.9),
+        ] {
+            let f = est.fit(Series::new(&times, &values));
+            let (lower, upper) = f.interval(20.0, 1.96);
+            assert!(lower < f.predict(20.0) && f.predict(20.0) < upper);
+            assert!(f.variance(20.0) > f.variance(11.0));
+        }
+
+        // no uncertainty on a perfect line
+        let values: Vec<f64> = times.iter().map(|t| 2.0 * t + 1.0).collect();
+        let f = HoltEstimator::linear(0.5, 0.5).fit(Series::new(&times, &values));
+        assert!(f.variance(20.0) < 1e-9);
+    }
+
+    #[test]
+    fn test_power_law_perfect() {
+        let times = vec![1.0, 2.0, 4.0, 5.0, 10.0];
+        let values: Vec<f64> = times.iter().map(|t| 100.0 - 50.0 / t).collect();
+        let series = Series::new(&times, &values);
+        let f = PowerLawEstimator::with_exponent(1.0).fit(series);
+        assert!((f.predict(20.0) - 97.5).abs() < 1e-9);
+        assert!((f.predict(1e12) - 100.0).abs() < 1e-6);
+        assert!(f.variance(20.0) < 1e-9);
+
+        let values: Vec<f64> = times.iter().map(|t| 10.0 + 4.0 / t.sqrt()).collect();
+        let series = Series::new(&times, &values);
+        let f = PowerLawEstimator::with_exponent(0.5).fit(series);
+        assert!((f.predict(16.0) - 11.0).abs() < 1e-9);
+    }
+
     fn make_test_candidates() -> ForecastSelector {
         let mut selector = ForecastSelector::default();
         selector.include(Box::new(TailEstimator::default()));
@@ -571,6 +1176,10 @@ mod tests {
         selector.include(Box::new(AverageTrendAffineEstimator::with_tail()));
         selector.include(Box::new(AverageTrendAffineEstimator::with_mean()));
         selector.include(Box::new(AverageTrendAffineEstimator::with_ses(0.5)));
+        selector.include(Box::new(HoltEstimator::linear(0.5, 0.5)));
+        selector.include(Box::new(HoltEstimator::damped(0.5, 0.5, 0.9)));
+        selector.include(Box::new(PowerLawEstimator::with_exponent(0.5)));
+        selector.include(Box::new(PowerLawEstimator::with_exponent(1.0)));
         selector
     }
 
@@ -581,7 +1190,7 @@ mod tests {
         let values: Vec<f64> = times.iter().map(|t| truth.predict(*t)).collect();
         let series = Series::new(&times, &values);
         let mut est = make_test_candidates();
-        assert_forecast(&est.fit(series), &truth)
+        assert_forecast(&*est.fit(series), &*truth)
     }
 
     #[test]
@@ -592,7 +1201,12 @@ mod tests {
         let mut est = make_test_candidates();
         let f = est.fit(series);
         let pred_v = f.predict(1100.0);
-        assert!((pred_v - 100.0).abs() < 10.0, "Inaccurate f= {:?}, pred= {}", f, pred_v);
+        assert!(
+            (pred_v - (100.0 - 100.0 / 1100.0)).abs() < 1e-6,
+            "Inaccurate f= {:?}, pred= {}",
+            f,
+            pred_v
+        );
     }
 
     #[test]
@@ -603,8 +1217,88 @@ mod tests {
         let mut est = make_test_candidates();
         let f = est.fit(series);
         let pred_v = f.predict(200.0);
-        assert!((pred_v - 200.0f64.ln()).abs() < 1.0, "Inaccurate f= {:?}, pred= {}", f, pred_v);
+        assert!(
+            (pred_v - 200.0f64.ln()).abs() < 1.0,
+            "Inaccurate f= {:?}, pred= {}",
+            f,
+            pred_v
+        );
+    }
+
+    #[test]
+    fn test_selector_criteria() {
+        // a line with little noise: the affine fit beats the more complex ones
+        let times: Vec<TimeType> = (1..41).map(|t| t.into()).collect();
+        let values: Vec<ValueType> = times
+            .iter()
+            .map(|t| 2.0 * t + 1.0 + (t % 3.0 - 1.0) * 0.01)
+            .collect();
+        for selection in [Selection::Aic, Selection::Aicc, Selection::Bic] {
+            let mut est = make_test_candidates();
+            est.set_selection(selection);
+            let f = est.fit(Series::new(&times, &values));
+            let pred_v = f.predict(100.0);
+            assert!(
+                (pred_v - 201.0).abs() < 0.1,
+                "{:?}: inaccurate f= {:?}, pred= {}",
+                selection,
+                f,
+                pred_v
+            );
+            assert_eq!(f.complexity(), 2.0, "{:?}: f= {:?}", selection, f);
+        }
     }
 
+    #[test]
+    fn test_selector_ensemble() {
+        let times = vec![1.0, 2.0, 3.0];
+        let values = vec![1.0, 3.0, 2.0];
+        let mut est = ForecastSelector::default();
+        est.set_selection(Selection::Ensemble);
+        est.include(Box::new(TailEstimator::default()));
+        est.include(Box::new(MeanEstimator::default()));
+        // trained on 1 and 3, the tail predicted 1 then 3 with squared errors 4 and 1, the mean
+        // predicted 1 then 2 with squared errors 4 and 0
+        let f = est.fit(Series::new(&times, &values));
+        let tail_err = (0.75 * 4.0 + 1.0) / 1.75;
+        let mean_err = (0.75 * 4.0) / 1.75;
+        let expected = (3.0 / tail_err + 2.0 / mean_err) / (1.0 / tail_err + 1.0 / mean_err);
+        assert!((f.predict(10.0) - expected).abs() < 1e-9, "f= {:?}", f);
+        assert_eq!(f.complexity(), 2.0);
+    }
 
+    #[test]
+    fn test_selector_breaks_ties() {
+        // 3 samples: the AICc of every candidate with 2 parameters or more is infinite
+        let times = vec![1.0, 2.0, 3.0];
+        let values = vec![1.0, 3.0, 3.0];
+        let mut est = ForecastSelector::default();
+        est.set_selection(Selection::Aicc);
+        est.include(Box::new(LeastSquareAffineEstimator::default()));
+        est.include(Box::new(TailEstimator::default()));
+        est.include(Box::new(PowerLawEstimator::with_exponent(1.0)));
+        // the tail predicted 1 then 3, the line 1 then 5: the tail has the lowest rolling error
+        let f = est.fit(Series::new(&times, &values));
+        assert_eq!(f.predict(10.0), 3.0, "f= {:?}", f);
+
+        // 2 samples: the rolling errors are equal, so the simplest candidate wins
+        let mut est = ForecastSelector::default();
+        est.set_selection(Selection::Aicc);
+        est.include(Box::new(LeastSquareAffineEstimator::default()));
+        est.include(Box::new(MeanEstimator::default()));
+        let f = est.fit(Series::new(&times[..2], &values[..2]));
+        assert_eq!(f.complexity(), 1.0, "f= {:?}", f);
+    }
+
+    #[test]
+    fn test_selector_variance() {
+        let times: Vec<TimeType> = (1..21).map(|t| t.into()).collect();
+        let values: Vec<ValueType> = times.iter().map(|t| t + (t % 2.0) * 2.0).collect();
+        let series = Series::new(&times, &values);
+        let mut est = make_test_candidates();
+        let f = est.fit(series);
+        assert!(f.variance(30.0) > 0.5, "f= {:?}", f);
+        let (lower, upper) = f.interval(30.0, 1.96);
+        assert!(lower < f.predict(30.0) && f.predict(30.0) < upper);
+    }
 }
@@ -2,7 +2,6 @@ use core::ops::Add;
 use core::ops::AddAssign;
 use core::ops::Div;
 
-
 /* Types that allow required arithmetic operation */
 
 pub trait Numeric:  // unused
@@ -15,7 +14,6 @@ pub trait Numeric:  // unused
     + std::iter::Sum<Self>
     + std::slice::SliceIndex<[Self]> {}
 
-
 pub type TimeType = f64;
 pub type ValueType = f64;
 
@@ -36,7 +34,7 @@ impl<'a> Series<'a> {
         Series { times, values }
     }
 
-    pub fn iter(&self) -> SeriesIter {
+    pub fn iter(&self) -> SeriesIter<'_> {
         SeriesIter::new(self)
     }
 
@@ -79,7 +77,8 @@ impl<'a> Iterator for SeriesIter<'a> {
 
 /* Imports */
 
+pub mod backtest;
 pub mod cell;
 pub mod row;
+pub mod score;
 pub mod table;
-pub mod score;
\ No newline at end of file
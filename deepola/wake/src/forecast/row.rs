@@ -1,29 +1,41 @@
 use itertools::izip;
 
+use crate::forecast::cell::CellEstimator;
+use crate::forecast::cell::CellForecast;
 use crate::forecast::TimeType;
 use crate::forecast::TimeValue;
 use crate::forecast::ValueType;
-use crate::forecast::cell::CellEstimator;
 
 #[derive(Default)]
 pub struct RowForecast {
-  cell_estimators: Vec<Box<dyn CellEstimator>>,
+    cell_estimators: Vec<Box<dyn CellEstimator>>,
 }
 
 impl RowForecast {
+    pub fn push_estimator(&mut self, estimator: Box<dyn CellEstimator>) {
+        self.cell_estimators.push(estimator);
+    }
 
-  pub fn push_estimator(&mut self, estimator: Box<dyn CellEstimator>) {
-    self.cell_estimators.push(estimator);
-  }
+    /// Consume the values observed at `time`, and produce the current forecaster of each cell
+    pub fn fit(&mut self, values: Vec<ValueType>, time: TimeType) -> Vec<Box<dyn CellForecast>> {
+        assert_eq!(values.len(), self.cell_estimators.len());
+        izip!(&mut self.cell_estimators, values)
+            .map(|(est, v)| {
+                est.consume(&TimeValue { t: time, v });
+                est.produce()
+            })
+            .collect()
+    }
 
-  pub fn fit_transform(&mut self, values: Vec<ValueType>, time: TimeType, final_time: TimeType) -> Vec<ValueType> {
-    assert_eq!(values.len(), self.cell_estimators.len());
-    izip!(&mut self.cell_estimators, values)
-      .map(|(est, v)| {
-        est.consume(&TimeValue { t: time, v });
-        let f = est.produce();
-        f.predict(final_time as f64)
-      })
-      .collect()
-  }
-}
\ No newline at end of file
+    pub fn fit_transform(
+        &mut self,
+        values: Vec<ValueType>,
+        time: TimeType,
+        final_time: TimeType,
+    ) -> Vec<ValueType> {
+        self.fit(values, time)
+            .iter()
+            .map(|f| f.predict(final_time))
+            .collect()
+    }
+}
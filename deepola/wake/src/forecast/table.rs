@@ -1,164 +1,407 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 
-use crate::data::ArrayRow;
-use crate::data::DataBlock;
-use crate::data::DataCell;
-use crate::data::Schema;
-use crate::data::SCHEMA_META_NAME;
+use polars::prelude::*;
+
+use crate::data::{DataBlock, DataCell, DataMessage, Payload};
+use crate::forecast::cell::CellEstimator;
+use crate::forecast::cell::CellForecast;
 use crate::forecast::cell::ForecastSelector;
+use crate::forecast::cell::Selection;
 use crate::forecast::row::RowForecast;
 use crate::forecast::TimeType;
 use crate::forecast::ValueType;
 use crate::graph::ExecutionNode;
-use crate::processor::SetProcessorV2;
-
+use crate::processor::StreamProcessor;
 
 /* Execution node */
 
-pub struct ForecastNode;
+/// What a snapshot's time is, for the forecast.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeAxis {
+    /// The fraction of the input read (see [crate::data::DATABLOCK_PROGRESS]), so the final
+    /// time is 1.0.
+    Progress,
+    /// The number of records read (see [crate::data::DATABLOCK_RECORDS_READ]), so the final
+    /// time is the number of records of the scanned table.
+    RecordsRead,
+    /// The position of the snapshot, starting at 1, so the final time is the number of
+    /// snapshots. Unlike the others, this assumes that all the partitions have the same size.
+    Snapshots,
+}
+
+impl TimeAxis {
+    /// The time to forecast at: `final_time` if set, which is required on every axis but
+    /// [TimeAxis::Progress], where it defaults to the end of the scan (1.0).
+    pub fn final_time_or_default(&self, final_time: Option<TimeType>) -> TimeType {
+        match (self, final_time) {
+            (_, Some(final_time)) => final_time,
+            (TimeAxis::Progress, None) => 1.0,
+            (time_axis, None) => panic!(
+                "Forecast: no final_time set for TimeAxis::{:?}, which has no default",
+                time_axis
+            ),
+        }
+    }
+}
+
+/// Factory class for nodes extrapolating running aggregates to their final values. Placed
+/// after an [crate::polars_operations::AccumulatorNode], the node keeps one forecast state
+/// per group, and replaces the aggregate columns of each snapshot with the values predicted at
+/// `final_time`.
+///
+/// By default, snapshots are placed by the progress of the scan they come from, so the
+/// forecast targets the end of the scan (`final_time` 1.0), whatever the partition sizes.
+/// Other time axes have no default `final_time`, and [ForecastNode::build] panics without one.
+///
+/// Example:
+/// ```
+/// use wake::forecast::table::ForecastNode;
+///
+/// ForecastNode::new()
+///     .group_key(vec!["l_returnflag".into(), "l_linestatus".into()])
+///     .build();
+/// ```
+pub struct ForecastNode {
+    group_key: Vec<String>,
+    value_columns: Vec<String>,
+    time_axis: TimeAxis,
+    final_time: Option<TimeType>,
+    interval: Option<ValueType>,
+    selection: Selection,
+    estimator: Option<EstimatorFactory>,
+}
+
+impl Default for ForecastNode {
+    fn default() -> Self {
+        ForecastNode {
+            group_key: vec![],
+            value_columns: vec![],
+            time_axis: TimeAxis::Progress,
+            final_time: None,
+            interval: None,
+            selection: Selection::default(),
+            estimator: None,
+        }
+    }
+}
 
 impl ForecastNode {
-    pub fn node(schema: &Schema, final_time: TimeType) -> ExecutionNode<ArrayRow> {
-        let data_processor = TableForecast::new_boxed(schema, final_time);
-        ExecutionNode::<ArrayRow>::from_set_processor(data_processor)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The columns identifying a group; snapshots are expected to have one row per group.
+    pub fn group_key(&mut self, group_key: Vec<String>) -> &mut Self {
+        self.group_key = group_key;
+        self
+    }
+
+    /// The columns to forecast. Defaults to all the numeric columns not in the group key.
+    pub fn value_columns(&mut self, value_columns: Vec<String>) -> &mut Self {
+        self.value_columns = value_columns;
+        self
+    }
+
+    pub fn time_axis(&mut self, time_axis: TimeAxis) -> &mut Self {
+        self.time_axis = time_axis;
+        self
+    }
+
+    /// The time to predict the values at, on the time axis (see
+    /// [TimeAxis::final_time_or_default]).
+    pub fn final_time(&mut self, final_time: TimeType) -> &mut Self {
+        self.final_time = Some(final_time);
+        self
+    }
+
+    /// Also emits `<column>_lower` and `<column>_upper` columns, `z` standard deviations of
+    /// the prediction error around each forecast (e.g., 1.96 for 95% with normal errors).
+    pub fn interval(&mut self, z: ValueType) -> &mut Self {
+        self.interval = Some(z);
+        self
+    }
+
+    /// How each forecast picks among its candidate models. Defaults to the lowest rolling
+    /// error.
+    pub fn selection(&mut self, selection: Selection) -> &mut Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Forecasts every value with estimators made by `make` instead of a [ForecastSelector].
+    pub fn estimator(&mut self, make: EstimatorFactory) -> &mut Self {
+        self.estimator = Some(make);
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<DataFrame> {
+        let final_time = self.time_axis.final_time_or_default(self.final_time);
+        let mut data_processor = TableForecast::new(
+            self.group_key.clone(),
+            self.value_columns.clone(),
+            self.time_axis,
+            final_time,
+            self.interval,
+            self.selection,
+        );
+        if let Some(make) = self.estimator {
+            data_processor.set_estimator(make);
+        }
+        let node = ExecutionNode::<DataFrame>::new(Box::new(data_processor), 1);
+        node.set_label(&format!("Forecast: {:?} = {}", self.time_axis, final_time));
+        node
     }
 }
 
+/* Table forecast as a stream processor */
 
-/* Table forecast mapper as set processor */
+/// The values of the group key columns of a row. Compared exactly, so that distinct groups
+/// never share a forecast state.
+pub type ForecastKey = Vec<DataCell>;
 
-pub type ForecastKey = u64;  // TODO: what's appropriate type?
+/// Makes the estimator of a value of a group.
+pub type EstimatorFactory = fn() -> Box<dyn CellEstimator>;
 
 pub struct TableForecast {
     /*
-     * There are 3 column groups
-     *   - Key: columns to group values together [TODO: what if keys are not unique]
+     * There are 2 column groups
+     *   - Key: columns to group values together
      *   - Values: columns to be forecasted (numeric only)
      */
-    key_columns: Vec<String>,  // TODO: auto-detect this
-    values_columns: Vec<String>,  // TODO: auto-detect this
+    key_columns: Vec<String>,
+    values_columns: Vec<String>,
+    time_axis: TimeAxis,
     final_time: TimeType,
+    interval: Option<ValueType>,
+    selection: Selection,
+    estimator: Option<EstimatorFactory>,
 
     // State of the forecast estimator per row
     row_states: RefCell<HashMap<ForecastKey, RowForecast>>,
-    time_counter: RefCell<TimeType>,  // TODO: feed this from data block
 }
 
 impl TableForecast {
-    pub fn new(schema: &Schema, final_time: TimeType) -> TableForecast {
-        let (key_columns, values_columns) = TableForecast::extract_schema(schema);
+    pub fn new(
+        key_columns: Vec<String>,
+        values_columns: Vec<String>,
+        time_axis: TimeAxis,
+        final_time: TimeType,
+        interval: Option<ValueType>,
+        selection: Selection,
+    ) -> TableForecast {
         TableForecast {
             key_columns,
             values_columns,
+            time_axis,
             final_time,
+            interval,
+            selection,
+            estimator: None,
             row_states: RefCell::new(HashMap::new()),
-            time_counter: RefCell::new(0.0),
         }
     }
 
-    pub fn new_boxed(schema: &Schema, final_time: TimeType) -> Box<dyn SetProcessorV2<ArrayRow>> {
-        Box::new(Self::new(schema, final_time))
+    /// Forecasts every value with estimators made by `make` instead of a [ForecastSelector].
+    pub fn set_estimator(&mut self, make: EstimatorFactory) {
+        self.estimator = Some(make);
     }
 
     fn get_key_columns(&self) -> &[String] {
         &self.key_columns
     }
 
-    fn get_values_columns(&self) -> &[String] {
-        &self.values_columns
+    /// The columns to forecast in `df`: the configured ones, or else its numeric columns
+    /// outside of the group key.
+    pub(crate) fn get_values_columns(&self, df: &DataFrame) -> Vec<String> {
+        if !self.values_columns.is_empty() {
+            return self.values_columns.clone();
+        }
+        df.get_columns()
+            .iter()
+            .filter(|series| series.dtype().is_numeric())
+            .map(|series| series.name().to_string())
+            .filter(|name| !self.key_columns.contains(name))
+            .collect()
     }
 
-    fn extract_key(&self, key_cells: Vec<DataCell>) -> ForecastKey {
-        assert_eq!(key_cells.len(), self.get_key_columns().len());
-        DataCell::vector_hash(key_cells)  // TODO: is this unique?
+    /// The time of the snapshot in `dblock`, which is the snapshot_no-th one (from 1).
+    pub(crate) fn get_time(&self, dblock: &DataBlock<DataFrame>, snapshot_no: usize) -> TimeType {
+        let time = match self.time_axis {
+            TimeAxis::Progress => dblock.progress(),
+            TimeAxis::RecordsRead => dblock.records_read(),
+            TimeAxis::Snapshots => Some(snapshot_no as TimeType),
+        };
+        time.unwrap_or_else(|| {
+            panic!(
+                "Forecast: no {:?} in the metadata of the input; use TimeAxis::Snapshots",
+                self.time_axis
+            )
+        })
     }
 
-    fn extract_value(&self, value_cells: Vec<DataCell>) -> Vec<ValueType> {
-        assert_eq!(value_cells.len(), self.get_values_columns().len());
-        value_cells.iter().map(ValueType::from).collect()
+    /// The group key of each row of `df`.
+    pub(crate) fn row_keys(&self, df: &DataFrame) -> Vec<ForecastKey> {
+        let key_series: Vec<&Series> = self
+            .get_key_columns()
+            .iter()
+            .map(|column| df.column(column).unwrap())
+            .collect();
+        (0..df.height())
+            .map(|row| {
+                key_series
+                    .iter()
+                    .map(|series| key_cell(series.get(row)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The values of `columns` in each row of `df`, as floats (0 for nulls).
+    pub(crate) fn row_values(df: &DataFrame, columns: &[String]) -> Vec<Vec<ValueType>> {
+        let value_series: Vec<Series> = columns
+            .iter()
+            .map(|column| df.column(column).unwrap().cast(&DataType::Float64).unwrap())
+            .collect();
+        let value_chunks: Vec<&Float64Chunked> = value_series
+            .iter()
+            .map(|series| series.f64().unwrap())
+            .collect();
+        (0..df.height())
+            .map(|row| {
+                value_chunks
+                    .iter()
+                    .map(|chunk| chunk.get(row).unwrap_or(0.0))
+                    .collect()
+            })
+            .collect()
     }
 
-    fn fit_transform(&self, key: ForecastKey, values: Vec<ValueType>) -> Vec<ValueType> {
+    fn fit(
+        &self,
+        key: ForecastKey,
+        values: Vec<ValueType>,
+        time: TimeType,
+    ) -> Vec<Box<dyn CellForecast>> {
         match self.row_states.borrow_mut().entry(key) {
-              Entry::Occupied(mut entry) => entry.get_mut()
-                .fit_transform(values, *self.time_counter.borrow(), self.final_time),
-              Entry::Vacant(entry) => entry.insert(self.make_row_forecast())
-                .fit_transform(values, *self.time_counter.borrow(), self.final_time),
+            Entry::Occupied(mut entry) => entry.get_mut().fit(values, time),
+            Entry::Vacant(entry) => entry
+                .insert(self.make_row_forecast(values.len()))
+                .fit(values, time),
         }
     }
 
-    fn make_row_forecast(&self) -> RowForecast {
-      // TODO: should this be configurable?
-      let mut row_forecast = RowForecast::default();
-      for _forecast_column in &self.values_columns {
-        row_forecast.push_estimator(ForecastSelector::make_with_default_candidates());
-      }
-      row_forecast
+    fn make_row_forecast(&self, num_values: usize) -> RowForecast {
+        let mut row_forecast = RowForecast::default();
+        for _forecast_column in 0..num_values {
+            row_forecast.push_estimator(match self.estimator {
+                Some(make) => make(),
+                None => ForecastSelector::make_with_selection(self.selection),
+            });
+        }
+        row_forecast
     }
 
-    fn extract_schema(schema: &Schema) -> (Vec<String>, Vec<String>) {
-        let mut key_columns = Vec::new();
-        let mut values_columns = Vec::new();
-        for column in &schema.columns {
-            if column.key {
-                key_columns.push(column.name.clone());
-            } else {
-                values_columns.push(column.name.clone());
+    /// Replaces the values of a snapshot, observed at `time`, with their forecasts, and adds
+    /// their bounds if an interval is set.
+    pub(crate) fn forecast(&self, df: &DataFrame, time: TimeType) -> DataFrame {
+        // extract keys (to select row state) and values (to train and predict)
+        let values_columns = self.get_values_columns(df);
+        let record_keys = self.row_keys(df);
+        let records_values = TableForecast::row_values(df, &values_columns);
+
+        // run through and forecast each target cell
+        let mut forecast_columns = vec![Vec::with_capacity(df.height()); values_columns.len()];
+        let mut lower_columns = forecast_columns.clone();
+        let mut upper_columns = forecast_columns.clone();
+        for (record_key, record_values) in record_keys.into_iter().zip(records_values) {
+            // train and predict on corresponding row state
+            let forecasts = self.fit(record_key, record_values, time);
+            for (i, forecast) in forecasts.iter().enumerate() {
+                forecast_columns[i].push(forecast.predict(self.final_time));
+                if let Some(z) = self.interval {
+                    let (lower, upper) = forecast.interval(self.final_time, z);
+                    lower_columns[i].push(lower);
+                    upper_columns[i].push(upper);
+                }
             }
         }
-        (key_columns, values_columns)
+
+        // replace the forecasted columns, and add their bounds
+        let mut output_df = df.clone();
+        for (name, values) in values_columns.iter().zip(forecast_columns) {
+            output_df.with_column(Series::new(name, values)).unwrap();
+        }
+        if self.interval.is_some() {
+            let bounds = lower_columns.into_iter().zip(upper_columns);
+            for (name, (lower, upper)) in values_columns.iter().zip(bounds) {
+                let lower_name = format!("{}_lower", name);
+                let upper_name = format!("{}_upper", name);
+                output_df
+                    .with_column(Series::new(&lower_name, lower))
+                    .unwrap();
+                output_df
+                    .with_column(Series::new(&upper_name, upper))
+                    .unwrap();
+            }
+        }
+        output_df
     }
 }
 
-impl SetProcessorV2<ArrayRow> for TableForecast {
-    fn process_v1(
-        &self,
-        input_set: &DataBlock<ArrayRow>,
-    ) -> DataBlock<ArrayRow> {
-        // HACK: step time forward
-        // TODO: feed this from data block
-        let mut t = self.time_counter.borrow_mut();
-        *t += 1.0;
-
-        // Build output schema metadata
-        let input_schema = input_set
-            .metadata()
-            .get(SCHEMA_META_NAME)
-            .unwrap()
-            .to_schema();
-        let key_indexes: Vec<usize> = self.get_key_columns()
-            .iter()
-            .map(|column| input_schema.index(column))
-            .collect();
-        let value_indexes: Vec<usize> = self.get_values_columns()
-            .iter()
-            .map(|column| input_schema.index(column))
-            .collect();
+/// The group key cell for a DataFrame value. 64-bit integers outside the range of
+/// [DataCell::Integer] are kept as unsigned integers, or as floats if negative.
+fn key_cell(value: AnyValue) -> DataCell {
+    match value {
+        AnyValue::Null => DataCell::Null(),
+        AnyValue::Boolean(v) => DataCell::Boolean(v),
+        AnyValue::Utf8(v) => DataCell::from(v),
+        AnyValue::Int8(v) => DataCell::Integer(v.into()),
+        AnyValue::Int16(v) => DataCell::Integer(v.into()),
+        AnyValue::Int32(v) => DataCell::Integer(v),
+        AnyValue::Int64(v) => match (i32::try_from(v), usize::try_from(v)) {
+            (Ok(v), _) => DataCell::Integer(v),
+            (_, Ok(v)) => DataCell::UnsignedInt(v),
+            _ => DataCell::Float(v as f64),
+        },
+        AnyValue::UInt8(v) => DataCell::UnsignedInt(v.into()),
+        AnyValue::UInt16(v) => DataCell::UnsignedInt(v.into()),
+        AnyValue::UInt32(v) => DataCell::UnsignedInt(v as usize),
+        AnyValue::UInt64(v) => DataCell::UnsignedInt(v as usize),
+        AnyValue::Float32(v) => DataCell::Float(v.into()),
+        AnyValue::Float64(v) => DataCell::Float(v),
+        other => DataCell::from(other.to_string()),
+    }
+}
 
-        // run through and forecast each target cell
-        let output_records = input_set.data().iter()
-            .map(|record| {
-                // extract keys (to select row state) and values (to train and predict)
-                let record_key = self.extract_key(record.slice_indices(&key_indexes));
-                let record_values = self.extract_value(
-                    record.slice_indices(&value_indexes));
-
-                // train and predict on corresponding row state
-                let forecast_values = self.fit_transform(record_key, record_values);
-
-                // copy forecast values
-                let mut new_record = record.clone();
-                for (forecast_idx, value_idx) in value_indexes.iter().enumerate() {
-                    new_record[*value_idx] = DataCell::from(forecast_values[forecast_idx]);
+impl StreamProcessor<DataFrame> for TableForecast {
+    fn process_stream(
+        &self,
+        input_stream: crate::channel::MultiChannelReader<DataFrame>,
+        output_stream: crate::channel::MultiChannelBroadcaster<DataFrame>,
+    ) {
+        let mut snapshot_no = 0;
+        loop {
+            let channel_seq = 0;
+            let message = input_stream.read(channel_seq);
+            match message.payload() {
+                Payload::EOF => {
+                    output_stream.write(message);
+                    break;
                 }
-                new_record
-            })
-            .collect();
-
-        // compose message and yield
-        DataBlock::new(output_records, input_set.metadata().clone())
+                Payload::Signal(_) => break,
+                Payload::Some(dblock) => {
+                    snapshot_no += 1;
+                    let time = self.get_time(&dblock, snapshot_no);
+                    let df = self.forecast(dblock.data(), time);
+                    output_stream.write(DataMessage::from(DataBlock::new(
+                        df,
+                        dblock.progress_metadata(),
+                    )));
+                }
+            }
+        }
     }
 }
 
@@ -166,201 +409,238 @@ impl SetProcessorV2<ArrayRow> for TableForecast {
 mod tests {
     use super::*;
 
-    use crate::data::Column;
-    use crate::data::DataMessage;
-    use crate::data::DataType;
-    use crate::data::MetaCell;
+    use crate::data::{MetaCell, DATABLOCK_PROGRESS};
     use crate::graph::NodeReader;
 
-    // arrow message schema
-    fn example_arrow_schema() -> Schema {
-        Schema::from(vec![
-            Column::from_key_field("country".into(), DataType::Text),
-            Column::from_key_field("state".into(), DataType::Text),
-            Column::from_key_field("city".into(), DataType::Text),
-            Column::from_field("population".into(), DataType::Integer),
-            Column::from_field("area".into(), DataType::Float),
-        ])
-    }
-
-
-    // generate message according to time scale t
-    fn example_arrow_message(t: TimeType) -> DataMessage<ArrayRow> {
-        let metadata = MetaCell::from(example_arrow_schema())
-        .into_meta_map();
-
-        let input_rows = vec![
-            ArrayRow::from([
-                "US".into(),
-                "IL".into(),
-                "Champaign".into(),
-                // ((t * 100.0) as i32).into(),  // TODO: how to keep cell type
-                (t * 100.0).into(),
-                (t * 1.5f64).into(),
-            ]),
-            ArrayRow::from([
-                "US".into(),
-                "IL".into(),
-                "Urbana".into(),
-                // ((t * 200.0) as i32).into(),  // TODO: how to keep cell type
-                (t * 200.0).into(),
-                (t * 0.0f64).into(),
-            ]),
-            ArrayRow::from([
-                "US".into(),
-                "CA".into(),
-                "San Francisco".into(),
-                // ((t * 300.0) as i32).into(),  // TODO: how to keep cell type
-                (t * 300.0).into(),
-                (2.5f64.into()),
-            ]),
-            ArrayRow::from([
-                "US".into(),
-                "CA".into(),
-                "San Jose".into(),
-                // ((t * 400.0) as i32).into(),  // TODO: how to keep cell type
-                (t * 400.0).into(),
-                (3.5f64.into()),
-            ]),
-            ArrayRow::from([
-                "IN".into(),
-                "UP".into(),
-                "Lucknow".into(),
-                // ((t * 500.0) as i32).into(),  // TODO: how to keep cell type
-                (t * 500.0).into(),
-                (4.5f64.into()),
-            ]),
-            ArrayRow::from([
-                "IN".into(),
-                "UP".into(),
-                "Noida".into(),
-                // ((t * 600.0) as i32).into(),  // TODO: how to keep cell type
-                (t * 600.0).into(),
-                (t * 0.0f64).into(),
-            ]),
-        ];
-        DataMessage::from(DataBlock::new(input_rows, metadata))
-    }
-
-    // generate smaller message according to time scale t
-    fn example_arrow_message_small(t: TimeType) -> DataMessage<ArrayRow> {
-        let metadata = MetaCell::from(example_arrow_schema())
-        .into_meta_map();
-
-        let input_rows = vec![
-            ArrayRow::from([
-                "US".into(),
-                "IL".into(),
-                "Champaign".into(),
-                // ((t * 100.0) as i32).into(),  // TODO: how to keep cell type
-                (t * 100.0).into(),
-                (t * 1.5f64).into(),
-            ]),
-            ArrayRow::from([
-                "US".into(),
-                "IL".into(),
-                "Urbana".into(),
-                // ((t * 200.0) as i32).into(),  // TODO: how to keep cell type
-                (t * 200.0).into(),
-                (t * 0.0f64).into(),
-            ]),
-            ArrayRow::from([
-                "US".into(),
-                "CA".into(),
-                "San Francisco".into(),
-                // ((t * 300.0) as i32).into(),  // TODO: how to keep cell type
-                (t * 300.0).into(),
-                (2.5f64.into()),
-            ]),
-        ];
-        DataMessage::from(DataBlock::new(input_rows, metadata))
-    }
-
-    fn make_arrow_forecast_node(schema: &Schema, final_time: TimeType) -> ExecutionNode<ArrayRow> {
-        ForecastNode::node(schema, final_time)
+    const CITIES: [&str; 6] = [
+        "Champaign",
+        "Urbana",
+        "San Francisco",
+        "San Jose",
+        "Lucknow",
+        "Noida",
+    ];
+
+    // generate a snapshot of the first `num_cities` cities according to time scale t
+    fn example_df(t: TimeType, num_cities: usize) -> DataFrame {
+        let population: Vec<f64> = (1..=num_cities).map(|i| t * 100.0 * i as f64).collect();
+        let area: Vec<f64> = [t * 1.5, 0.0, 2.5, 3.5, 4.5, 0.0][..num_cities].to_vec();
+        df!(
+            "city" => &CITIES[..num_cities],
+            "population" => population,
+            "area" => area,
+        )
+        .unwrap()
     }
 
-    #[test]
-    fn test_perfect_arrow_message() {
-        let final_time = 10;
-        let schema = example_arrow_schema();
-        let forecast_node = make_arrow_forecast_node(&schema, final_time as TimeType);
+    fn assert_close(actual: &DataFrame, expected: &DataFrame) {
+        assert_eq!(actual.shape(), expected.shape());
+        assert!(actual
+            .column("city")
+            .unwrap()
+            .series_equal(expected.column("city").unwrap()));
+        for column in ["population", "area"] {
+            let actual = actual.column(column).unwrap().f64().unwrap();
+            let expected = expected.column(column).unwrap().f64().unwrap();
+            for (a, e) in actual.into_no_null_iter().zip(expected.into_no_null_iter()) {
+                assert!((a - e).abs() < 1e-6, "{}: {} != {}", column, a, e);
+            }
+        }
+    }
 
-        // push partitions
-        for t in 1 .. final_time {
-            let message = example_arrow_message(t as TimeType);
-            forecast_node.write_to_self(0, message)
+    fn run_forecast(
+        forecast_node: ExecutionNode<DataFrame>,
+        snapshots: Vec<DataBlock<DataFrame>>,
+    ) -> Vec<DataFrame> {
+        for dblock in snapshots {
+            forecast_node.write_to_self(0, DataMessage::from(dblock));
         }
         forecast_node.write_to_self(0, DataMessage::eof());
         let reader_node = NodeReader::new(&forecast_node);
         forecast_node.run();
 
-        // inspect message
-        let fc_message = reader_node.read();
-        let fc_data = fc_message.datablock().data();
-        assert_eq!(fc_data.len(), 6);
-        assert_eq!(fc_data[0].len(), 5);
-
-        // expecting perfect prediction on later rows
-        let expected_message = example_arrow_message(final_time as TimeType);
-        let expected_data = expected_message.datablock().data();
-        for _t in 2 .. final_time {
-            let fc_message = reader_node.read();
-            let fc_data = fc_message.datablock().data();
-            assert_eq!(fc_data, expected_data);
+        let mut outputs = vec![];
+        loop {
+            let message = reader_node.read();
+            if message.is_eof() {
+                break;
+            }
+            outputs.push(message.datablock().data().clone());
         }
+        outputs
+    }
+
+    fn snapshot_forecast_node(final_time: usize) -> ExecutionNode<DataFrame> {
+        ForecastNode::new()
+            .group_key(vec!["city".into()])
+            .time_axis(TimeAxis::Snapshots)
+            .final_time(final_time as TimeType)
+            .build()
     }
 
     #[test]
-    fn test_perfect_arrow_message_new_value() {
+    fn can_forecast_linear_growth() {
+        let final_time = 10;
+        let snapshots = (1..final_time)
+            .map(|t| DataBlock::from(example_df(t as TimeType, 6)))
+            .collect();
+        let outputs = run_forecast(snapshot_forecast_node(final_time), snapshots);
+        assert_eq!(outputs.len(), final_time - 1);
+
+        // expecting perfect prediction on later snapshots
+        let expected_df = example_df(final_time as TimeType, 6);
+        for df in &outputs[1..] {
+            assert_close(df, &expected_df);
+        }
+    }
+
+    #[test]
+    fn can_forecast_with_selection() {
+        let final_time = 10;
+        for selection in [Selection::Aicc, Selection::Bic] {
+            let snapshots = (1..final_time)
+                .map(|t| DataBlock::from(example_df(t as TimeType, 6)))
+                .collect();
+            let forecast_node = ForecastNode::new()
+                .group_key(vec!["city".into()])
+                .time_axis(TimeAxis::Snapshots)
+                .final_time(final_time as TimeType)
+                .selection(selection)
+                .build();
+            let outputs = run_forecast(forecast_node, snapshots);
+
+            // the linear growth is found once there are enough snapshots to score the models
+            let expected_df = example_df(final_time as TimeType, 6);
+            for df in &outputs[4..] {
+                assert_close(df, &expected_df);
+            }
+        }
+    }
+
+    #[test]
+    fn can_forecast_new_groups() {
         // suddenly rows with new keys show up after mid_time
         let mid_time = 12;
         let final_time = 20;
-        let schema = example_arrow_schema();
-        let forecast_node = make_arrow_forecast_node(&schema, final_time as TimeType);
+        let snapshots = (1..final_time)
+            .map(|t| DataBlock::from(example_df(t as TimeType, if t < mid_time { 3 } else { 6 })))
+            .collect();
+        let outputs = run_forecast(snapshot_forecast_node(final_time), snapshots);
 
-        // push partitions
-        for t in 1 .. mid_time {
-            let message = example_arrow_message_small(t as TimeType);
-            forecast_node.write_to_self(0, message)
+        let expected_df = example_df(final_time as TimeType, 3);
+        for df in &outputs[1..mid_time - 1] {
+            assert_close(df, &expected_df);
         }
-        for t in mid_time .. final_time {
-            let message = example_arrow_message(t as TimeType);
-            forecast_node.write_to_self(0, message)
+        let expected_df = example_df(final_time as TimeType, 6);
+        for df in &outputs[mid_time..] {
+            assert_close(df, &expected_df);
         }
-        forecast_node.write_to_self(0, DataMessage::eof());
-        let reader_node = NodeReader::new(&forecast_node);
-        forecast_node.run();
+    }
 
-        // inspect message
-        let fc_message = reader_node.read();
-        let fc_data = fc_message.datablock().data();
-        assert_eq!(fc_data.len(), 3);
-        assert_eq!(fc_data[0].len(), 5);
-
-        // expecting perfect prediction on later rows
-        let expected_message = example_arrow_message_small(final_time as TimeType);
-        let expected_data = expected_message.datablock().data();
-        for _t in 2 .. mid_time {
-            let fc_message = reader_node.read();
-            let fc_data = fc_message.datablock().data();
-            assert_eq!(fc_data, expected_data);
+    #[test]
+    fn can_forecast_float_groups() {
+        // groups told apart by a float key only
+        let final_time = 10;
+        let snapshots = (1..final_time)
+            .map(|t| {
+                let t = t as f64;
+                let df = df!(
+                    "city" => &["Urbana", "Urbana", "Urbana"],
+                    "latitude" => &[40.1, 40.2, f64::NAN],
+                    "population" => &[t * 100.0, t * 200.0, t * 300.0],
+                )
+                .unwrap();
+                DataBlock::from(df)
+            })
+            .collect();
+        let forecast_node = ForecastNode::new()
+            .group_key(vec!["city".into(), "latitude".into()])
+            .time_axis(TimeAxis::Snapshots)
+            .final_time(final_time as TimeType)
+            .build();
+        let outputs = run_forecast(forecast_node, snapshots);
+
+        for df in &outputs[1..] {
+            let population = df.column("population").unwrap().f64().unwrap();
+            let expected = [1000.0, 2000.0, 3000.0];
+            for (a, e) in population.into_no_null_iter().zip(expected) {
+                assert!((a - e).abs() < 1e-6, "{} != {}", a, e);
+            }
         }
+    }
 
-        // inspect message, new keys
-        let fc_message = reader_node.read();
-        let fc_data = fc_message.datablock().data();
-        assert_eq!(fc_data.len(), 6);
-        assert_eq!(fc_data[0].len(), 5);
-
-        // expecting perfect prediction on later rows
-        let expected_message = example_arrow_message(final_time as TimeType);
-        let expected_data = expected_message.datablock().data();
-        for _t in mid_time + 1 .. final_time {
-            let fc_message = reader_node.read();
-            let fc_data = fc_message.datablock().data();
-            println!("{:?}\n", expected_data);
-            assert_eq!(fc_data, expected_data);
+    #[test]
+    fn can_forecast_by_progress() {
+        // partitions of different sizes: the values grow with the fraction of input read
+        let progress = [0.1, 0.15, 0.4, 0.5, 0.8];
+        let snapshots = progress
+            .iter()
+            .map(|p| {
+                let metadata = HashMap::from([(DATABLOCK_PROGRESS.into(), MetaCell::from(*p))]);
+                DataBlock::new(example_df(*p * 10.0, 6), metadata)
+            })
+            .collect();
+        let forecast_node = ForecastNode::new().group_key(vec!["city".into()]).build();
+        assert_eq!(forecast_node.label(), "Forecast: Progress = 1");
+        let outputs = run_forecast(forecast_node, snapshots);
+
+        // the same values as after 10 equal partitions
+        let expected_df = example_df(10.0, 6);
+        for df in &outputs[1..] {
+            assert_close(df, &expected_df);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[should_panic(expected = "no final_time set for TimeAxis::RecordsRead")]
+    fn requires_final_time_off_progress() {
+        assert_eq!(TimeAxis::Progress.final_time_or_default(None), 1.0);
+        assert_eq!(TimeAxis::Snapshots.final_time_or_default(Some(8.0)), 8.0);
+        ForecastNode::new().time_axis(TimeAxis::RecordsRead).build();
+    }
+
+    #[test]
+    fn can_emit_intervals() {
+        // a noisy linear growth
+        let snapshots = (1..8)
+            .map(|t| {
+                let noise = if t % 2 == 0 { 10.0 } else { -10.0 };
+                let population = [t as f64 * 100.0 + noise];
+                DataBlock::from(df!("city" => &["Urbana"], "population" => &population).unwrap())
+            })
+            .collect();
+        let forecast_node = ForecastNode::new()
+            .group_key(vec!["city".into()])
+            .time_axis(TimeAxis::Snapshots)
+            .final_time(10.0)
+            .interval(1.96)
+            .build();
+        let outputs = run_forecast(forecast_node, snapshots);
+
+        let df = outputs.last().unwrap();
+        assert_eq!(
+            df.get_column_names(),
+            ["city", "population", "population_lower", "population_upper"]
+        );
+        let value = |column: &str| df.column(column).unwrap().f64().unwrap().get(0).unwrap();
+        assert!(value("population_lower") < value("population"));
+        assert!(value("population") < value("population_upper"));
+    }
+
+    #[test]
+    fn keys_integers_by_value() {
+        assert_eq!(key_cell(AnyValue::Int8(-3)), DataCell::Integer(-3));
+        assert_eq!(key_cell(AnyValue::Int16(300)), DataCell::Integer(300));
+        assert_eq!(key_cell(AnyValue::UInt8(7)), DataCell::UnsignedInt(7));
+        assert_eq!(key_cell(AnyValue::UInt16(700)), DataCell::UnsignedInt(700));
+        assert_eq!(key_cell(AnyValue::Int64(42)), DataCell::Integer(42));
+        assert_eq!(
+            key_cell(AnyValue::Int64(1 << 40)),
+            DataCell::UnsignedInt(1 << 40)
+        );
+        assert_eq!(
+            key_cell(AnyValue::Int64(-(1 << 40))),
+            DataCell::Float(-((1u64 << 40) as f64))
+        );
+    }
+}
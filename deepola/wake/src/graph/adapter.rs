@@ -0,0 +1,201 @@
+use std::sync::{Arc, Mutex};
+
+use polars::prelude::DataFrame;
+
+use crate::channel::{ChannelWriter, MultiChannelBroadcaster, MultiChannelReader};
+use crate::data::{ArrayRow, DataBlock, DataMessage, EstimatedSize, Payload};
+use crate::processor::StreamProcessor;
+
+use super::node::ExecutionNode;
+use super::node_base::Subscribable;
+
+/// Converts a DataBlock of one data type into a DataBlock of another.
+pub type BlockConverter<I, O> = Arc<dyn Fn(&DataBlock<I>) -> DataBlock<O>>;
+
+/// (input channel of `I`) -> [AdapterNode] -> (output channels of `O`)
+///
+/// Switches the data type of a pipeline, e.g., from DataFrames to ArrayRows. The adapter runs
+/// as an `ExecutionNode<I>`, added to the service running its producers; the nodes reading
+/// its output subscribe to the adapter itself, and run in a service of their own. The input
+/// can be DataFrames or blocks of ArrayRows (see [BlockAdapter]).
+///
+/// Example:
+/// ```
+/// use wake::data::{ArrayRow, DataBlock, DataMessage};
+/// use wake::graph::{AdapterNode, ExecutionService, NodeReader};
+///
+/// let counts = AdapterNode::new(|dblock: &DataBlock<Vec<ArrayRow>>| {
+///     DataBlock::new(dblock.data().len(), dblock.progress_metadata())
+/// });
+/// let reader = NodeReader::new(&counts);
+/// counts.node().write_to_self(0, DataMessage::from(ArrayRow::from_example()));
+/// counts.node().write_to_self(0, DataMessage::eof());
+///
+/// let mut service = ExecutionService::<Vec<ArrayRow>>::create();
+/// service.add(counts.into_node());
+/// service.run();
+/// assert_eq!(*reader.read().datablock().data(), 2);
+/// service.join();
+/// ```
+pub struct AdapterNode<I: Send, O: Send> {
+    node: ExecutionNode<I>,
+    outputs: Arc<Mutex<MultiChannelBroadcaster<O>>>,
+}
+
+impl<I: EstimatedSize + Send + 'static, O: EstimatedSize + Send + 'static> AdapterNode<I, O>
+where
+    BlockAdapter<I, O>: StreamProcessor<I>,
+{
+    pub fn new<F>(convert: F) -> Self
+    where
+        F: Fn(&DataBlock<I>) -> DataBlock<O> + 'static,
+    {
+        let outputs = Arc::new(Mutex::new(MultiChannelBroadcaster::new()));
+        let data_processor = BlockAdapter {
+            convert: Arc::new(convert),
+            outputs: outputs.clone(),
+        };
+        let node = ExecutionNode::<I>::new(Box::new(data_processor), 1);
+        node.set_label("Adapter");
+        // What the adapter sends is of type O, so its statistics are recorded here.
+        let stats = node.stats();
+        let mut broadcaster = outputs.lock().unwrap();
+        *broadcaster = MultiChannelBroadcaster::new().with_stats(stats, O::row_count);
+        drop(broadcaster);
+        Self { node, outputs }
+    }
+}
+
+impl<I: EstimatedSize + Send + 'static, O: Send> AdapterNode<I, O> {
+    /// Makes `source_node` write its output to the for_channel-th (i.e., the only) input
+    /// channel of the adapter.
+    pub fn subscribe_to_node(&self, source_node: &dyn Subscribable<I>, for_channel: usize) {
+        self.node.subscribe_to_node(source_node, for_channel);
+    }
+
+    /// The node running the adapter, e.g., to write to its input or to set its label.
+    pub fn node(&self) -> &ExecutionNode<I> {
+        &self.node
+    }
+
+    /// The node running the adapter, to be added to an [super::ExecutionService] once the
+    /// nodes reading from the adapter have subscribed to it.
+    pub fn into_node(self) -> ExecutionNode<I> {
+        self.node
+    }
+}
+
+impl<I: Send, O: Send> Subscribable<O> for AdapterNode<I, O> {
+    fn add(&self, channel_writer: ChannelWriter<O>) {
+        self.outputs.lock().unwrap().push(channel_writer);
+    }
+}
+
+/// The processor of an [AdapterNode], which sends the converted DataBlocks to the subscribers
+/// of the adapter. It only processes streams of DataFrames and of blocks of ArrayRows: an
+/// implementation for any data type would overlap with that of
+/// [crate::processor::MessageProcessor]s.
+pub struct BlockAdapter<I: Send, O: Send> {
+    convert: BlockConverter<I, O>,
+    outputs: Arc<Mutex<MultiChannelBroadcaster<O>>>,
+}
+
+/// Needed to be sent to different threads.
+unsafe impl<I: Send, O: Send> Send for BlockAdapter<I, O> {}
+
+impl<O: Send> StreamProcessor<DataFrame> for BlockAdapter<DataFrame, O> {
+    fn process_stream(
+        &self,
+        input_stream: MultiChannelReader<DataFrame>,
+        output_stream: MultiChannelBroadcaster<DataFrame>,
+    ) {
+        self.adapt(input_stream, output_stream);
+    }
+}
+
+impl<O: Send> StreamProcessor<Vec<ArrayRow>> for BlockAdapter<Vec<ArrayRow>, O> {
+    fn process_stream(
+        &self,
+        input_stream: MultiChannelReader<Vec<ArrayRow>>,
+        output_stream: MultiChannelBroadcaster<Vec<ArrayRow>>,
+    ) {
+        self.adapt(input_stream, output_stream);
+    }
+}
+
+impl<I: Send, O: Send> BlockAdapter<I, O> {
+    fn adapt(
+        &self,
+        input_stream: MultiChannelReader<I>,
+        output_stream: MultiChannelBroadcaster<I>,
+    ) {
+        let outputs = self.outputs.lock().unwrap().clone();
+        loop {
+            let message = input_stream.read(0);
+            match message.payload() {
+                Payload::EOF => {
+                    outputs.write(DataMessage::eof());
+                    output_stream.write(message);
+                    break;
+                }
+                Payload::Signal(_) => break,
+                Payload::Some(dblock) => {
+                    outputs.write(DataMessage::from((self.convert)(&dblock)));
+                    if outputs.is_closed() {
+                        input_stream.close();
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{DataCell, MetaCell, DATABLOCK_PROGRESS};
+    use crate::graph::{ExecutionService, NodeReader};
+    use crate::processor::SimpleMapper;
+    use std::collections::HashMap;
+
+    #[test]
+    fn can_switch_data_type() {
+        // ArrayRow pipeline -> adapter -> usize pipeline
+        let source = ExecutionNode::<Vec<ArrayRow>>::from(SimpleMapper::identity());
+        let counts = AdapterNode::new(|dblock: &DataBlock<Vec<ArrayRow>>| {
+            DataBlock::new(dblock.data().len(), dblock.progress_metadata())
+        });
+        counts.subscribe_to_node(&source, 0);
+        let doubles = ExecutionNode::<usize>::from(SimpleMapper::from(|n: &usize| Some(2 * n)));
+        doubles.subscribe_to_node(&counts, 0);
+        let reader = NodeReader::new(&doubles);
+
+        for (num_rows, progress) in [(2, 0.5), (4, 1.0)] {
+            let rows = vec![ArrayRow::from([DataCell::from(1)]); num_rows];
+            let metadata = HashMap::from([(DATABLOCK_PROGRESS.into(), MetaCell::from(progress))]);
+            source.write_to_self(0, DataMessage::from(DataBlock::new(rows, metadata)));
+        }
+        source.write_to_self(0, DataMessage::eof());
+
+        let stats = counts.node().stats();
+        let mut row_service = ExecutionService::<Vec<ArrayRow>>::create();
+        row_service.add(source);
+        row_service.add(counts.into_node());
+        let mut usize_service = ExecutionService::<usize>::create();
+        usize_service.add(doubles);
+        row_service.run();
+        usize_service.run();
+
+        let message = reader.read();
+        assert_eq!(*message.datablock().data(), 4);
+        assert_eq!(message.datablock().progress(), Some(0.5));
+        let message = reader.read();
+        assert_eq!(*message.datablock().data(), 8);
+        assert_eq!(message.datablock().progress(), Some(1.0));
+        assert!(reader.read().is_eof());
+        row_service.join();
+        usize_service.join();
+        assert_eq!(stats.messages(), 2);
+    }
+}
@@ -0,0 +1,385 @@
+use std::any::{type_name, Any, TypeId};
+use std::error::Error;
+use std::fmt;
+use std::thread::{self, JoinHandle};
+
+use crate::channel::ChannelWriter;
+use crate::data::EstimatedSize;
+
+use super::adapter::{AdapterNode, BlockAdapter};
+use super::node::{ExecutionNode, NodeReader};
+use super::node_base::Subscribable;
+use crate::processor::StreamProcessor;
+
+/// The data type carried by the DataBlocks of a channel, e.g., `DataFrame` or `Vec<ArrayRow>`.
+#[derive(Clone, Copy, Debug)]
+pub struct PayloadType {
+    id: TypeId,
+    name: &'static str,
+}
+
+impl PayloadType {
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+}
+
+impl PartialEq for PayloadType {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for PayloadType {}
+
+impl fmt::Display for PayloadType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A node of an [ExecutionGraph], whatever the data types of its input and output channels.
+pub trait GraphNode: Send {
+    fn input_type(&self) -> PayloadType;
+
+    fn output_type(&self) -> PayloadType;
+
+    fn num_inputs(&self) -> usize;
+
+    fn label(&self) -> String;
+
+    /// Makes `producer` write its output to the for_channel-th input channel. The output type of
+    /// `producer` must be the input type of this node.
+    fn subscribe_to_graph_node(&self, producer: &dyn GraphNode, for_channel: usize);
+
+    /// Adds an output channel. `channel_writer` must be a `ChannelWriter` of the output type.
+    fn add_output(&self, channel_writer: Box<dyn Any>);
+
+    fn run(&self);
+}
+
+/// The output channels of a [GraphNode], for a consumer reading its output type.
+struct GraphNodeOutput<'a>(&'a dyn GraphNode);
+
+impl<'a, T: Send + 'static> Subscribable<T> for GraphNodeOutput<'a> {
+    fn add(&self, channel_writer: ChannelWriter<T>) {
+        self.0.add_output(Box::new(channel_writer));
+    }
+}
+
+/// Downcasts a writer given to [GraphNode::add_output].
+fn downcast_writer<T: Send + 'static>(channel_writer: Box<dyn Any>) -> ChannelWriter<T> {
+    match channel_writer.downcast::<ChannelWriter<T>>() {
+        Ok(channel_writer) => *channel_writer,
+        Err(_) => panic!("Not a writer of {}", type_name::<T>()),
+    }
+}
+
+impl<T: EstimatedSize + Send + 'static> GraphNode for ExecutionNode<T> {
+    fn input_type(&self) -> PayloadType {
+        PayloadType::of::<T>()
+    }
+
+    fn output_type(&self) -> PayloadType {
+        PayloadType::of::<T>()
+    }
+
+    fn num_inputs(&self) -> usize {
+        self.input_reader().readers.len()
+    }
+
+    fn label(&self) -> String {
+        ExecutionNode::label(self)
+    }
+
+    fn subscribe_to_graph_node(&self, producer: &dyn GraphNode, for_channel: usize) {
+        self.subscribe_to_node(&GraphNodeOutput(producer), for_channel);
+    }
+
+    fn add_output(&self, channel_writer: Box<dyn Any>) {
+        self.add(downcast_writer::<T>(channel_writer));
+    }
+
+    fn run(&self) {
+        ExecutionNode::run(self)
+    }
+}
+
+/// Needed to be sent to different threads.
+unsafe impl<I: Send, O: Send> Send for AdapterNode<I, O> {}
+
+impl<I: EstimatedSize + Send + 'static, O: EstimatedSize + Send + 'static> GraphNode
+    for AdapterNode<I, O>
+where
+    BlockAdapter<I, O>: StreamProcessor<I>,
+{
+    fn input_type(&self) -> PayloadType {
+        PayloadType::of::<I>()
+    }
+
+    fn output_type(&self) -> PayloadType {
+        PayloadType::of::<O>()
+    }
+
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn label(&self) -> String {
+        self.node().label()
+    }
+
+    fn subscribe_to_graph_node(&self, producer: &dyn GraphNode, for_channel: usize) {
+        self.subscribe_to_node(&GraphNodeOutput(producer), for_channel);
+    }
+
+    fn add_output(&self, channel_writer: Box<dyn Any>) {
+        self.add(downcast_writer::<O>(channel_writer));
+    }
+
+    fn run(&self) {
+        self.node().run()
+    }
+}
+
+/// The index of a node in an [ExecutionGraph].
+pub type NodeId = usize;
+
+/// Runs nodes whose channels carry different data types, e.g., DataFrame operators feeding
+/// ArrayRow operators through a [crate::polars_operations::ToRowsNode]. Unlike
+/// [super::ExecutionService], which runs nodes of a single type, the graph wires the nodes
+/// itself, and checks that every channel connects nodes of the same type.
+///
+/// Example:
+/// ```
+/// use wake::data::{ArrayRow, DataBlock, DataMessage};
+/// use wake::graph::{AdapterNode, ExecutionGraph, ExecutionNode};
+/// use wake::processor::SimpleMapper;
+///
+/// let mut graph = ExecutionGraph::create();
+/// let rows = graph.add(ExecutionNode::<Vec<ArrayRow>>::from(SimpleMapper::identity()));
+/// let count = graph.add(AdapterNode::new(|dblock: &DataBlock<Vec<ArrayRow>>| {
+///     DataBlock::new(dblock.data().len(), dblock.progress_metadata())
+/// }));
+/// graph.connect(rows, count, 0).unwrap();
+/// // a node of ArrayRows cannot read the counts
+/// let other_rows = graph.add(ExecutionNode::<Vec<ArrayRow>>::create());
+/// assert!(graph.connect(count, other_rows, 0).is_err());
+/// ```
+pub struct ExecutionGraph {
+    nodes: Vec<Box<dyn GraphNode>>,
+
+    /// The producers connected to each node, with their input channels.
+    inputs: Vec<Vec<(usize, NodeId)>>,
+
+    thread_handles: Vec<JoinHandle<Box<dyn GraphNode>>>,
+}
+
+impl ExecutionGraph {
+    pub fn create() -> Self {
+        ExecutionGraph {
+            nodes: vec![],
+            inputs: vec![],
+            thread_handles: vec![],
+        }
+    }
+
+    /// Registers a node (e.g., an [ExecutionNode] or an [AdapterNode]), which is **owned** by
+    /// the graph now. Nodes are only registered before [Self::run] and after [Self::join].
+    pub fn add<N: GraphNode + 'static>(&mut self, node: N) -> NodeId {
+        self.assert_not_running();
+        self.nodes.push(Box::new(node));
+        self.inputs.push(vec![]);
+        self.nodes.len() - 1
+    }
+
+    fn assert_not_running(&self) {
+        if !self.thread_handles.is_empty() {
+            panic!("There are {} thread handles.", self.thread_handles.len());
+        }
+    }
+
+    /// Makes `producer` write its output to the for_channel-th input channel of `consumer`.
+    /// Fails if the output type of `producer` is not the input type of `consumer`, or if
+    /// `consumer` has no such channel.
+    pub fn connect(
+        &mut self,
+        producer: NodeId,
+        consumer: NodeId,
+        for_channel: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        self.assert_not_running();
+        let producer_node = self.node(producer)?;
+        let consumer_node = self.node(consumer)?;
+        if producer_node.output_type() != consumer_node.input_type() {
+            return Err(format!(
+                "Cannot connect {} (sends {}) to {} (reads {})",
+                producer_node.label(),
+                producer_node.output_type(),
+                consumer_node.label(),
+                consumer_node.input_type()
+            )
+            .into());
+        }
+        if for_channel >= consumer_node.num_inputs() {
+            return Err(format!(
+                "{} has no input channel {} (it has {})",
+                consumer_node.label(),
+                for_channel,
+                consumer_node.num_inputs()
+            )
+            .into());
+        }
+        consumer_node.subscribe_to_graph_node(producer_node, for_channel);
+        self.inputs[consumer].push((for_channel, producer));
+        Ok(())
+    }
+
+    /// A reader of the output of `producer`, whose type must be `T`.
+    pub fn reader<T: EstimatedSize + Send + Clone + 'static>(
+        &self,
+        producer: NodeId,
+    ) -> Result<NodeReader<T>, Box<dyn Error>> {
+        let producer_node = self.node(producer)?;
+        if producer_node.output_type() != PayloadType::of::<T>() {
+            return Err(format!(
+                "Cannot read {} from {} (sends {})",
+                PayloadType::of::<T>(),
+                producer_node.label(),
+                producer_node.output_type()
+            )
+            .into());
+        }
+        let mut reader = NodeReader::empty();
+        reader.subscribe_to_node(&GraphNodeOutput(producer_node), 0);
+        Ok(reader)
+    }
+
+    fn node(&self, id: NodeId) -> Result<&dyn GraphNode, Box<dyn Error>> {
+        match self.nodes.get(id) {
+            Some(node) => Ok(node.as_ref()),
+            None => Err(format!("No node {} in the graph", id).into()),
+        }
+    }
+
+    /// The nodes as a tree with one node per line, with the data type of their output, as in
+    /// [super::ExecutionService::explain].
+    pub fn explain(&self) -> String {
+        let mut consumed = vec![false; self.nodes.len()];
+        for inputs in &self.inputs {
+            for (_, producer) in inputs {
+                consumed[*producer] = true;
+            }
+        }
+        let mut lines = vec![];
+        for root in (0..self.nodes.len()).filter(|node| !consumed[*node]) {
+            self.format_lines(root, 0, &mut lines);
+        }
+        lines.join("\n")
+    }
+
+    fn format_lines(&self, node: NodeId, depth: usize, lines: &mut Vec<String>) {
+        lines.push(format!(
+            "{}{} -> {}",
+            "  ".repeat(depth),
+            self.nodes[node].label(),
+            self.nodes[node].output_type()
+        ));
+        let mut inputs = self.inputs[node].clone();
+        inputs.sort();
+        for (_, producer) in inputs {
+            self.format_lines(producer, depth + 1, lines);
+        }
+    }
+
+    pub fn run(&mut self) {
+        self.assert_not_running();
+        while let Some(node) = self.nodes.pop() {
+            let handle = thread::spawn(move || {
+                node.run();
+                node
+            });
+            self.thread_handles.push(handle);
+        }
+    }
+
+    /// Waits for all the nodes to finish. The nodes are then registered again, with the same
+    /// ids.
+    pub fn join(&mut self) {
+        while let Some(handle) = self.thread_handles.pop() {
+            self.nodes.push(handle.join().unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{ArrayRow, DataBlock, DataCell, DataMessage};
+    use crate::processor::SimpleMapper;
+
+    /// ArrayRow source -> adapter -> usize mapper
+    fn counting_graph() -> (ExecutionGraph, NodeId, NodeId, NodeId) {
+        let mut graph = ExecutionGraph::create();
+        let source = ExecutionNode::<Vec<ArrayRow>>::from(SimpleMapper::identity());
+        source.set_label("Source");
+        for num_rows in [2, 3] {
+            let rows = vec![ArrayRow::from([DataCell::from(1)]); num_rows];
+            source.write_to_self(0, DataMessage::from(rows));
+        }
+        source.write_to_self(0, DataMessage::eof());
+        let source = graph.add(source);
+        let counts = AdapterNode::new(|dblock: &DataBlock<Vec<ArrayRow>>| {
+            DataBlock::new(dblock.data().len(), dblock.progress_metadata())
+        });
+        counts.node().set_label("Count");
+        let counts = graph.add(counts);
+        let doubles = ExecutionNode::<usize>::from(SimpleMapper::from(|n: &usize| Some(2 * n)));
+        doubles.set_label("Double");
+        let doubles = graph.add(doubles);
+        (graph, source, counts, doubles)
+    }
+
+    #[test]
+    fn can_run_nodes_of_different_types() {
+        let (mut graph, source, counts, doubles) = counting_graph();
+        graph.connect(source, counts, 0).unwrap();
+        graph.connect(counts, doubles, 0).unwrap();
+        let reader = graph.reader::<usize>(doubles).unwrap();
+        assert_eq!(
+            graph.explain(),
+            format!(
+                "Double -> usize\n  Count -> usize\n    Source -> {}",
+                type_name::<Vec<ArrayRow>>()
+            )
+        );
+
+        graph.run();
+        assert_eq!(*reader.read().datablock().data(), 4);
+        assert_eq!(*reader.read().datablock().data(), 6);
+        assert!(reader.read().is_eof());
+        graph.join();
+        assert_eq!(graph.node(doubles).unwrap().label(), "Double");
+    }
+
+    #[test]
+    fn cannot_connect_different_types() {
+        let (mut graph, source, counts, doubles) = counting_graph();
+        let error = graph.connect(source, doubles, 0).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Cannot connect Source (sends {0}) to Double (reads usize)",
+                type_name::<Vec<ArrayRow>>()
+            )
+        );
+        assert!(graph.connect(counts, source, 0).is_err());
+        assert!(graph.connect(source, counts, 1).is_err());
+        assert!(graph.connect(source, 7, 0).is_err());
+        assert!(graph.reader::<Vec<ArrayRow>>(counts).is_err());
+        assert!(graph.reader::<usize>(counts).is_ok());
+    }
+}
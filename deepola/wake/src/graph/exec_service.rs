@@ -1,6 +1,9 @@
 use getset::Getters;
+use std::collections::{HashMap, HashSet};
 use std::thread::{self, JoinHandle};
 
+use crate::data::EstimatedSize;
+
 use super::node::ExecutionNode;
 
 #[derive(Getters)]
@@ -11,7 +14,7 @@ pub struct ExecutionService<T: Send> {
     thread_handles: Vec<JoinHandle<ExecutionNode<T>>>,
 }
 
-impl<T: Send + 'static> ExecutionService<T> {
+impl<T: EstimatedSize + Send + 'static> ExecutionService<T> {
     /// Register a node to execute. Note that the registered node is
     /// **owned** by this service now.
     pub fn add(&mut self, node: ExecutionNode<T>) {
@@ -37,9 +40,72 @@ impl<T: Send + 'static> ExecutionService<T> {
         }
     }
 
+    /// Waits for all the nodes to finish. The nodes are then registered again, so that their
+    /// statistics can be reported by [Self::explain_analyze].
     pub fn join(&mut self) {
         while let Some(handle) = self.thread_handles.pop() {
-            handle.join().unwrap();
+            self.nodes.push(handle.join().unwrap());
+        }
+    }
+
+    /// The registered nodes as a tree with one node per line. The nodes writing to the input
+    /// channels of a node are listed below it, indented, in the order of the channels. Nodes
+    /// whose output is not read by another registered node are the roots.
+    ///
+    /// Nodes are only registered before [Self::run] and after [Self::join].
+    pub fn explain(&self) -> String {
+        self.format_tree(&|node| node.label())
+    }
+
+    /// Same as [Self::explain], with what each node has sent. Call it after [Self::join].
+    pub fn explain_analyze(&self) -> String {
+        self.format_tree(&|node| format!("{}  actual: {}", node.label(), node.stats()))
+    }
+
+    fn format_tree(&self, describe: &dyn Fn(&ExecutionNode<T>) -> String) -> String {
+        let mut producers: HashMap<String, usize> = HashMap::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            for writer in node.output_writer().iter() {
+                producers.insert(writer.channel_id().clone(), i);
+            }
+        }
+        let mut consumed: HashSet<usize> = HashSet::new();
+        let inputs: Vec<Vec<usize>> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let readers = node.input_reader().readers;
+                let inputs: Vec<usize> = readers
+                    .iter()
+                    .filter_map(|reader| producers.get(reader.channel_id()).copied())
+                    .collect();
+                consumed.extend(inputs.iter().copied());
+                inputs
+            })
+            .collect();
+
+        let mut lines = vec![];
+        for root in (0..self.nodes.len()).filter(|i| !consumed.contains(i)) {
+            self.format_lines(root, 0, &inputs, describe, &mut lines);
+        }
+        lines.join("\n")
+    }
+
+    fn format_lines(
+        &self,
+        node: usize,
+        depth: usize,
+        inputs: &[Vec<usize>],
+        describe: &dyn Fn(&ExecutionNode<T>) -> String,
+        lines: &mut Vec<String>,
+    ) {
+        lines.push(format!(
+            "{}{}",
+            "  ".repeat(depth),
+            describe(&self.nodes[node])
+        ));
+        for input in &inputs[node] {
+            self.format_lines(*input, depth + 1, inputs, describe, lines);
         }
     }
 
@@ -53,7 +119,11 @@ impl<T: Send + 'static> ExecutionService<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{data::DataMessage, graph::node::ExecutionNode, processor::SimpleMapper};
+    use crate::{
+        data::DataMessage,
+        graph::node::{ExecutionNode, NodeReader},
+        processor::SimpleMapper,
+    };
 
     use super::ExecutionService;
 
@@ -70,4 +140,31 @@ mod tests {
 
         exec_service.join();
     }
+
+    #[test]
+    fn can_explain_nodes() {
+        let source = ExecutionNode::from(SimpleMapper::from(|r: &String| Some(r.clone())));
+        source.set_label("Source");
+        let mapper = ExecutionNode::from(SimpleMapper::from(|r: &String| Some(r.clone() + "X")));
+        mapper.set_label("Mapper");
+        mapper.subscribe_to_node(&source, 0);
+        let reader = NodeReader::new(&mapper);
+        source.write_to_self(0, DataMessage::from("a".to_string()));
+        source.write_to_self(0, DataMessage::from("b".to_string()));
+        source.write_to_self(0, DataMessage::eof());
+
+        let mut exec_service = ExecutionService::create();
+        exec_service.add(source);
+        exec_service.add(mapper);
+        assert_eq!(exec_service.explain(), "Mapper\n  Source");
+
+        exec_service.run();
+        while !reader.read().is_eof() {}
+        exec_service.join();
+        let explained = exec_service.explain_analyze();
+        let lines: Vec<&str> = explained.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("Mapper  actual: rows=2 (last=1), messages=2, time="));
+        assert!(lines[1].starts_with("  Source  actual: rows=2"));
+    }
 }
@@ -1,7 +1,13 @@
+mod adapter;
+mod exec_graph;
 mod exec_service;
 mod node;
 /// Asynchronous Execution Graph
 mod node_base;
+mod stats;
 
+pub use adapter::*;
+pub use exec_graph::*;
 pub use exec_service::*;
 pub use node::*;
+pub use stats::*;
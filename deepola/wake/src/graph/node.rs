@@ -5,8 +5,11 @@ use getset::{Getters, Setters};
 use nanoid::nanoid;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Instant;
 
 use super::node_base::*;
+use super::stats::*;
 use crate::channel::*;
 
 /// (input channel) -> [This Node] -> (output channels)
@@ -34,6 +37,12 @@ pub struct ExecutionNode<T: Send> {
 
     #[getset(get = "pub")]
     node_id: String,
+
+    /// A description of the operation of this node, shown by [super::ExecutionService::explain].
+    label: RefCell<String>,
+
+    /// What this node has written to its output channels.
+    stats: Arc<NodeStats>,
 }
 
 unsafe impl<T: Send> Send for ExecutionNode<T> {}
@@ -45,13 +54,13 @@ impl<T: Send> Subscribable<T> for ExecutionNode<T> {
 }
 
 /// Convenience method for creating ExecutionNode from a simple mapper.
-impl<T: Send + 'static> From<SimpleMapper<T>> for ExecutionNode<T> {
+impl<T: EstimatedSize + Send + 'static> From<SimpleMapper<T>> for ExecutionNode<T> {
     fn from(simple_mapper: SimpleMapper<T>) -> Self {
         ExecutionNode::<T>::new(Box::new(simple_mapper), 1)
     }
 }
 
-impl<T: Send + 'static> ExecutionNode<T> {
+impl<T: EstimatedSize + Send + 'static> ExecutionNode<T> {
     /// Obtains a clone of self_writer. A caller of this method can then write messages to
     /// this node using the obtained writer. This is useful for testing. Why not simply use
     /// another method `write_to_self()`? Obtaining a cloned writer is useful when we need to
@@ -77,11 +86,25 @@ impl<T: Send + 'static> ExecutionNode<T> {
         (&self.self_writers[channel_no]).write(message)
     }
 
+    /// Makes `source_node` write its output to the for_channel-th input channel. Several
+    /// nodes can write to the same channel, in which case this node receives their messages
+    /// interleaved, and a single EOF once all of them have sent EOF.
     pub fn subscribe_to_node(&self, source_node: &dyn Subscribable<T>, for_channel: usize) {
         let writer = &self.self_writers[for_channel];
+        writer.register_producer();
         source_node.add(writer.clone());
     }
 
+    /// Sets how much the channel_no-th input channel can hold before the nodes writing to it
+    /// block (see [ChannelCapacity]), e.g., to bound the DataFrames queued between a fast
+    /// CSVReader and a slow join.
+    pub fn set_input_capacity(&self, channel_no: usize, capacity: ChannelCapacity) {
+        self.input_reader
+            .borrow()
+            .reader(channel_no)
+            .set_capacity(capacity);
+    }
+
     /// Processes the data from input stream until we see EOF from all input channels.
     ///
     /// This is the primary method used by ExecutionService to start all the nodes.
@@ -108,6 +131,8 @@ impl<T: Send + 'static> ExecutionNode<T> {
             );
         }
 
+        let start = Instant::now();
+
         // Pre-processing (if needed)
         log::debug!("Starts Pre-Processing for Node: [{}]", self.node_id());
         self.stream_processor
@@ -117,10 +142,14 @@ impl<T: Send + 'static> ExecutionNode<T> {
 
         // Actual data processing
         log::debug!("Starts Data Processing for Node: [{}]", self.node_id());
+        let output_writer = output_writer
+            .clone()
+            .with_stats(self.stats.clone(), T::row_count);
         self.stream_processor()
             .borrow()
-            .process_stream(input_reader.clone(), output_writer.clone());
+            .process_stream(input_reader.clone(), output_writer);
         log::debug!("Finished Data Processing for Node: [{}]", self.node_id());
+        self.stats.record_elapsed(start.elapsed());
 
         log::debug!("Terminating Node: [{}]", self.node_id());
     }
@@ -137,6 +166,19 @@ impl<T: Send + 'static> ExecutionNode<T> {
         Self::from(SimpleMapper::<T>::ignore())
     }
 
+    pub fn label(&self) -> String {
+        self.label.borrow().clone()
+    }
+
+    pub fn set_label(&self, label: &str) {
+        *self.label.borrow_mut() = label.to_string();
+    }
+
+    /// Statistics of the messages this node has sent. They are updated while the node runs.
+    pub fn stats(&self) -> Arc<NodeStats> {
+        self.stats.clone()
+    }
+
     pub fn new_single_input(stream_processor: Box<dyn StreamProcessor<T>>) -> Self {
         Self::new(stream_processor, 1)
     }
@@ -158,12 +200,15 @@ impl<T: Send + 'static> ExecutionNode<T> {
             self_writers.push(write_channel);
         }
 
+        let node_id = nanoid!(NODE_ID_LEN, &NODE_ID_ALPHABET);
         Self {
             stream_processor: RefCell::new(stream_processor),
             input_reader: RefCell::new(input_channels),
             self_writers,
             output_writer: RefCell::new(MultiChannelBroadcaster::<T>::new()),
-            node_id: nanoid!(NODE_ID_LEN, &NODE_ID_ALPHABET),
+            label: RefCell::new(format!("Node [{}]", node_id)),
+            node_id,
+            stats: Arc::new(NodeStats::new()),
         }
     }
 }
@@ -174,12 +219,12 @@ pub struct NodeReader<T: Send> {
     internal_node: ExecutionNode<T>,
 }
 
-impl<T: Send + Clone + 'static> NodeReader<T> {
+impl<T: EstimatedSize + Send + Clone + 'static> NodeReader<T> {
     pub fn read(&self) -> DataMessage<T> {
         self.internal_node.input_reader().read(0)
     }
 
-    pub fn new(listens_to: &ExecutionNode<T>) -> Self {
+    pub fn new(listens_to: &dyn Subscribable<T>) -> Self {
         let mut node = ExecutionNode::create();
         node.set_simple_map(SimpleMapper::identity());
         node.subscribe_to_node(listens_to, 0);
@@ -194,7 +239,7 @@ impl<T: Send + Clone + 'static> NodeReader<T> {
         }
     }
 
-    pub fn subscribe_to_node(&mut self, listens_to: &ExecutionNode<T>, for_channel: usize) {
+    pub fn subscribe_to_node(&mut self, listens_to: &dyn Subscribable<T>, for_channel: usize) {
         self.internal_node
             .subscribe_to_node(listens_to, for_channel);
     }
@@ -329,6 +374,36 @@ mod tests {
         )
     }
 
+    /// Two nodes write to the same input channel; the mapper reads the records of both before
+    /// a single EOF.
+    #[test]
+    fn can_fan_in_to_one_channel() {
+        let source1 = ExecutionNode::<String>::from(SimpleMapper::identity());
+        let source2 = ExecutionNode::<String>::from(SimpleMapper::identity());
+        let mapper = ExecutionNode::<String>::from(SimpleMapper::identity());
+        mapper.subscribe_to_node(&source1, 0);
+        mapper.subscribe_to_node(&source2, 0);
+        let reader_node = NodeReader::new(&mapper);
+        source1.write_to_self(0, DataMessage::from("a".to_string()));
+        source1.write_to_self(0, DataMessage::eof());
+        source2.write_to_self(0, DataMessage::from("b".to_string()));
+        source2.write_to_self(0, DataMessage::eof());
+
+        source1.run();
+        source2.run();
+        mapper.run();
+
+        let mut records = vec![];
+        loop {
+            let message = reader_node.read();
+            if message.is_eof() {
+                break;
+            }
+            records.push(message.datablock().data().clone());
+        }
+        assert_eq!(records, vec!["a", "b"]);
+    }
+
     /// We create ten linearly connected nodes. Each node adds "X" at the end of the passed
     /// value. Finally, we see ten "X"es added to the value.
     #[test]
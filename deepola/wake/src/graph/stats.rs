@@ -0,0 +1,85 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// What an [crate::graph::ExecutionNode] has produced so far. The counters are updated by the
+/// running node and can be read from any thread.
+#[derive(Debug, Default)]
+pub struct NodeStats {
+    messages: AtomicUsize,
+    rows: AtomicUsize,
+    last_rows: AtomicUsize,
+    elapsed_micros: AtomicU64,
+}
+
+impl NodeStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a data message with `rows` rows written to the output channels.
+    pub fn record_message(&self, rows: usize) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        self.rows.fetch_add(rows, Ordering::Relaxed);
+        self.last_rows.store(rows, Ordering::Relaxed);
+    }
+
+    pub fn record_elapsed(&self, elapsed: Duration) {
+        self.elapsed_micros
+            .store(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Number of data messages (i.e., snapshots, for nodes emitting running results) sent.
+    pub fn messages(&self) -> usize {
+        self.messages.load(Ordering::Relaxed)
+    }
+
+    /// Total number of rows over all the data messages sent.
+    pub fn rows(&self) -> usize {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    /// Number of rows of the last data message sent.
+    pub fn last_rows(&self) -> usize {
+        self.last_rows.load(Ordering::Relaxed)
+    }
+
+    /// Time from the start of the node until it finished processing its input.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_micros(self.elapsed_micros.load(Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for NodeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rows={} (last={}), messages={}, time={:.3} ms",
+            self.rows(),
+            self.last_rows(),
+            self.messages(),
+            self.elapsed().as_secs_f64() * 1000.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn can_record_messages() {
+        let stats = NodeStats::new();
+        stats.record_message(10);
+        stats.record_message(4);
+        stats.record_elapsed(Duration::from_millis(2));
+        assert_eq!(stats.messages(), 2);
+        assert_eq!(stats.rows(), 14);
+        assert_eq!(stats.last_rows(), 4);
+        assert_eq!(
+            stats.to_string(),
+            "rows=14 (last=4), messages=2, time=2.000 ms"
+        );
+    }
+}
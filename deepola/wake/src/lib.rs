@@ -1,9 +1,12 @@
+pub mod catalog;
 pub mod channel;
 pub mod data;
+pub mod forecast;
 pub mod graph;
+pub mod operations;
 pub mod polars_operations;
 pub mod processor;
-// pub mod forecast;
+pub mod sql;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use crate::channel::{MultiChannelBroadcaster, MultiChannelReader};
+use crate::data::{ArrayRow, DataBlock, Schema};
+use crate::graph::ExecutionNode;
+use crate::processor::StreamProcessor;
+
+use super::util::{derived_metadata, process_blocks};
+
+/// A predicate on a row, given the schema of its DataBlock.
+pub type RowPredicate = Arc<dyn Fn(&ArrayRow, &Schema) -> bool>;
+
+/// Factory class for nodes keeping the rows of each DataBlock for which a predicate is true.
+///
+/// Example:
+/// ```
+/// use wake::operations::WhereNode;
+///
+/// WhereNode::new()
+///     .predicate(|row, schema| i32::from(&row[schema.index("l_quantity")]) < 25)
+///     .build();
+/// ```
+pub struct WhereNode {
+    predicate: RowPredicate,
+}
+
+impl Default for WhereNode {
+    /// A filter keeping all the rows.
+    fn default() -> Self {
+        Self {
+            predicate: Arc::new(|_, _| true),
+        }
+    }
+}
+
+impl WhereNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn predicate<F>(&mut self, predicate: F) -> &mut Self
+    where
+        F: Fn(&ArrayRow, &Schema) -> bool + 'static,
+    {
+        self.predicate = Arc::new(predicate);
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<Vec<ArrayRow>> {
+        let data_processor = RowFilter {
+            predicate: self.predicate.clone(),
+        };
+        let node = ExecutionNode::<Vec<ArrayRow>>::new(Box::new(data_processor), 1);
+        node.set_label("Where");
+        node
+    }
+}
+
+struct RowFilter {
+    predicate: RowPredicate,
+}
+
+/// Needed to be sent to different threads.
+unsafe impl Send for RowFilter {}
+
+impl StreamProcessor<Vec<ArrayRow>> for RowFilter {
+    fn process_stream(
+        &self,
+        input_stream: MultiChannelReader<Vec<ArrayRow>>,
+        output_stream: MultiChannelBroadcaster<Vec<ArrayRow>>,
+    ) {
+        process_blocks(0, input_stream, output_stream, |dblock| {
+            let schema = dblock.schema();
+            let rows = dblock
+                .data()
+                .iter()
+                .filter(|row| (self.predicate)(row, schema))
+                .cloned()
+                .collect();
+            DataBlock::new(rows, derived_metadata(dblock, schema.clone()))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        Column, DataMessage, DataType, MetaCell, DATABLOCK_PROGRESS, DATABLOCK_RECORDS_READ,
+    };
+    use crate::graph::NodeReader;
+
+    #[test]
+    fn can_filter_rows() {
+        let filter = WhereNode::new()
+            .predicate(|row, schema| f64::from(&row[schema.index("price")]) > 10.0)
+            .build();
+        let schema = Schema::from(vec![
+            Column::from_field("item".into(), DataType::Text),
+            Column::from_field("price".into(), DataType::Float),
+        ]);
+        let rows = vec![
+            ArrayRow::from(["pen".into(), 1.5.into()]),
+            ArrayRow::from(["book".into(), 12.0.into()]),
+            ArrayRow::from(["lamp".into(), 30.0.into()]),
+        ];
+        let mut metadata = MetaCell::from(schema.clone()).into_meta_map();
+        metadata.insert(DATABLOCK_PROGRESS.into(), MetaCell::from(0.25));
+        metadata.insert(DATABLOCK_RECORDS_READ.into(), MetaCell::from(3.0));
+        filter.write_to_self(0, DataMessage::from(DataBlock::new(rows, metadata)));
+        filter.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(&filter);
+        filter.run();
+
+        let message = reader_node.read();
+        let dblock = message.datablock();
+        assert_eq!(dblock.schema(), &schema);
+        assert_eq!(dblock.progress(), Some(0.25));
+        assert_eq!(dblock.records_read(), Some(3.0));
+        assert_eq!(
+            dblock.data(),
+            &vec![
+                ArrayRow::from(["book".into(), 12.0.into()]),
+                ArrayRow::from(["lamp".into(), 30.0.into()]),
+            ]
+        );
+        assert!(reader_node.read().is_eof());
+    }
+}
@@ -0,0 +1,424 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::channel::{MultiChannelBroadcaster, MultiChannelReader};
+use crate::data::{ArrayRow, Column, DataBlock, DataCell, DataType, Schema};
+use crate::graph::ExecutionNode;
+use crate::processor::StreamProcessor;
+
+use super::util::{derived_metadata, process_blocks};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AggregationOperation {
+    Sum,
+    Min,
+    Max,
+    Count,
+    Avg,
+}
+
+impl AggregationOperation {
+    /// The name of the operation, e.g., "sum".
+    pub fn name(&self) -> &'static str {
+        match self {
+            AggregationOperation::Sum => "sum",
+            AggregationOperation::Min => "min",
+            AggregationOperation::Max => "max",
+            AggregationOperation::Count => "count",
+            AggregationOperation::Avg => "avg",
+        }
+    }
+
+    /// The partial aggregate of some non-null cells. An average is kept as a (sum, count) tuple.
+    fn aggregate(&self, cells: &[DataCell]) -> DataCell {
+        match self {
+            AggregationOperation::Sum => DataCell::sum(cells),
+            AggregationOperation::Min => DataCell::min(cells),
+            AggregationOperation::Max => DataCell::max(cells),
+            AggregationOperation::Count => DataCell::count(cells),
+            AggregationOperation::Avg => DataCell::avg(cells),
+        }
+    }
+
+    /// Combines two partial aggregates.
+    fn merge(&self, a: &DataCell, b: &DataCell) -> DataCell {
+        match (self, a, b) {
+            (AggregationOperation::Avg, DataCell::Tuple(a), DataCell::Tuple(b)) => {
+                DataCell::from((
+                    AggregationOperation::Sum.merge(&a.0, &b.0),
+                    AggregationOperation::Sum.merge(&a.1, &b.1),
+                ))
+            }
+            (AggregationOperation::Count, _, _) => DataCell::sum(&[a.clone(), b.clone()]),
+            _ => {
+                let cells: Vec<DataCell> = [a, b]
+                    .into_iter()
+                    .filter(|cell| !matches!(cell, DataCell::Null()))
+                    .cloned()
+                    .collect();
+                self.aggregate(&cells)
+            }
+        }
+    }
+
+    /// The final value of a partial aggregate: the sum over the count for an average (null if
+    /// there is no value).
+    fn finalize(&self, cell: &DataCell) -> DataCell {
+        match (self, cell) {
+            (AggregationOperation::Avg, DataCell::Tuple(a)) => match a.as_ref() {
+                (DataCell::Null(), _) => DataCell::Null(),
+                (sum, count) => DataCell::Float(f64::from(sum) / f64::from(count)),
+            },
+            _ => cell.clone(),
+        }
+    }
+
+    /// The type of the aggregates of a column of type `dtype`.
+    fn dtype(&self, dtype: DataType) -> DataType {
+        match self {
+            AggregationOperation::Count => DataType::Integer,
+            AggregationOperation::Avg => DataType::Float,
+            _ => dtype,
+        }
+    }
+}
+
+/// An aggregate of a column. Its output column is named `alias`, or `{column}_{operation}`
+/// (e.g., "l_quantity_sum") by default.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Aggregate {
+    pub column: String,
+    pub operation: AggregationOperation,
+    pub alias: Option<String>,
+}
+
+impl Aggregate {
+    pub fn output_name(&self) -> String {
+        match &self.alias {
+            Some(alias) => alias.clone(),
+            None => format!("{}_{}", self.column, self.operation.name()),
+        }
+    }
+}
+
+/// Factory class for nodes grouping rows by some columns, and aggregating the others. Like
+/// [crate::polars_operations::AccumulatorNode], the aggregates accumulate over the DataBlocks:
+/// each output DataBlock holds the aggregates of all the rows read so far, with a row per group
+/// in the order the groups were first seen. The output has the group columns (as key columns)
+/// followed by the aggregates. Null values are ignored, except by counts.
+///
+/// Example:
+/// ```
+/// use wake::operations::{Aggregate, AggregationOperation, GroupByNode};
+///
+/// GroupByNode::new()
+///     .group_by(vec!["l_returnflag".into(), "l_linestatus".into()])
+///     .aggregates(vec![Aggregate {
+///         column: "l_quantity".into(),
+///         operation: AggregationOperation::Sum,
+///         alias: Some("sum_qty".into()),
+///     }])
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct GroupByNode {
+    group_by: Vec<String>,
+    aggregates: Vec<Aggregate>,
+}
+
+impl GroupByNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The columns whose values identify a group. All the rows are a single group if empty.
+    pub fn group_by(&mut self, group_by: Vec<String>) -> &mut Self {
+        self.group_by = group_by;
+        self
+    }
+
+    pub fn aggregates(&mut self, aggregates: Vec<Aggregate>) -> &mut Self {
+        self.aggregates = aggregates;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<Vec<ArrayRow>> {
+        let data_processor = RowGroupBy {
+            group_by: self.group_by.clone(),
+            aggregates: self.aggregates.clone(),
+            groups: RefCell::new(vec![]),
+            group_index: RefCell::new(HashMap::new()),
+        };
+        let node = ExecutionNode::<Vec<ArrayRow>>::new(Box::new(data_processor), 1);
+        let aggregates: Vec<String> = self
+            .aggregates
+            .iter()
+            .map(|aggregate| aggregate.output_name())
+            .collect();
+        node.set_label(&format!(
+            "GroupBy: [{}] [{}]",
+            self.group_by.join(", "),
+            aggregates.join(", ")
+        ));
+        node
+    }
+}
+
+struct RowGroupBy {
+    group_by: Vec<String>,
+    aggregates: Vec<Aggregate>,
+    /// The key and partial aggregates of each group, in the order they were first seen.
+    groups: RefCell<Vec<(Vec<DataCell>, Vec<DataCell>)>>,
+    /// The position of each group in `groups`.
+    group_index: RefCell<HashMap<Vec<DataCell>, usize>>,
+}
+
+/// Needed to be sent to different threads.
+unsafe impl Send for RowGroupBy {}
+
+impl RowGroupBy {
+    fn output_schema(&self, schema: &Schema) -> Schema {
+        let mut columns: Vec<Column> = self
+            .group_by
+            .iter()
+            .map(|column| Column::from_key_field(column.clone(), schema.dtype(column)))
+            .collect();
+        columns.extend(self.aggregates.iter().map(|aggregate| {
+            Column::from_field(
+                aggregate.output_name(),
+                aggregate.operation.dtype(schema.dtype(&aggregate.column)),
+            )
+        }));
+        Schema::new(schema.table.clone(), columns)
+    }
+
+    fn accumulate(&self, dblock: &DataBlock<Vec<ArrayRow>>) -> DataBlock<Vec<ArrayRow>> {
+        let schema = dblock.schema();
+        let keys: Vec<usize> = self
+            .group_by
+            .iter()
+            .map(|column| schema.index(column))
+            .collect();
+        let values: Vec<usize> = self
+            .aggregates
+            .iter()
+            .map(|aggregate| schema.index(&aggregate.column))
+            .collect();
+
+        // the non-null values of each aggregate, for the groups of this block
+        let mut block_groups: HashMap<Vec<DataCell>, Vec<Vec<DataCell>>> = HashMap::new();
+        let mut block_keys = vec![];
+        for row in dblock.data() {
+            let key = row.slice_indices(&keys);
+            let cells = block_groups.entry(key.clone()).or_insert_with(|| {
+                block_keys.push(key);
+                vec![vec![]; values.len()]
+            });
+            for (cells, index) in cells.iter_mut().zip(&values) {
+                match &row[*index] {
+                    DataCell::Null() => {}
+                    cell => cells.push(cell.clone()),
+                }
+            }
+        }
+
+        let mut groups = self.groups.borrow_mut();
+        let mut group_index = self.group_index.borrow_mut();
+        for key in block_keys {
+            let partials: Vec<DataCell> = self
+                .aggregates
+                .iter()
+                .zip(&block_groups[&key])
+                .map(|(aggregate, cells)| aggregate.operation.aggregate(cells))
+                .collect();
+            match group_index.get(&key) {
+                Some(position) => {
+                    let state = &mut groups[*position].1;
+                    for ((cell, partial), aggregate) in
+                        state.iter_mut().zip(&partials).zip(&self.aggregates)
+                    {
+                        *cell = aggregate.operation.merge(cell, partial);
+                    }
+                }
+                None => {
+                    group_index.insert(key.clone(), groups.len());
+                    groups.push((key, partials));
+                }
+            }
+        }
+
+        let rows = groups
+            .iter()
+            .map(|(key, state)| {
+                let mut values = key.clone();
+                values.extend(
+                    state
+                        .iter()
+                        .zip(&self.aggregates)
+                        .map(|(cell, aggregate)| aggregate.operation.finalize(cell)),
+                );
+                ArrayRow::from(values)
+            })
+            .collect();
+        DataBlock::new(rows, derived_metadata(dblock, self.output_schema(schema)))
+    }
+}
+
+impl StreamProcessor<Vec<ArrayRow>> for RowGroupBy {
+    fn process_stream(
+        &self,
+        input_stream: MultiChannelReader<Vec<ArrayRow>>,
+        output_stream: MultiChannelBroadcaster<Vec<ArrayRow>>,
+    ) {
+        process_blocks(0, input_stream, output_stream, |dblock| {
+            self.accumulate(dblock)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{DataMessage, MetaCell, DATABLOCK_PROGRESS};
+    use crate::graph::NodeReader;
+
+    fn aggregate(column: &str, operation: AggregationOperation) -> Aggregate {
+        Aggregate {
+            column: column.into(),
+            operation,
+            alias: None,
+        }
+    }
+
+    #[test]
+    fn can_accumulate_groups() {
+        let groupby = GroupByNode::new()
+            .group_by(vec!["city".into()])
+            .aggregates(vec![
+                aggregate("sales", AggregationOperation::Sum),
+                aggregate("sales", AggregationOperation::Min),
+                aggregate("sales", AggregationOperation::Max),
+                aggregate("sales", AggregationOperation::Count),
+                Aggregate {
+                    column: "price".into(),
+                    operation: AggregationOperation::Avg,
+                    alias: Some("avg_price".into()),
+                },
+            ])
+            .build();
+        assert_eq!(
+            groupby.label(),
+            "GroupBy: [city] [sales_sum, sales_min, sales_max, sales_count, avg_price]"
+        );
+        let schema = Schema::from(vec![
+            Column::from_field("city".into(), DataType::Text),
+            Column::from_field("sales".into(), DataType::Integer),
+            Column::from_field("price".into(), DataType::Float),
+        ]);
+        let blocks = vec![
+            vec![
+                ArrayRow::from(["Urbana".into(), 3.into(), 1.0.into()]),
+                ArrayRow::from(["Champaign".into(), 5.into(), DataCell::Null()]),
+                ArrayRow::from(["Urbana".into(), 1.into(), 2.0.into()]),
+            ],
+            vec![
+                ArrayRow::from(["Champaign".into(), DataCell::Null(), 4.0.into()]),
+                ArrayRow::from(["Urbana".into(), 7.into(), 6.0.into()]),
+            ],
+        ];
+        for (i, rows) in blocks.into_iter().enumerate() {
+            let mut metadata = MetaCell::from(schema.clone()).into_meta_map();
+            metadata.insert(
+                DATABLOCK_PROGRESS.into(),
+                MetaCell::from(0.5 * (i + 1) as f64),
+            );
+            groupby.write_to_self(0, DataMessage::from(DataBlock::new(rows, metadata)));
+        }
+        groupby.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(&groupby);
+        groupby.run();
+
+        let message = reader_node.read();
+        let dblock = message.datablock();
+        assert_eq!(dblock.progress(), Some(0.5));
+        assert_eq!(
+            dblock.data(),
+            &vec![
+                ArrayRow::from([
+                    "Urbana".into(),
+                    4.into(),
+                    1.into(),
+                    3.into(),
+                    2.into(),
+                    1.5.into()
+                ]),
+                ArrayRow::from([
+                    "Champaign".into(),
+                    5.into(),
+                    5.into(),
+                    5.into(),
+                    1.into(),
+                    DataCell::Null()
+                ]),
+            ]
+        );
+
+        let message = reader_node.read();
+        let dblock = message.datablock();
+        assert_eq!(dblock.progress(), Some(1.0));
+        assert_eq!(
+            dblock.schema().columns,
+            vec![
+                Column::from_key_field("city".into(), DataType::Text),
+                Column::from_field("sales_sum".into(), DataType::Integer),
+                Column::from_field("sales_min".into(), DataType::Integer),
+                Column::from_field("sales_max".into(), DataType::Integer),
+                Column::from_field("sales_count".into(), DataType::Integer),
+                Column::from_field("avg_price".into(), DataType::Float),
+            ]
+        );
+        assert_eq!(
+            dblock.data(),
+            &vec![
+                ArrayRow::from([
+                    "Urbana".into(),
+                    11.into(),
+                    1.into(),
+                    7.into(),
+                    3.into(),
+                    3.0.into()
+                ]),
+                ArrayRow::from([
+                    "Champaign".into(),
+                    5.into(),
+                    5.into(),
+                    5.into(),
+                    1.into(),
+                    4.0.into()
+                ]),
+            ]
+        );
+        assert!(reader_node.read().is_eof());
+    }
+
+    #[test]
+    fn can_aggregate_without_groups() {
+        let groupby = GroupByNode::new()
+            .aggregates(vec![aggregate("sales", AggregationOperation::Avg)])
+            .build();
+        let schema = Schema::from(vec![Column::from_field("sales".into(), DataType::Integer)]);
+        let rows = vec![ArrayRow::from([1.into()]), ArrayRow::from([2.into()])];
+        let metadata = MetaCell::from(schema).into_meta_map();
+        groupby.write_to_self(0, DataMessage::from(DataBlock::new(rows, metadata)));
+        groupby.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(&groupby);
+        groupby.run();
+
+        let message = reader_node.read();
+        assert_eq!(
+            message.datablock().data(),
+            &vec![ArrayRow::from([1.5.into()])]
+        );
+        assert!(reader_node.read().is_eof());
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use crate::channel::{MultiChannelBroadcaster, MultiChannelReader};
+use crate::data::{ArrayRow, DataBlock, DataCell, Payload, Schema};
+use crate::graph::ExecutionNode;
+use crate::processor::StreamProcessor;
+
+use super::util::{derived_metadata, process_blocks};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum JoinType {
+    #[default]
+    Inner,
+    /// Keeps the left rows without a match, with nulls for the right columns.
+    Left,
+}
+
+/// Factory class for nodes joining the rows of two inputs on equal keys. The right input
+/// (channel 1) is read until EOF into a hash table first; then each DataBlock of the left input
+/// (channel 0) is joined with it. The output has the left columns followed by the right columns
+/// that are not part of the key. As in SQL, null keys match nothing.
+///
+/// Example:
+/// ```
+/// use wake::operations::{HashJoinNode, JoinType};
+///
+/// HashJoinNode::new()
+///     .left_on(vec!["l_orderkey".into()])
+///     .right_on(vec!["o_orderkey".into()])
+///     .join_type(JoinType::Inner)
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct HashJoinNode {
+    left_on: Vec<String>,
+    right_on: Vec<String>,
+    join_type: JoinType,
+}
+
+impl HashJoinNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn left_on(&mut self, left_on: Vec<String>) -> &mut Self {
+        self.left_on = left_on;
+        self
+    }
+
+    pub fn right_on(&mut self, right_on: Vec<String>) -> &mut Self {
+        self.right_on = right_on;
+        self
+    }
+
+    pub fn join_type(&mut self, join_type: JoinType) -> &mut Self {
+        self.join_type = join_type;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<Vec<ArrayRow>> {
+        let data_processor = RowHashJoin {
+            left_on: self.left_on.clone(),
+            right_on: self.right_on.clone(),
+            join_type: self.join_type,
+            right_schema: None,
+            right_rows: HashMap::new(),
+        };
+        let node = ExecutionNode::<Vec<ArrayRow>>::new(Box::new(data_processor), 2);
+        node.set_label(&format!(
+            "HashJoin: {:?} ({}) = ({})",
+            self.join_type,
+            self.left_on.join(", "),
+            self.right_on.join(", ")
+        ));
+        node
+    }
+}
+
+struct RowHashJoin {
+    left_on: Vec<String>,
+    right_on: Vec<String>,
+    join_type: JoinType,
+    /// The schema of the right input, if it had any DataBlock.
+    right_schema: Option<Schema>,
+    /// The non-key values of the right rows, by key.
+    right_rows: HashMap<Vec<DataCell>, Vec<Vec<DataCell>>>,
+}
+
+/// The values of `row` at `indices`, or None if one is null.
+fn join_key(row: &ArrayRow, indices: &[usize]) -> Option<Vec<DataCell>> {
+    let key = row.slice_indices(indices);
+    if key.iter().any(|cell| matches!(cell, DataCell::Null())) {
+        None
+    } else {
+        Some(key)
+    }
+}
+
+impl RowHashJoin {
+    /// The indices of the non-key columns of the right input.
+    fn right_values(&self, right_schema: &Schema) -> Vec<usize> {
+        let keys: Vec<usize> = self
+            .right_on
+            .iter()
+            .map(|column| right_schema.index(column))
+            .collect();
+        (0..right_schema.col_count())
+            .filter(|index| !keys.contains(index))
+            .collect()
+    }
+
+    fn build_table(&mut self, dblock: &DataBlock<Vec<ArrayRow>>) {
+        let schema = dblock.schema();
+        let keys: Vec<usize> = self
+            .right_on
+            .iter()
+            .map(|column| schema.index(column))
+            .collect();
+        let values = self.right_values(schema);
+        for row in dblock.data() {
+            if let Some(key) = join_key(row, &keys) {
+                self.right_rows
+                    .entry(key)
+                    .or_default()
+                    .push(row.slice_indices(&values));
+            }
+        }
+        self.right_schema.get_or_insert_with(|| schema.clone());
+    }
+
+    fn join(&self, dblock: &DataBlock<Vec<ArrayRow>>) -> DataBlock<Vec<ArrayRow>> {
+        let left_schema = dblock.schema();
+        let keys: Vec<usize> = self
+            .left_on
+            .iter()
+            .map(|column| left_schema.index(column))
+            .collect();
+        let mut columns = left_schema.columns.clone();
+        let mut nulls = vec![];
+        if let Some(right_schema) = &self.right_schema {
+            for index in self.right_values(right_schema) {
+                columns.push(right_schema.get_column_from_index(index));
+                nulls.push(DataCell::Null());
+            }
+        }
+
+        let mut rows = vec![];
+        for row in dblock.data() {
+            let matches = join_key(row, &keys).and_then(|key| self.right_rows.get(&key));
+            match (matches, self.join_type) {
+                (Some(right_values), _) => {
+                    for values in right_values {
+                        let mut joined = row.values.clone();
+                        joined.extend(values.iter().cloned());
+                        rows.push(ArrayRow::from(joined));
+                    }
+                }
+                (None, JoinType::Left) => {
+                    let mut joined = row.values.clone();
+                    joined.extend(nulls.iter().cloned());
+                    rows.push(ArrayRow::from(joined));
+                }
+                (None, JoinType::Inner) => {}
+            }
+        }
+        // The progress of the join is that of its probe side.
+        let schema = Schema::new(left_schema.table.clone(), columns);
+        DataBlock::new(rows, derived_metadata(dblock, schema))
+    }
+}
+
+impl StreamProcessor<Vec<ArrayRow>> for RowHashJoin {
+    fn pre_process(&mut self, input_stream: MultiChannelReader<Vec<ArrayRow>>) {
+        loop {
+            let channel_seq = 1;
+            let message = input_stream.read(channel_seq);
+            match message.payload() {
+                Payload::EOF => break,
+                Payload::Signal(_) => break,
+                Payload::Some(dblock) => self.build_table(&dblock),
+            }
+        }
+    }
+
+    fn process_stream(
+        &self,
+        input_stream: MultiChannelReader<Vec<ArrayRow>>,
+        output_stream: MultiChannelBroadcaster<Vec<ArrayRow>>,
+    ) {
+        process_blocks(0, input_stream, output_stream, |dblock| self.join(dblock));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Column, DataMessage, DataType, MetaCell, DATABLOCK_PROGRESS};
+    use crate::graph::NodeReader;
+
+    fn join_rows(join_type: JoinType) -> (Schema, Vec<ArrayRow>) {
+        let join = HashJoinNode::new()
+            .left_on(vec!["l_orderkey".into()])
+            .right_on(vec!["o_orderkey".into()])
+            .join_type(join_type)
+            .build();
+        let lineitem = Schema::new(
+            "lineitem".into(),
+            vec![
+                Column::from_field("l_orderkey".into(), DataType::Integer),
+                Column::from_field("l_quantity".into(), DataType::Integer),
+            ],
+        );
+        let orders = Schema::new(
+            "orders".into(),
+            vec![
+                Column::from_field("o_custkey".into(), DataType::Integer),
+                Column::from_key_field("o_orderkey".into(), DataType::Integer),
+            ],
+        );
+        let lineitem_rows = vec![
+            ArrayRow::from([1.into(), 10.into()]),
+            ArrayRow::from([2.into(), 20.into()]),
+            ArrayRow::from([DataCell::Null(), 30.into()]),
+            ArrayRow::from([1.into(), 40.into()]),
+        ];
+        let orders_rows = vec![
+            ArrayRow::from([7.into(), 1.into()]),
+            ArrayRow::from([8.into(), DataCell::Null()]),
+            ArrayRow::from([9.into(), 3.into()]),
+        ];
+        let mut lineitem_metadata = MetaCell::from(lineitem).into_meta_map();
+        lineitem_metadata.insert(DATABLOCK_PROGRESS.into(), MetaCell::from(0.25));
+        let mut orders_metadata = MetaCell::from(orders).into_meta_map();
+        orders_metadata.insert(DATABLOCK_PROGRESS.into(), MetaCell::from(1.0));
+        join.write_to_self(
+            0,
+            DataMessage::from(DataBlock::new(lineitem_rows, lineitem_metadata)),
+        );
+        join.write_to_self(0, DataMessage::eof());
+        join.write_to_self(
+            1,
+            DataMessage::from(DataBlock::new(orders_rows, orders_metadata)),
+        );
+        join.write_to_self(1, DataMessage::eof());
+        let reader_node = NodeReader::new(&join);
+        join.run();
+
+        let message = reader_node.read();
+        let dblock = message.datablock();
+        // The progress of the probe side.
+        assert_eq!(dblock.progress(), Some(0.25));
+        assert!(reader_node.read().is_eof());
+        (dblock.schema().clone(), dblock.data().clone())
+    }
+
+    #[test]
+    fn can_inner_join_rows() {
+        let (schema, rows) = join_rows(JoinType::Inner);
+        assert_eq!(schema.table, "lineitem");
+        assert_eq!(
+            schema.columns,
+            vec![
+                Column::from_field("l_orderkey".into(), DataType::Integer),
+                Column::from_field("l_quantity".into(), DataType::Integer),
+                Column::from_field("o_custkey".into(), DataType::Integer),
+            ]
+        );
+        assert_eq!(
+            rows,
+            vec![
+                ArrayRow::from([1.into(), 10.into(), 7.into()]),
+                ArrayRow::from([1.into(), 40.into(), 7.into()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_left_join_rows() {
+        let (schema, rows) = join_rows(JoinType::Left);
+        assert_eq!(schema.col_count(), 3);
+        assert_eq!(
+            rows,
+            vec![
+                ArrayRow::from([1.into(), 10.into(), 7.into()]),
+                ArrayRow::from([2.into(), 20.into(), DataCell::Null()]),
+                ArrayRow::from([DataCell::Null(), 30.into(), DataCell::Null()]),
+                ArrayRow::from([1.into(), 40.into(), 7.into()]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,13 @@
+//! Operators over blocks of [crate::data::ArrayRow], for workloads that do not fit polars
+//! DataFrames. Each DataBlock carries the [crate::data::Schema] of its rows in its metadata (see
+//! [crate::data::SCHEMA_META_NAME]), and so do the DataBlocks these operators produce.
+mod filter;
+mod groupby;
+mod hash_join;
+mod project;
+mod util;
+
+pub use filter::*;
+pub use groupby::*;
+pub use hash_join::*;
+pub use project::*;
@@ -0,0 +1,122 @@
+use crate::channel::{MultiChannelBroadcaster, MultiChannelReader};
+use crate::data::{ArrayRow, DataBlock, Schema};
+use crate::graph::ExecutionNode;
+use crate::processor::StreamProcessor;
+
+use super::util::{derived_metadata, process_blocks};
+
+/// Factory class for nodes keeping some columns of each DataBlock, in the given order.
+///
+/// Example:
+/// ```
+/// use wake::operations::ProjectNode;
+///
+/// ProjectNode::new()
+///     .columns(vec!["l_orderkey".into(), "l_extendedprice".into()])
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct ProjectNode {
+    columns: Vec<String>,
+}
+
+impl ProjectNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn columns(&mut self, columns: Vec<String>) -> &mut Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<Vec<ArrayRow>> {
+        let data_processor = RowProject {
+            columns: self.columns.clone(),
+        };
+        let node = ExecutionNode::<Vec<ArrayRow>>::new(Box::new(data_processor), 1);
+        node.set_label(&format!("Project: {}", self.columns.join(", ")));
+        node
+    }
+}
+
+struct RowProject {
+    columns: Vec<String>,
+}
+
+impl StreamProcessor<Vec<ArrayRow>> for RowProject {
+    fn process_stream(
+        &self,
+        input_stream: MultiChannelReader<Vec<ArrayRow>>,
+        output_stream: MultiChannelBroadcaster<Vec<ArrayRow>>,
+    ) {
+        process_blocks(0, input_stream, output_stream, |dblock| {
+            let schema = dblock.schema();
+            let indices: Vec<usize> = self
+                .columns
+                .iter()
+                .map(|column| schema.index(column))
+                .collect();
+            let rows = dblock
+                .data()
+                .iter()
+                .map(|row| ArrayRow::from(row.slice_indices(&indices)))
+                .collect();
+            let columns = indices
+                .iter()
+                .map(|index| schema.get_column_from_index(*index))
+                .collect();
+            let schema = Schema::new(schema.table.clone(), columns);
+            DataBlock::new(rows, derived_metadata(dblock, schema))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Column, DataCell, DataMessage, DataType, MetaCell, DATABLOCK_PROGRESS};
+    use crate::graph::NodeReader;
+
+    #[test]
+    fn can_project_columns() {
+        let project = ProjectNode::new()
+            .columns(vec!["price".into(), "item".into()])
+            .build();
+        assert_eq!(project.label(), "Project: price, item");
+        let schema = Schema::from(vec![
+            Column::from_key_field("item".into(), DataType::Text),
+            Column::from_field("stock".into(), DataType::Integer),
+            Column::from_field("price".into(), DataType::Float),
+        ]);
+        let rows = vec![
+            ArrayRow::from(["pen".into(), 100.into(), 1.5.into()]),
+            ArrayRow::from(["book".into(), DataCell::Null(), 12.0.into()]),
+        ];
+        let mut metadata = MetaCell::from(schema).into_meta_map();
+        metadata.insert(DATABLOCK_PROGRESS.into(), MetaCell::from(0.5));
+        project.write_to_self(0, DataMessage::from(DataBlock::new(rows, metadata)));
+        project.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(&project);
+        project.run();
+
+        let message = reader_node.read();
+        let dblock = message.datablock();
+        assert_eq!(dblock.progress(), Some(0.5));
+        assert_eq!(
+            dblock.schema().columns,
+            vec![
+                Column::from_field("price".into(), DataType::Float),
+                Column::from_key_field("item".into(), DataType::Text),
+            ]
+        );
+        assert_eq!(
+            dblock.data(),
+            &vec![
+                ArrayRow::from([1.5.into(), "pen".into()]),
+                ArrayRow::from([12.0.into(), "book".into()]),
+            ]
+        );
+        assert!(reader_node.read().is_eof());
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use crate::channel::{MultiChannelBroadcaster, MultiChannelReader};
+use crate::data::{ArrayRow, DataBlock, DataMessage, MetaCell, Payload, Schema, SCHEMA_META_NAME};
+
+/// The metadata of a DataBlock computed from `dblock`, whose rows have `schema`.
+pub(crate) fn derived_metadata(
+    dblock: &DataBlock<Vec<ArrayRow>>,
+    schema: Schema,
+) -> HashMap<String, MetaCell> {
+    let mut metadata = dblock.progress_metadata();
+    metadata.insert(SCHEMA_META_NAME.into(), MetaCell::from(schema));
+    metadata
+}
+
+/// Maps each DataBlock read from the `channel_seq`-th input channel to an output DataBlock, until
+/// EOF.
+pub(crate) fn process_blocks<F>(
+    channel_seq: usize,
+    input_stream: MultiChannelReader<Vec<ArrayRow>>,
+    output_stream: MultiChannelBroadcaster<Vec<ArrayRow>>,
+    process: F,
+) where
+    F: Fn(&DataBlock<Vec<ArrayRow>>) -> DataBlock<Vec<ArrayRow>>,
+{
+    loop {
+        let message = input_stream.read(channel_seq);
+        match message.payload() {
+            Payload::EOF => {
+                output_stream.write(message);
+                break;
+            }
+            Payload::Signal(_) => break,
+            Payload::Some(dblock) => {
+                output_stream.write(DataMessage::from(process(&dblock)));
+                if output_stream.is_closed() {
+                    input_stream.close();
+                    break;
+                }
+            }
+        }
+    }
+}
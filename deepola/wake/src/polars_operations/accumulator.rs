@@ -3,7 +3,7 @@ use std::{cell::RefCell, marker::PhantomData};
 use getset::{Getters, Setters};
 use polars::prelude::*;
 
-use crate::{graph::ExecutionNode, processor::MessageProcessor};
+use crate::{data::EstimatedSize, graph::ExecutionNode, processor::MessageProcessor};
 
 /// Factory class for creating an ExecutionNode that can perform AccumulatorOp.
 #[derive(Getters, Setters)]
@@ -25,7 +25,7 @@ impl<T, P: AccumulatorOp<T> + Clone> Default for AccumulatorNode<T, P> {
     }
 }
 
-impl<T: 'static + Send, P> AccumulatorNode<T, P>
+impl<T: 'static + Send + EstimatedSize, P> AccumulatorNode<T, P>
 where
     P: 'static + AccumulatorOp<T> + MessageProcessor<T> + Clone,
 {
@@ -40,7 +40,9 @@ where
 
     pub fn build(&self) -> ExecutionNode<T> {
         let data_processor = Box::new(self.accumulator.clone());
-        ExecutionNode::<T>::new(data_processor, 1)
+        let node = ExecutionNode::<T>::new(data_processor, 1);
+        node.set_label("Accumulator");
+        node
     }
 }
 
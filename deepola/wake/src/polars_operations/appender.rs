@@ -3,7 +3,7 @@ use std::{marker::PhantomData, sync::Arc};
 use getset::{Getters, Setters};
 use polars::prelude::DataFrame;
 
-use crate::{graph::ExecutionNode, processor::MessageProcessor};
+use crate::{data::EstimatedSize, graph::ExecutionNode, processor::MessageProcessor};
 
 /// Factory class for generating an Appender-type execution node, which is designed to support
 /// [AppenderOp] transformation.
@@ -31,7 +31,7 @@ impl<T, P: AppenderOp<T>> Default for AppenderNode<T, P> {
     }
 }
 
-impl<T: 'static + Send, P> AppenderNode<T, P>
+impl<T: 'static + Send + EstimatedSize, P> AppenderNode<T, P>
 where
     P: 'static + AppenderOp<T> + MessageProcessor<T> + Clone,
 {
@@ -57,7 +57,9 @@ where
 
     pub fn build(&self) -> ExecutionNode<T> {
         let data_processor = self.appender.clone();
-        ExecutionNode::<T>::new(Box::new(data_processor), 1)
+        let node = ExecutionNode::<T>::new(Box::new(data_processor), 1);
+        node.set_label("Appender");
+        node
     }
 }
 
@@ -0,0 +1,422 @@
+use polars::prelude::{DataFrame, Field, NamedFrom, Series};
+use polars::prelude::{DataType as PolarsDataType, Schema as PolarsSchema};
+
+use crate::data::{
+    ArrayRow, Column, DataBlock, DataCell, DataType, MetaCell, Schema, SCHEMA_META_NAME,
+};
+use crate::graph::AdapterNode;
+
+/// The polars type of the values of a column of type `dtype`. Panics for tuples, which have no
+/// counterpart.
+pub fn to_polars_dtype(dtype: &DataType) -> PolarsDataType {
+    match dtype {
+        DataType::Boolean => PolarsDataType::Boolean,
+        DataType::UnsignedInt => PolarsDataType::UInt64,
+        DataType::Integer => PolarsDataType::Int32,
+        DataType::Float => PolarsDataType::Float64,
+        DataType::Text => PolarsDataType::Utf8,
+        DataType::Null => PolarsDataType::Null,
+        DataType::Tuple => panic!("No polars type for {:?}", dtype),
+    }
+}
+
+/// The type of the cells read from a polars column of type `dtype`. Integers of any width
+/// become (32-bit) Integers. Panics for types without a counterpart (e.g., dates or lists).
+pub fn from_polars_dtype(dtype: &PolarsDataType) -> DataType {
+    match dtype {
+        PolarsDataType::Boolean => DataType::Boolean,
+        PolarsDataType::UInt8
+        | PolarsDataType::UInt16
+        | PolarsDataType::UInt32
+        | PolarsDataType::UInt64 => DataType::UnsignedInt,
+        PolarsDataType::Int8
+        | PolarsDataType::Int16
+        | PolarsDataType::Int32
+        | PolarsDataType::Int64 => DataType::Integer,
+        PolarsDataType::Float32 | PolarsDataType::Float64 => DataType::Float,
+        PolarsDataType::Utf8 => DataType::Text,
+        PolarsDataType::Null => DataType::Null,
+        _ => panic!("No DataType for polars type {}", dtype),
+    }
+}
+
+/// The polars schema of the DataFrames holding rows of `schema`.
+pub fn to_polars_schema(schema: &Schema) -> PolarsSchema {
+    schema
+        .columns
+        .iter()
+        .map(|column| Field::new(&column.name, to_polars_dtype(&column.dtype)))
+        .collect()
+}
+
+/// The schema of the rows of `df` (see [dataframe_to_rows]), with `key_columns` as keys.
+pub fn schema_from_dataframe(df: &DataFrame, table: &str, key_columns: &[String]) -> Schema {
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|series| {
+            let name = series.name().to_string();
+            let dtype = from_polars_dtype(series.dtype());
+            if key_columns.contains(&name) {
+                Column::from_key_field(name, dtype)
+            } else {
+                Column::from_field(name, dtype)
+            }
+        })
+        .collect();
+    Schema::new(table.to_string(), columns)
+}
+
+/// The cells of a column, with nulls as [DataCell::Null].
+fn series_to_cells(series: &Series) -> Vec<DataCell> {
+    let cells = |values: Vec<Option<DataCell>>| -> Vec<DataCell> {
+        values
+            .into_iter()
+            .map(|value| value.unwrap_or(DataCell::Null()))
+            .collect()
+    };
+    match from_polars_dtype(series.dtype()) {
+        DataType::Boolean => cells(
+            series
+                .bool()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.map(DataCell::Boolean))
+                .collect(),
+        ),
+        DataType::UnsignedInt => cells(
+            series
+                .cast(&PolarsDataType::UInt64)
+                .unwrap()
+                .u64()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.map(|value| DataCell::UnsignedInt(value as usize)))
+                .collect(),
+        ),
+        DataType::Integer => cells(
+            series
+                .cast(&PolarsDataType::Int64)
+                .unwrap()
+                .i64()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.map(|value| DataCell::Integer(value.try_into().unwrap())))
+                .collect(),
+        ),
+        DataType::Float => cells(
+            series
+                .cast(&PolarsDataType::Float64)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.map(DataCell::Float))
+                .collect(),
+        ),
+        DataType::Text => cells(
+            series
+                .utf8()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.map(DataCell::from))
+                .collect(),
+        ),
+        _ => vec![DataCell::Null(); series.len()],
+    }
+}
+
+/// The rows of `df`, with nulls as [DataCell::Null].
+pub fn dataframe_to_rows(df: &DataFrame) -> Vec<ArrayRow> {
+    let columns: Vec<Vec<DataCell>> = df.get_columns().iter().map(series_to_cells).collect();
+    (0..df.height())
+        .map(|row| {
+            columns
+                .iter()
+                .map(|column| column[row].clone())
+                .collect::<Vec<DataCell>>()
+                .into()
+        })
+        .collect()
+}
+
+/// The column of `rows` at `index`, of type `dtype`. Null cells become nulls.
+fn cells_to_series(name: &str, dtype: &DataType, rows: &[ArrayRow], index: usize) -> Series {
+    let cells = rows.iter().map(|row| match &row[index] {
+        DataCell::Null() => None,
+        cell => Some(cell),
+    });
+    match dtype {
+        DataType::Boolean => {
+            let values: Vec<Option<bool>> = cells
+                .map(|cell| {
+                    cell.map(|cell| match cell {
+                        DataCell::Boolean(value) => *value,
+                        _ => panic!("Invalid Conversion: {:?}", cell),
+                    })
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        DataType::UnsignedInt => {
+            let values: Vec<Option<u64>> = cells
+                .map(|cell| {
+                    cell.map(|cell| match cell {
+                        DataCell::UnsignedInt(value) => *value as u64,
+                        _ => panic!("Invalid Conversion: {:?}", cell),
+                    })
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        DataType::Integer => {
+            let values: Vec<Option<i32>> = cells.map(|cell| cell.map(i32::from)).collect();
+            Series::new(name, values)
+        }
+        DataType::Float => {
+            let values: Vec<Option<f64>> = cells.map(|cell| cell.map(f64::from)).collect();
+            Series::new(name, values)
+        }
+        DataType::Text => {
+            let values: Vec<Option<&str>> = cells
+                .map(|cell| {
+                    cell.map(|cell| match cell {
+                        DataCell::Text(value) => value.as_ref(),
+                        _ => panic!("Invalid Conversion: {:?}", cell),
+                    })
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        _ => Series::full_null(name, rows.len(), &to_polars_dtype(dtype)),
+    }
+}
+
+/// The DataFrame holding `rows`, whose columns are those of `schema` (see [to_polars_dtype]).
+pub fn rows_to_dataframe(rows: &[ArrayRow], schema: &Schema) -> DataFrame {
+    let columns = schema
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| cells_to_series(&column.name, &column.dtype, rows, index))
+        .collect();
+    DataFrame::new(columns).unwrap()
+}
+
+/// Factory class for adapters converting DataFrames into blocks of ArrayRows, e.g., to feed the
+/// operators of [crate::operations]. Each block carries the schema of its rows, from the
+/// columns of its DataFrame (see [schema_from_dataframe]), and the progress of the DataFrame.
+///
+/// Example:
+/// ```
+/// use wake::polars_operations::ToRowsNode;
+///
+/// ToRowsNode::new()
+///     .table("lineitem")
+///     .key_columns(vec!["l_orderkey".into(), "l_linenumber".into()])
+///     .build();
+/// ```
+pub struct ToRowsNode {
+    table: String,
+    key_columns: Vec<String>,
+}
+
+impl Default for ToRowsNode {
+    fn default() -> Self {
+        Self {
+            table: "unnamed".to_string(),
+            key_columns: vec![],
+        }
+    }
+}
+
+impl ToRowsNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The table of the schema of the rows.
+    pub fn table(&mut self, table: &str) -> &mut Self {
+        self.table = table.to_string();
+        self
+    }
+
+    /// The columns marked as keys in the schema of the rows.
+    pub fn key_columns(&mut self, key_columns: Vec<String>) -> &mut Self {
+        self.key_columns = key_columns;
+        self
+    }
+
+    pub fn build(&self) -> AdapterNode<DataFrame, Vec<ArrayRow>> {
+        let table = self.table.clone();
+        let key_columns = self.key_columns.clone();
+        let adapter = AdapterNode::new(move |dblock: &DataBlock<DataFrame>| {
+            let df = dblock.data();
+            let schema = schema_from_dataframe(df, &table, &key_columns);
+            let mut metadata = dblock.progress_metadata();
+            metadata.insert(SCHEMA_META_NAME.into(), MetaCell::from(schema));
+            DataBlock::new(dataframe_to_rows(df), metadata)
+        });
+        adapter.node().set_label("ToRows");
+        adapter
+    }
+}
+
+/// Factory class for adapters converting blocks of ArrayRows into DataFrames, whose columns are
+/// those of the schema of each block (see [rows_to_dataframe]).
+///
+/// Example:
+/// ```
+/// use wake::polars_operations::ToDataFrameNode;
+///
+/// ToDataFrameNode::new().build();
+/// ```
+#[derive(Default)]
+pub struct ToDataFrameNode;
+
+impl ToDataFrameNode {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn build(&self) -> AdapterNode<Vec<ArrayRow>, DataFrame> {
+        let adapter = AdapterNode::new(|dblock: &DataBlock<Vec<ArrayRow>>| {
+            let df = rows_to_dataframe(dblock.data(), dblock.schema());
+            DataBlock::new(df, dblock.progress_metadata())
+        });
+        adapter.node().set_label("ToDataFrame");
+        adapter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{DataMessage, DATABLOCK_PROGRESS};
+    use crate::graph::{ExecutionService, NodeReader};
+    use crate::operations::{Aggregate, AggregationOperation, GroupByNode};
+    use polars::prelude::df;
+    use std::collections::HashMap;
+
+    fn example_df() -> DataFrame {
+        df!(
+            "city" => &[Some("Urbana"), Some("Champaign"), None],
+            "population" => &[Some(42i64), None, Some(7)],
+            "area" => &[Some(30.5), Some(59.0), None],
+            "capital" => &[Some(false), None, Some(true)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn can_convert_dtypes() {
+        for dtype in [
+            DataType::Boolean,
+            DataType::UnsignedInt,
+            DataType::Integer,
+            DataType::Float,
+            DataType::Text,
+            DataType::Null,
+        ] {
+            assert_eq!(from_polars_dtype(&to_polars_dtype(&dtype)), dtype);
+        }
+        assert_eq!(from_polars_dtype(&PolarsDataType::Int64), DataType::Integer);
+        assert_eq!(from_polars_dtype(&PolarsDataType::Float32), DataType::Float);
+    }
+
+    #[test]
+    fn can_convert_rows_with_nulls() {
+        let df = example_df();
+        let schema = schema_from_dataframe(&df, "cities", &["city".into()]);
+        assert_eq!(
+            schema.columns,
+            vec![
+                Column::from_key_field("city".into(), DataType::Text),
+                Column::from_field("population".into(), DataType::Integer),
+                Column::from_field("area".into(), DataType::Float),
+                Column::from_field("capital".into(), DataType::Boolean),
+            ]
+        );
+        let rows = dataframe_to_rows(&df);
+        assert_eq!(
+            rows,
+            vec![
+                ArrayRow::from([
+                    "Urbana".into(),
+                    42.into(),
+                    30.5.into(),
+                    DataCell::Boolean(false)
+                ]),
+                ArrayRow::from([
+                    "Champaign".into(),
+                    DataCell::Null(),
+                    59.0.into(),
+                    DataCell::Null()
+                ]),
+                ArrayRow::from([
+                    DataCell::Null(),
+                    7.into(),
+                    DataCell::Null(),
+                    DataCell::Boolean(true)
+                ]),
+            ]
+        );
+
+        // back to a DataFrame, with the polars types of the schema
+        let output_df = rows_to_dataframe(&rows, &schema);
+        let expected_df = df!(
+            "city" => &[Some("Urbana"), Some("Champaign"), None],
+            "population" => &[Some(42i32), None, Some(7)],
+            "area" => &[Some(30.5), Some(59.0), None],
+            "capital" => &[Some(false), None, Some(true)],
+        )
+        .unwrap();
+        assert!(output_df.frame_equal_missing(&expected_df));
+        assert_eq!(output_df.schema(), to_polars_schema(&schema));
+    }
+
+    #[test]
+    fn can_switch_representation_in_pipeline() {
+        // DataFrames -> rows -> GroupByNode -> DataFrames
+        let to_rows = ToRowsNode::new().build();
+        let groupby = GroupByNode::new()
+            .group_by(vec!["city".into()])
+            .aggregates(vec![Aggregate {
+                column: "population".into(),
+                operation: AggregationOperation::Sum,
+                alias: Some("population".into()),
+            }])
+            .build();
+        groupby.subscribe_to_node(&to_rows, 0);
+        let to_df = ToDataFrameNode::new().build();
+        to_df.subscribe_to_node(&groupby, 0);
+        let reader_node = NodeReader::new(&to_df);
+
+        let metadata = HashMap::from([(DATABLOCK_PROGRESS.into(), MetaCell::from(0.5))]);
+        let input = DataBlock::new(example_df(), metadata);
+        to_rows.node().write_to_self(0, DataMessage::from(input));
+        to_rows.node().write_to_self(0, DataMessage::eof());
+
+        // Each adapter runs with the nodes of its input type.
+        let mut df_service = ExecutionService::<DataFrame>::create();
+        df_service.add(to_rows.into_node());
+        let mut row_service = ExecutionService::<Vec<ArrayRow>>::create();
+        row_service.add(groupby);
+        row_service.add(to_df.into_node());
+        df_service.run();
+        row_service.run();
+
+        let message = reader_node.read();
+        let dblock = message.datablock();
+        assert_eq!(dblock.progress(), Some(0.5));
+        let expected_df = df!(
+            "city" => &[Some("Urbana"), Some("Champaign"), None],
+            "population" => &[Some(42i32), None, Some(7)],
+        )
+        .unwrap();
+        assert!(dblock.data().frame_equal_missing(&expected_df));
+        assert!(reader_node.read().is_eof());
+        df_service.join();
+        row_service.join();
+    }
+}
@@ -1,5 +1,8 @@
 // use polars::series::Series;
 use polars::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read};
 
 use crate::data::*;
 use crate::graph::ExecutionNode;
@@ -55,7 +58,47 @@ impl CSVReaderBuilder {
             self.column_names.clone(),
             self.projected_cols.clone(),
         );
-        ExecutionNode::<DataFrame>::new(Box::new(data_processor), 1)
+        let node = ExecutionNode::<DataFrame>::new(Box::new(data_processor), 1);
+        node.set_label("CSVReader");
+        node
+    }
+}
+
+/// Compression formats that [CSVReader] decompresses transparently. The format is detected
+/// from the file extension, e.g., `lineitem.tbl.1.gz` or `lineitem.tbl.zst`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputCompression {
+    Gzip,
+    Zstd,
+}
+
+impl InputCompression {
+    /// Returns `None` if the file is not compressed.
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        if filename.ends_with(".gz") {
+            Some(InputCompression::Gzip)
+        } else if filename.ends_with(".zst") {
+            Some(InputCompression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Reads and decompresses the whole file.
+    pub fn decompress(&self, filename: &str) -> Vec<u8> {
+        let file = File::open(filename).unwrap();
+        let mut bytes = vec![];
+        match self {
+            // MultiGzDecoder also handles files made of several gzip members (e.g., by pigz).
+            InputCompression::Gzip => flate2::read::MultiGzDecoder::new(file)
+                .read_to_end(&mut bytes)
+                .unwrap(),
+            InputCompression::Zstd => zstd::stream::read::Decoder::new(file)
+                .unwrap()
+                .read_to_end(&mut bytes)
+                .unwrap(),
+        };
+        bytes
     }
 }
 
@@ -85,14 +128,25 @@ impl CSVReader {
     }
 
     fn dataframe_from_filename(&self, filename: &str) -> DataFrame {
-        let mut reader = polars::prelude::CsvReader::from_path(filename)
-            .unwrap()
-            .has_header(self.has_headers)
-            .with_delimiter(self.delimiter as u8);
-        if self.projected_cols.is_some() {
-            reader = reader.with_projection(self.projected_cols.clone());
-        }
-        let mut df = reader.finish().unwrap();
+        let mut df = match InputCompression::from_filename(filename) {
+            Some(compression) => {
+                // Compressed partitions are decompressed in memory, one file at a time.
+                let bytes = compression.decompress(filename);
+                polars::prelude::CsvReader::new(Cursor::new(bytes))
+                    .has_header(self.has_headers)
+                    .with_delimiter(self.delimiter as u8)
+                    .with_projection(self.projected_cols.clone())
+                    .finish()
+                    .unwrap()
+            }
+            None => polars::prelude::CsvReader::from_path(filename)
+                .unwrap()
+                .has_header(self.has_headers)
+                .with_delimiter(self.delimiter as u8)
+                .with_projection(self.projected_cols.clone())
+                .finish()
+                .unwrap(),
+        };
         if self.column_names.is_some() {
             if let Some(a) = &self.column_names {
                 df.set_column_names(a).unwrap();
@@ -108,6 +162,7 @@ impl StreamProcessor<DataFrame> for CSVReader {
         input_stream: crate::channel::MultiChannelReader<DataFrame>,
         output_stream: crate::channel::MultiChannelBroadcaster<DataFrame>,
     ) {
+        let mut records_read = 0;
         loop {
             let channel_seq = 0;
             let message = input_stream.read(channel_seq);
@@ -118,17 +173,33 @@ impl StreamProcessor<DataFrame> for CSVReader {
                 }
                 Payload::Signal(_) => break,
                 Payload::Some(dblock) => {
-                    for series in dblock.data().iter() {
-                        // This must be a length-one Polars series containing
-                        // file names in its rows
-                        let rows = series.utf8().unwrap();
-
-                        // each file name produces multiple Series (each is a column)
-                        rows.into_iter().for_each(|filename| {
-                            let df = self.dataframe_from_filename(filename.unwrap());
-                            let message = DataMessage::from(DataBlock::from(df));
-                            output_stream.write(message);
-                        });
+                    // Each series must contain file names in its rows; each file name
+                    // produces a DataFrame.
+                    let filenames: Vec<&str> = dblock
+                        .data()
+                        .iter()
+                        .flat_map(|series| series.utf8().unwrap().into_no_null_iter())
+                        .collect();
+                    let progress = ScanProgress::new(&filenames);
+                    for (i, filename) in filenames.iter().enumerate() {
+                        // The remaining files are skipped once no subscriber needs more
+                        // data, e.g., after a LIMIT is reached downstream.
+                        if output_stream.is_closed() {
+                            log::debug!("Output closed; skipping the remaining files.");
+                            input_stream.close();
+                            return;
+                        }
+                        let df = self.dataframe_from_filename(filename);
+                        records_read += df.height();
+                        let metadata = HashMap::from([
+                            (DATABLOCK_PROGRESS.into(), MetaCell::from(progress.after(i))),
+                            (
+                                DATABLOCK_RECORDS_READ.into(),
+                                MetaCell::from(records_read as f64),
+                            ),
+                        ]);
+                        let message = DataMessage::from(DataBlock::new(df, metadata));
+                        output_stream.write(message);
                     }
                 }
             }
@@ -136,6 +207,37 @@ impl StreamProcessor<DataFrame> for CSVReader {
     }
 }
 
+/// The progress of a [CSVReader] through the files of an input message, weighted by their
+/// sizes so that partitions of different sizes are accounted for.
+struct ScanProgress {
+    /// Total size of the first i+1 files.
+    cumulative_sizes: Vec<u64>,
+}
+
+impl ScanProgress {
+    fn new(filenames: &[&str]) -> Self {
+        let mut total = 0;
+        let cumulative_sizes = filenames
+            .iter()
+            .map(|filename| {
+                total += std::fs::metadata(filename).map(|m| m.len()).unwrap_or(0);
+                total
+            })
+            .collect();
+        ScanProgress { cumulative_sizes }
+    }
+
+    /// The fraction of the input read once the i-th file has been read.
+    fn after(&self, i: usize) -> f64 {
+        let total = *self.cumulative_sizes.last().unwrap();
+        if total == 0 {
+            // Sizes are unknown; the files are assumed to be of the same size.
+            return (i + 1) as f64 / self.cumulative_sizes.len() as f64;
+        }
+        self.cumulative_sizes[i] as f64 / total as f64
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,6 +271,7 @@ mod tests {
         // We are reading lineitem-100 four times.
         let total_input_len = 400;
         let mut total_output_len = 0;
+        let mut progress = vec![];
         loop {
             let message = reader_node.read();
             if message.is_eof() {
@@ -178,9 +281,13 @@ mod tests {
             let data = dblock.data();
             let message_len = data.height();
             total_output_len += message_len;
+            assert_eq!(dblock.records_read(), Some(total_output_len as f64));
+            progress.push(dblock.progress().unwrap());
         }
         // Assert total record length.
         assert_eq!(total_output_len, total_input_len);
+        // The progress is through the files of each input message.
+        assert_eq!(progress, vec![0.5, 1.0, 0.5, 1.0]);
     }
 
     #[test]
@@ -239,4 +346,73 @@ mod tests {
             assert_eq!(data.width(), total_column_count);
         }
     }
+
+    /// Writes a gzip and a zstd copy of lineitem-100 to a temporary directory.
+    fn compressed_copies() -> (std::path::PathBuf, Vec<String>) {
+        use std::io::Write;
+
+        let raw = std::fs::read("resources/tpc-h/data/lineitem-100.csv").unwrap();
+        let directory = std::env::temp_dir().join(format!("wake-csvreader-{}", nanoid::nanoid!(8)));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let gz_path = directory.join("lineitem-100.csv.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&gz_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(&raw).unwrap();
+        encoder.finish().unwrap();
+
+        let zst_path = directory.join("lineitem-100.csv.zst");
+        std::fs::write(&zst_path, zstd::stream::encode_all(&raw[..], 0).unwrap()).unwrap();
+
+        let filenames = vec![
+            gz_path.to_str().unwrap().to_string(),
+            zst_path.to_str().unwrap().to_string(),
+        ];
+        (directory, filenames)
+    }
+
+    #[test]
+    fn test_compressed_read() {
+        assert_eq!(InputCompression::from_filename("lineitem.tbl.1"), None);
+        assert_eq!(
+            InputCompression::from_filename("lineitem.tbl.1.gz"),
+            Some(InputCompression::Gzip)
+        );
+        assert_eq!(
+            InputCompression::from_filename("lineitem.tbl.zst"),
+            Some(InputCompression::Zstd)
+        );
+
+        let (directory, filenames) = compressed_copies();
+        let input_files = df!("col" => &filenames).unwrap();
+        let csvreader = CSVReaderBuilder::new()
+            .delimiter(',')
+            .has_headers(true)
+            .projected_cols(Some(vec![0, 1, 2]))
+            .build();
+        csvreader.write_to_self(0, DataMessage::from(input_files));
+        csvreader.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(&csvreader);
+        csvreader.run();
+
+        // Each compressed file still produces exactly one message.
+        let mut message_count = 0;
+        loop {
+            let message = reader_node.read();
+            if message.is_eof() {
+                break;
+            }
+            let data = message.datablock().data();
+            assert_eq!(data.height(), 100);
+            assert_eq!(
+                data.get_column_names(),
+                vec!["l_orderkey", "l_partkey", "l_suppkey"]
+            );
+            message_count += 1;
+        }
+        assert_eq!(message_count, 2);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
 }
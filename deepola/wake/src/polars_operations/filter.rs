@@ -0,0 +1,102 @@
+use polars::prelude::DataFrame;
+
+use crate::graph::ExecutionNode;
+use crate::processor::MessageProcessor;
+use crate::sql::{Expr, Literal};
+
+/// Factory class for nodes keeping the rows of each DataBlock for which a predicate is true.
+/// Unlike a [crate::polars_operations::MapAppender], the predicate is an [Expr], so it can be
+/// printed and inspected by a planner.
+///
+/// Example:
+/// ```
+/// use wake::polars_operations::FilterNode;
+/// use wake::sql::parse_expr;
+///
+/// FilterNode::new()
+///     .predicate(parse_expr("p_size IN (5, 10) AND p_mfgr LIKE 'Manufacturer#1%'").unwrap())
+///     .build();
+/// ```
+pub struct FilterNode {
+    predicate: Expr,
+}
+
+impl Default for FilterNode {
+    /// A filter keeping all the rows.
+    fn default() -> Self {
+        Self {
+            predicate: Expr::Literal(Literal::Boolean(true)),
+        }
+    }
+}
+
+impl FilterNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn predicate(&mut self, predicate: Expr) -> &mut Self {
+        self.predicate = predicate;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<DataFrame> {
+        let data_processor = ExprFilter {
+            predicate: self.predicate.clone(),
+        };
+        let node = ExecutionNode::<DataFrame>::new(Box::new(data_processor), 1);
+        node.set_label(&format!("Filter: {}", self.predicate));
+        node
+    }
+}
+
+struct ExprFilter {
+    predicate: Expr,
+}
+
+impl MessageProcessor<DataFrame> for ExprFilter {
+    fn process_msg(&self, df: &DataFrame) -> Option<DataFrame> {
+        let mask = self
+            .predicate
+            .evaluate_mask(df)
+            .unwrap_or_else(|e| panic!("Failed to evaluate {}: {}", self.predicate, e));
+        Some(df.filter(&mask).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data::DataMessage, graph::NodeReader, sql::parse_expr};
+    use polars::prelude::*;
+
+    #[test]
+    fn can_filter_rows() {
+        let filter = FilterNode::new()
+            .predicate(parse_expr("p_retailprice > 1000 AND p_type NOT LIKE 'STANDARD%'").unwrap())
+            .build();
+        assert_eq!(
+            filter.label(),
+            "Filter: p_retailprice > 1000 and p_type not like 'STANDARD%'"
+        );
+        let input_df = df!(
+            "p_type" => &["PROMO BRUSHED", "STANDARD TIN", "PROMO TIN"],
+            "p_retailprice" => &[1000.5, 1100.0, 900.0],
+        )
+        .unwrap();
+        let expected_output = df!(
+            "p_type" => &["PROMO BRUSHED"],
+            "p_retailprice" => &[1000.5],
+        )
+        .unwrap();
+
+        filter.write_to_self(0, DataMessage::from(input_df));
+        filter.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(&filter);
+        filter.run();
+
+        let message = reader_node.read();
+        assert_eq!(*message.datablock().data(), expected_output);
+        assert!(reader_node.read().is_eof());
+    }
+}
@@ -28,7 +28,15 @@ impl HashJoinBuilder {
 
     pub fn build(&self) -> ExecutionNode<DataFrame> {
         let hash_join_node = HashJoinNode::new(&self.left_on, &self.right_on);
-        ExecutionNode::<DataFrame>::new(Box::new(hash_join_node), 2)
+        let node = ExecutionNode::<DataFrame>::new(Box::new(hash_join_node), 2);
+        let keys: Vec<String> = self
+            .left_on
+            .iter()
+            .zip(self.right_on.iter())
+            .map(|(l, r)| format!("{} = {}", l, r))
+            .collect();
+        node.set_label(&format!("HashJoin: {}", keys.join(", ")));
+        node
     }
 }
 
@@ -102,8 +110,13 @@ impl StreamProcessor<DataFrame> for HashJoinNode {
                 Payload::Signal(_) => break,
                 Payload::Some(dblock) => {
                     let df = self.process(dblock.data());
-                    let message = DataMessage::from(DataBlock::from(df));
+                    // The progress of the join is that of its probe side.
+                    let message = DataMessage::from(DataBlock::new(df, dblock.progress_metadata()));
                     output_stream.write(message);
+                    if output_stream.is_closed() {
+                        input_stream.close();
+                        break;
+                    }
                 }
             }
         }
@@ -0,0 +1,145 @@
+use polars::prelude::*;
+
+use crate::data::*;
+use crate::graph::ExecutionNode;
+use crate::processor::StreamProcessor;
+
+/// Factory class for nodes keeping the first `limit` rows of their input.
+///
+/// Once `limit` rows have been sent, the node sends EOF and closes its input channel, so that
+/// the nodes upstream (down to the CSVReaders) stop reading data that would be discarded.
+///
+/// Example:
+/// ```
+/// use wake::polars_operations::LimitNode;
+///
+/// LimitNode::new().limit(10).build();
+/// ```
+#[derive(Default)]
+pub struct LimitNode {
+    limit: usize,
+    snapshots: bool,
+}
+
+impl LimitNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn limit(&mut self, limit: usize) -> &mut Self {
+        self.limit = limit;
+        self
+    }
+
+    /// If set, each input message is a complete snapshot (e.g., the output of an
+    /// accumulator), and the first rows of every snapshot are sent. The input is then read
+    /// until EOF.
+    pub fn snapshots(&mut self, snapshots: bool) -> &mut Self {
+        self.snapshots = snapshots;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<DataFrame> {
+        let data_processor = Limit {
+            limit: self.limit,
+            snapshots: self.snapshots,
+        };
+        let node = ExecutionNode::<DataFrame>::new(Box::new(data_processor), 1);
+        node.set_label(&format!("Limit: {}", self.limit));
+        node
+    }
+}
+
+/// A custom StreamProcessor<DataFrame> that sends the first rows of its input.
+struct Limit {
+    limit: usize,
+    snapshots: bool,
+}
+
+impl StreamProcessor<DataFrame> for Limit {
+    fn process_stream(
+        &self,
+        input_stream: crate::channel::MultiChannelReader<DataFrame>,
+        output_stream: crate::channel::MultiChannelBroadcaster<DataFrame>,
+    ) {
+        let mut remaining = self.limit;
+        loop {
+            if remaining == 0 && !self.snapshots {
+                output_stream.write(DataMessage::eof());
+                input_stream.close();
+                break;
+            }
+            let channel_seq = 0;
+            let message = input_stream.read(channel_seq);
+            match message.payload() {
+                Payload::EOF => {
+                    output_stream.write(message);
+                    break;
+                }
+                Payload::Signal(_) => break,
+                Payload::Some(dblock) => {
+                    let df = dblock.data().head(Some(remaining));
+                    if !self.snapshots {
+                        remaining -= df.height();
+                    }
+                    output_stream.write(DataMessage::from(DataBlock::new(
+                        df,
+                        dblock.progress_metadata(),
+                    )));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::NodeReader;
+
+    #[test]
+    fn stops_after_limit() {
+        let limit_node = LimitNode::new().limit(3).build();
+        for _ in 0..3 {
+            let df = df!("key" => &["a", "b"]).unwrap();
+            limit_node.write_to_self(0, DataMessage::from(df));
+        }
+        let reader_node = NodeReader::new(&limit_node);
+        limit_node.run();
+
+        let mut heights = vec![];
+        loop {
+            let message = reader_node.read();
+            if message.is_eof() {
+                break;
+            }
+            heights.push(message.datablock().data().height());
+        }
+        assert_eq!(heights, vec![2, 1]);
+        // The input is closed although its EOF has never been written.
+        assert!(limit_node.self_writer(0).is_closed());
+    }
+
+    #[test]
+    fn limits_each_snapshot() {
+        let limit_node = LimitNode::new().limit(1).snapshots(true).build();
+        for _ in 0..2 {
+            let df = df!("key" => &["a", "b"]).unwrap();
+            limit_node.write_to_self(0, DataMessage::from(df));
+        }
+        limit_node.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(&limit_node);
+        limit_node.run();
+
+        let mut heights = vec![];
+        loop {
+            let message = reader_node.read();
+            if message.is_eof() {
+                break;
+            }
+            heights.push(message.datablock().data().height());
+        }
+        assert_eq!(heights, vec![1, 1]);
+        assert!(!limit_node.self_writer(0).is_closed());
+    }
+}
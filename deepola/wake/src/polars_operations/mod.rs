@@ -1,11 +1,25 @@
 mod accumulator;
 mod appender;
+mod convert;
 mod csvreader;
+mod filter;
 mod hash_join;
+mod limit;
+mod project;
 mod series_mq;
+mod sink;
+mod sort;
+mod union;
 pub mod util;
 
 pub use accumulator::*;
 pub use appender::*;
+pub use convert::*;
 pub use csvreader::*;
+pub use filter::*;
 pub use hash_join::*;
+pub use limit::*;
+pub use project::*;
+pub use sink::*;
+pub use sort::*;
+pub use union::*;
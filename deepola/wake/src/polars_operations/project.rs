@@ -0,0 +1,112 @@
+use polars::prelude::{DataFrame, Series};
+
+use crate::graph::ExecutionNode;
+use crate::processor::MessageProcessor;
+use crate::sql::{format_named, Expr};
+
+/// Factory class for nodes computing named expressions on each DataBlock. The output has one
+/// column per expression, in order; input columns that are not listed are dropped.
+///
+/// Example:
+/// ```
+/// use wake::polars_operations::ProjectNode;
+/// use wake::sql::{parse_expr, Expr};
+///
+/// ProjectNode::new()
+///     .expressions(vec![
+///         (Expr::column("l_partkey"), "l_partkey".into()),
+///         (parse_expr("l_extendedprice * (1 - l_discount)").unwrap(), "disc_price".into()),
+///     ])
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct ProjectNode {
+    expressions: Vec<(Expr, String)>,
+}
+
+impl ProjectNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The expressions to compute, with the names of their output columns.
+    pub fn expressions(&mut self, expressions: Vec<(Expr, String)>) -> &mut Self {
+        self.expressions = expressions;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<DataFrame> {
+        let data_processor = ExprProject {
+            expressions: self.expressions.clone(),
+        };
+        let node = ExecutionNode::<DataFrame>::new(Box::new(data_processor), 1);
+        node.set_label(&format!("Project: {}", format_named(&self.expressions)));
+        node
+    }
+}
+
+struct ExprProject {
+    expressions: Vec<(Expr, String)>,
+}
+
+impl MessageProcessor<DataFrame> for ExprProject {
+    fn process_msg(&self, df: &DataFrame) -> Option<DataFrame> {
+        let columns = self
+            .expressions
+            .iter()
+            .map(|(expr, name)| {
+                let mut series = expr
+                    .evaluate(df)
+                    .unwrap_or_else(|e| panic!("Failed to evaluate {}: {}", expr, e));
+                series.rename(name);
+                series
+            })
+            .collect::<Vec<Series>>();
+        Some(DataFrame::new(columns).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data::DataMessage, graph::NodeReader, sql::parse_expr};
+    use polars::prelude::*;
+
+    #[test]
+    fn can_compute_expressions() {
+        let projector = ProjectNode::new()
+            .expressions(vec![
+                (Expr::column("p_size"), "p_size".into()),
+                (
+                    parse_expr("case when p_type like 'PROMO%' then p_retailprice else 0 end")
+                        .unwrap(),
+                    "promo_price".into(),
+                ),
+            ])
+            .build();
+        assert_eq!(
+            projector.label(),
+            "Project: p_size, case when p_type like 'PROMO%' then p_retailprice else 0 end AS promo_price"
+        );
+        let input_df = df!(
+            "p_type" => &["PROMO BRUSHED", "STANDARD TIN"],
+            "p_size" => &[5i64, 12],
+            "p_retailprice" => &[1000.5, 901.0],
+        )
+        .unwrap();
+        let expected_output = df!(
+            "p_size" => &[5i64, 12],
+            "promo_price" => &[1000.5, 0.0],
+        )
+        .unwrap();
+
+        projector.write_to_self(0, DataMessage::from(input_df));
+        projector.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(&projector);
+        projector.run();
+
+        let message = reader_node.read();
+        assert_eq!(*message.datablock().data(), expected_output);
+        assert!(reader_node.read().is_eof());
+    }
+}
@@ -0,0 +1,316 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use polars::prelude::*;
+
+use crate::data::*;
+use crate::graph::ExecutionNode;
+use crate::processor::StreamProcessor;
+
+/// File formats supported by [FileSinkBuilder].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinkFormat {
+    Csv,
+    Parquet,
+    ArrowIpc,
+}
+
+impl SinkFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SinkFormat::Csv => "csv",
+            SinkFormat::Parquet => "parquet",
+            SinkFormat::ArrowIpc => "arrow",
+        }
+    }
+}
+
+/// How the snapshots observed by a sink are laid out on disk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinkMode {
+    /// Every snapshot goes to its own file: `<prefix>-<snapshot no>.<ext>`.
+    FilePerSnapshot,
+    /// A single file `<prefix>.<ext>` always holds the latest snapshot. The file is replaced
+    /// atomically so that readers never observe a partially written result.
+    OverwriteLatest,
+}
+
+/// Factory class for creating a sink node that writes query results to files.
+///
+/// A sink node forwards every message it reads to its subscribers, so it can be placed
+/// between the final node of a query and a [crate::graph::NodeReader].
+pub struct FileSinkBuilder {
+    directory: PathBuf,
+    prefix: String,
+    format: SinkFormat,
+    mode: SinkMode,
+    final_only: bool,
+    delimiter: char,
+}
+
+impl Default for FileSinkBuilder {
+    fn default() -> Self {
+        FileSinkBuilder {
+            directory: PathBuf::from("."),
+            prefix: "result".to_string(),
+            format: SinkFormat::Csv,
+            mode: SinkMode::OverwriteLatest,
+            final_only: false,
+            delimiter: ',',
+        }
+    }
+}
+
+impl FileSinkBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn directory<P: AsRef<Path>>(&mut self, directory: P) -> &mut Self {
+        self.directory = directory.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn prefix(&mut self, prefix: &str) -> &mut Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn format(&mut self, format: SinkFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    pub fn mode(&mut self, mode: SinkMode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    /// If set, only the last snapshot (the one observed right before EOF) is written.
+    pub fn final_only(&mut self, final_only: bool) -> &mut Self {
+        self.final_only = final_only;
+        self
+    }
+
+    /// Field delimiter used when the format is [SinkFormat::Csv].
+    pub fn delimiter(&mut self, delimiter: char) -> &mut Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<DataFrame> {
+        fs::create_dir_all(&self.directory).unwrap();
+        let data_processor = FileSink {
+            directory: self.directory.clone(),
+            prefix: self.prefix.clone(),
+            format: self.format,
+            mode: self.mode,
+            final_only: self.final_only,
+            delimiter: self.delimiter,
+        };
+        let node = ExecutionNode::<DataFrame>::new(Box::new(data_processor), 1);
+        node.set_label(&format!("FileSink: {}", self.directory.display()));
+        node
+    }
+}
+
+/// A custom StreamProcessor<DataFrame> that writes DataFrames to files.
+struct FileSink {
+    directory: PathBuf,
+    prefix: String,
+    format: SinkFormat,
+    mode: SinkMode,
+    final_only: bool,
+    delimiter: char,
+}
+
+impl FileSink {
+    /// The path that the `snapshot_no`-th snapshot is written to.
+    fn snapshot_path(&self, snapshot_no: usize) -> PathBuf {
+        let file_name = match self.mode {
+            SinkMode::FilePerSnapshot => {
+                format!(
+                    "{}-{:05}.{}",
+                    self.prefix,
+                    snapshot_no,
+                    self.format.extension()
+                )
+            }
+            SinkMode::OverwriteLatest => format!("{}.{}", self.prefix, self.format.extension()),
+        };
+        self.directory.join(file_name)
+    }
+
+    fn write_snapshot(&self, df: &DataFrame, snapshot_no: usize) {
+        let path = self.snapshot_path(snapshot_no);
+        match self.mode {
+            SinkMode::FilePerSnapshot => self.write_df(df, &path),
+            SinkMode::OverwriteLatest => {
+                // Write next to the target and rename, so that readers polling the target
+                // file see either the previous or the new snapshot.
+                let tmp_path = path.with_extension(format!("{}.tmp", self.format.extension()));
+                self.write_df(df, &tmp_path);
+                fs::rename(&tmp_path, &path).unwrap();
+            }
+        }
+        log::debug!("Wrote snapshot {} to {:?}", snapshot_no, path);
+    }
+
+    fn write_df(&self, df: &DataFrame, path: &Path) {
+        let mut df = df.clone();
+        let file = File::create(path).unwrap();
+        match self.format {
+            SinkFormat::Csv => {
+                CsvWriter::new(file)
+                    .has_header(true)
+                    .with_delimiter(self.delimiter as u8)
+                    .finish(&mut df)
+                    .unwrap();
+            }
+            SinkFormat::Parquet => {
+                ParquetWriter::new(file).finish(&mut df).unwrap();
+            }
+            SinkFormat::ArrowIpc => {
+                IpcWriter::new(file).finish(&mut df).unwrap();
+            }
+        }
+    }
+}
+
+impl StreamProcessor<DataFrame> for FileSink {
+    fn process_stream(
+        &self,
+        input_stream: crate::channel::MultiChannelReader<DataFrame>,
+        output_stream: crate::channel::MultiChannelBroadcaster<DataFrame>,
+    ) {
+        let mut snapshot_no = 0;
+        let mut last_df: Option<DataFrame> = None;
+        loop {
+            let channel_seq = 0;
+            let message = input_stream.read(channel_seq);
+            match message.payload() {
+                Payload::EOF => {
+                    if let Some(df) = &last_df {
+                        self.write_snapshot(df, snapshot_no);
+                    }
+                    output_stream.write(message);
+                    break;
+                }
+                Payload::Signal(_) => break,
+                Payload::Some(dblock) => {
+                    if self.final_only {
+                        last_df = Some(dblock.data().clone());
+                    } else {
+                        self.write_snapshot(dblock.data(), snapshot_no);
+                        snapshot_no += 1;
+                    }
+                    output_stream.write(message);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataMessage;
+    use crate::graph::NodeReader;
+    use nanoid::nanoid;
+
+    fn example_snapshots() -> Vec<DataFrame> {
+        vec![
+            df!("key" => &["a", "b"], "value" => &[1i64, 2]).unwrap(),
+            df!("key" => &["a", "b"], "value" => &[3i64, 5]).unwrap(),
+        ]
+    }
+
+    fn test_directory() -> PathBuf {
+        std::env::temp_dir().join(format!("wake-sink-{}", nanoid!(8)))
+    }
+
+    fn run_sink(sink: &ExecutionNode<DataFrame>, snapshots: &[DataFrame]) -> usize {
+        for df in snapshots {
+            sink.write_to_self(0, DataMessage::from(df.clone()));
+        }
+        sink.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(sink);
+        sink.run();
+
+        // The sink forwards every message.
+        let mut forwarded = 0;
+        loop {
+            let message = reader_node.read();
+            if message.is_eof() {
+                break;
+            }
+            forwarded += 1;
+        }
+        forwarded
+    }
+
+    fn read_csv(path: &Path) -> DataFrame {
+        CsvReader::from_path(path)
+            .unwrap()
+            .has_header(true)
+            .finish()
+            .unwrap()
+    }
+
+    #[test]
+    fn csv_file_per_snapshot() {
+        let directory = test_directory();
+        let sink = FileSinkBuilder::new()
+            .directory(&directory)
+            .prefix("q1")
+            .format(SinkFormat::Csv)
+            .mode(SinkMode::FilePerSnapshot)
+            .build();
+        let snapshots = example_snapshots();
+        assert_eq!(run_sink(&sink, &snapshots), snapshots.len());
+
+        for (i, expected) in snapshots.iter().enumerate() {
+            let written = read_csv(&directory.join(format!("q1-{:05}.csv", i)));
+            assert_eq!(&written, expected);
+        }
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn parquet_overwrite_latest() {
+        let directory = test_directory();
+        let sink = FileSinkBuilder::new()
+            .directory(&directory)
+            .format(SinkFormat::Parquet)
+            .mode(SinkMode::OverwriteLatest)
+            .build();
+        let snapshots = example_snapshots();
+        run_sink(&sink, &snapshots);
+
+        let file = File::open(directory.join("result.parquet")).unwrap();
+        let written = ParquetReader::new(file).finish().unwrap();
+        assert_eq!(&written, snapshots.last().unwrap());
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn ipc_final_only() {
+        let directory = test_directory();
+        let sink = FileSinkBuilder::new()
+            .directory(&directory)
+            .format(SinkFormat::ArrowIpc)
+            .mode(SinkMode::FilePerSnapshot)
+            .final_only(true)
+            .build();
+        let snapshots = example_snapshots();
+        assert_eq!(run_sink(&sink, &snapshots), snapshots.len());
+
+        // Only the final result is written.
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+        let file = File::open(directory.join("result-00000.arrow")).unwrap();
+        let written = IpcReader::new(file).finish().unwrap();
+        assert_eq!(&written, snapshots.last().unwrap());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,408 @@
+use std::cmp::Ordering;
+
+use polars::prelude::*;
+
+use crate::data::*;
+use crate::graph::ExecutionNode;
+use crate::processor::StreamProcessor;
+
+/// When a [SortNode] sorts its input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortMode {
+    /// Every message is sorted on its own. Use it when each message is a complete snapshot,
+    /// e.g., the output of an accumulator.
+    PerSnapshot,
+    /// All the messages are buffered and sent as a single sorted DataFrame at EOF. Use it when
+    /// each message is a part of the result.
+    AtEof,
+}
+
+/// Factory class for nodes sorting DataFrames by a list of columns.
+///
+/// Example:
+/// ```
+/// use wake::polars_operations::{SortMode, SortNode};
+///
+/// SortNode::new()
+///     .by(vec![("l_returnflag".into(), false), ("l_linestatus".into(), false)])
+///     .mode(SortMode::PerSnapshot)
+///     .build();
+/// ```
+pub struct SortNode {
+    by: Vec<(String, bool)>,
+    mode: SortMode,
+}
+
+impl Default for SortNode {
+    fn default() -> Self {
+        SortNode {
+            by: vec![],
+            mode: SortMode::AtEof,
+        }
+    }
+}
+
+impl SortNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The columns to sort by; `true` means descending.
+    pub fn by(&mut self, by: Vec<(String, bool)>) -> &mut Self {
+        self.by = by;
+        self
+    }
+
+    pub fn mode(&mut self, mode: SortMode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<DataFrame> {
+        let data_processor = Sorter {
+            order: SortOrder::new(&self.by),
+            mode: self.mode,
+        };
+        let node = ExecutionNode::<DataFrame>::new(Box::new(data_processor), 1);
+        node.set_label(&format!("Sort: {}", format_sort_keys(&self.by)));
+        node
+    }
+}
+
+/// Factory class for nodes computing `ORDER BY .. LIMIT k` incrementally. The node keeps the
+/// first `k` rows seen so far, and after each input message sends them, sorted, as the
+/// up-to-date result. At most `k` rows are kept between messages, so the input is never
+/// buffered, and only the selected rows are sorted.
+///
+/// Example:
+/// ```
+/// use wake::polars_operations::TopKNode;
+///
+/// TopKNode::new()
+///     .by(vec![("revenue".into(), true)])
+///     .k(10)
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct TopKNode {
+    by: Vec<(String, bool)>,
+    k: usize,
+    snapshots: bool,
+}
+
+impl TopKNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The columns to sort by; `true` means descending.
+    pub fn by(&mut self, by: Vec<(String, bool)>) -> &mut Self {
+        self.by = by;
+        self
+    }
+
+    pub fn k(&mut self, k: usize) -> &mut Self {
+        self.k = k;
+        self
+    }
+
+    /// If set, each input message is a complete snapshot (e.g., the output of an
+    /// accumulator) that replaces the previous ones, rather than a part of the input.
+    pub fn snapshots(&mut self, snapshots: bool) -> &mut Self {
+        self.snapshots = snapshots;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<DataFrame> {
+        let data_processor = TopK {
+            order: SortOrder::new(&self.by),
+            k: self.k,
+            snapshots: self.snapshots,
+        };
+        let node = ExecutionNode::<DataFrame>::new(Box::new(data_processor), 1);
+        node.set_label(&format!(
+            "TopK: {} LIMIT {}",
+            format_sort_keys(&self.by),
+            self.k
+        ));
+        node
+    }
+}
+
+/// Sort keys as `column [DESC], ..`.
+pub(crate) fn format_sort_keys(by: &[(String, bool)]) -> String {
+    by.iter()
+        .map(|(column, descending)| {
+            if *descending {
+                format!("{} DESC", column)
+            } else {
+                column.clone()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+struct SortOrder {
+    columns: Vec<String>,
+    descending: Vec<bool>,
+}
+
+impl SortOrder {
+    fn new(by: &[(String, bool)]) -> Self {
+        SortOrder {
+            columns: by.iter().map(|(c, _)| c.clone()).collect(),
+            descending: by.iter().map(|(_, d)| *d).collect(),
+        }
+    }
+
+    fn sort(&self, df: &DataFrame) -> DataFrame {
+        if self.columns.is_empty() {
+            return df.clone();
+        }
+        df.sort(self.columns.clone(), self.descending.clone())
+            .unwrap()
+    }
+
+    /// The first `k` rows of `df` in sort order. The rows are selected in linear time, and only
+    /// the selected ones are sorted.
+    fn first_rows(&self, df: &DataFrame, k: usize) -> DataFrame {
+        if self.columns.is_empty() {
+            return df.head(Some(k));
+        }
+        if df.height() > k {
+            // Dates and other logical types compare by their physical values.
+            let keys: Vec<Series> = self
+                .columns
+                .iter()
+                .map(|c| df.column(c).unwrap().to_physical_repr().into_owned())
+                .collect();
+            let mut indices: Vec<IdxSize> = (0..df.height() as IdxSize).collect();
+            if k > 0 {
+                indices.select_nth_unstable_by(k - 1, |a, b| {
+                    self.compare_rows(&keys, *a as usize, *b as usize)
+                });
+            }
+            indices.truncate(k);
+            let selected = df.take(&IdxCa::from_vec("", indices)).unwrap();
+            return self.sort(&selected);
+        }
+        self.sort(df)
+    }
+
+    /// Compares rows `a` and `b` of the sort key columns. Nulls order before any value.
+    fn compare_rows(&self, keys: &[Series], a: usize, b: usize) -> Ordering {
+        for (key, descending) in keys.iter().zip(&self.descending) {
+            let ordering = match (key.get(a), key.get(b)) {
+                (AnyValue::Null, AnyValue::Null) => Ordering::Equal,
+                (AnyValue::Null, _) => Ordering::Less,
+                (_, AnyValue::Null) => Ordering::Greater,
+                (l, r) => l.partial_cmp(&r).unwrap_or(Ordering::Equal),
+            };
+            let ordering = if *descending {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+/// A custom StreamProcessor<DataFrame> that sorts each message, or all of them at EOF.
+struct Sorter {
+    order: SortOrder,
+    mode: SortMode,
+}
+
+impl StreamProcessor<DataFrame> for Sorter {
+    fn process_stream(
+        &self,
+        input_stream: crate::channel::MultiChannelReader<DataFrame>,
+        output_stream: crate::channel::MultiChannelBroadcaster<DataFrame>,
+    ) {
+        let mut buffered: Option<DataFrame> = None;
+        loop {
+            let channel_seq = 0;
+            let message = input_stream.read(channel_seq);
+            match message.payload() {
+                Payload::EOF => {
+                    if let Some(df) = &buffered {
+                        output_stream.write(DataMessage::from(self.order.sort(df)));
+                    }
+                    output_stream.write(message);
+                    break;
+                }
+                Payload::Signal(_) => break,
+                Payload::Some(dblock) => match self.mode {
+                    SortMode::PerSnapshot => {
+                        output_stream.write(DataMessage::from(DataBlock::new(
+                            self.order.sort(dblock.data()),
+                            dblock.progress_metadata(),
+                        )));
+                    }
+                    SortMode::AtEof => match &mut buffered {
+                        Some(df) => {
+                            df.vstack_mut(dblock.data()).unwrap();
+                        }
+                        None => buffered = Some(dblock.data().clone()),
+                    },
+                },
+            }
+        }
+    }
+}
+
+/// A custom StreamProcessor<DataFrame> that keeps the first `k` rows in sort order.
+struct TopK {
+    order: SortOrder,
+    k: usize,
+    snapshots: bool,
+}
+
+impl TopK {
+    fn first_rows(&self, df: &DataFrame) -> DataFrame {
+        self.order.first_rows(df, self.k)
+    }
+}
+
+impl StreamProcessor<DataFrame> for TopK {
+    fn process_stream(
+        &self,
+        input_stream: crate::channel::MultiChannelReader<DataFrame>,
+        output_stream: crate::channel::MultiChannelBroadcaster<DataFrame>,
+    ) {
+        let mut top: Option<DataFrame> = None;
+        loop {
+            let channel_seq = 0;
+            let message = input_stream.read(channel_seq);
+            match message.payload() {
+                Payload::EOF => {
+                    output_stream.write(message);
+                    break;
+                }
+                Payload::Signal(_) => break,
+                Payload::Some(dblock) => {
+                    // Only the first k rows of a message can make it to the result.
+                    let candidates = self.first_rows(dblock.data());
+                    let result = match &top {
+                        Some(top) if !self.snapshots => {
+                            self.first_rows(&top.vstack(&candidates).unwrap())
+                        }
+                        _ => candidates,
+                    };
+                    output_stream.write(DataMessage::from(DataBlock::new(
+                        result.clone(),
+                        dblock.progress_metadata(),
+                    )));
+                    top = Some(result);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::NodeReader;
+
+    fn example_parts() -> Vec<DataFrame> {
+        vec![
+            df!("key" => &["a", "b", "c"], "value" => &[3i64, 1, 4]).unwrap(),
+            df!("key" => &["d", "e"], "value" => &[1i64, 5]).unwrap(),
+            df!("key" => &["f"], "value" => &[2i64]).unwrap(),
+        ]
+    }
+
+    fn run_node(node: &ExecutionNode<DataFrame>) -> Vec<DataFrame> {
+        for df in example_parts() {
+            node.write_to_self(0, DataMessage::from(df));
+        }
+        node.write_to_self(0, DataMessage::eof());
+        let reader_node = NodeReader::new(node);
+        node.run();
+
+        let mut outputs = vec![];
+        loop {
+            let message = reader_node.read();
+            if message.is_eof() {
+                break;
+            }
+            outputs.push(message.datablock().data().clone());
+        }
+        outputs
+    }
+
+    fn keys(df: &DataFrame) -> Vec<&str> {
+        df.column("key")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect()
+    }
+
+    #[test]
+    fn can_sort_at_eof() {
+        let sort_node = SortNode::new()
+            .by(vec![("value".into(), false), ("key".into(), true)])
+            .build();
+        assert_eq!(sort_node.label(), "Sort: value, key DESC");
+        let outputs = run_node(&sort_node);
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(keys(&outputs[0]), vec!["d", "b", "f", "a", "c", "e"]);
+
+        let sort_node = SortNode::new()
+            .by(vec![("value".into(), true)])
+            .mode(SortMode::PerSnapshot)
+            .build();
+        let outputs = run_node(&sort_node);
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(keys(&outputs[0]), vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn can_keep_top_k_rows() {
+        let top_k_node = TopKNode::new()
+            .by(vec![("value".into(), true)])
+            .k(2)
+            .build();
+        assert_eq!(top_k_node.label(), "TopK: value DESC LIMIT 2");
+        let outputs = run_node(&top_k_node);
+        let tops: Vec<Vec<&str>> = outputs.iter().map(keys).collect();
+        assert_eq!(tops, vec![vec!["c", "a"], vec!["e", "c"], vec!["e", "c"]]);
+
+        let top_k_node = TopKNode::new()
+            .by(vec![("value".into(), false)])
+            .k(2)
+            .snapshots(true)
+            .build();
+        let outputs = run_node(&top_k_node);
+        let tops: Vec<Vec<&str>> = outputs.iter().map(keys).collect();
+        assert_eq!(tops, vec![vec!["b", "a"], vec!["d", "e"], vec!["f"]]);
+    }
+
+    #[test]
+    fn selects_first_rows_by_several_keys() {
+        let mut df = df!(
+            "key" => &["a", "b", "c", "d", "e", "f"],
+            "group" => &[Some(2i64), Some(1), None, Some(1), Some(2), Some(1)],
+            "day" => &[3i32, 5, 1, 4, 2, 6],
+        )
+        .unwrap();
+        let day = df
+            .column("day")
+            .unwrap()
+            .cast(&polars::prelude::DataType::Date);
+        df.replace("day", day.unwrap()).unwrap();
+
+        let order = SortOrder::new(&[("group".into(), false), ("day".into(), true)]);
+        assert_eq!(keys(&order.first_rows(&df, 3)), vec!["c", "f", "b"]);
+        assert_eq!(keys(&order.first_rows(&df, 10)).len(), 6);
+        assert_eq!(order.first_rows(&df, 0).height(), 0);
+    }
+}
@@ -0,0 +1,120 @@
+use polars::prelude::*;
+
+use crate::data::*;
+use crate::graph::ExecutionNode;
+use crate::processor::StreamProcessor;
+
+/// Factory class for nodes merging the messages of any number of input channels (UNION ALL).
+/// The DataBlocks are forwarded as they arrive, in no particular order across inputs, and a
+/// single EOF is sent once every input has sent EOF. The inputs are expected to have the same
+/// schema.
+///
+/// Example:
+/// ```
+/// use wake::polars_operations::UnionNode;
+///
+/// let union_node = UnionNode::new().inputs(3).build();
+/// // union_node.subscribe_to_node(&pipeline_1994, 0);
+/// // union_node.subscribe_to_node(&pipeline_1995, 1);
+/// // union_node.subscribe_to_node(&pipeline_1996, 2);
+/// ```
+pub struct UnionNode {
+    inputs: usize,
+}
+
+impl Default for UnionNode {
+    fn default() -> Self {
+        UnionNode { inputs: 2 }
+    }
+}
+
+impl UnionNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of input channels, numbered from 0.
+    pub fn inputs(&mut self, inputs: usize) -> &mut Self {
+        self.inputs = inputs;
+        self
+    }
+
+    pub fn build(&self) -> ExecutionNode<DataFrame> {
+        let node = ExecutionNode::<DataFrame>::new(Box::new(Union), self.inputs);
+        node.set_label("Union");
+        node
+    }
+}
+
+/// A custom StreamProcessor<DataFrame> that forwards the messages of all its input channels.
+struct Union;
+
+impl StreamProcessor<DataFrame> for Union {
+    fn process_stream(
+        &self,
+        input_stream: crate::channel::MultiChannelReader<DataFrame>,
+        output_stream: crate::channel::MultiChannelBroadcaster<DataFrame>,
+    ) {
+        while let Some((_, message)) = input_stream.read_any() {
+            match message.payload() {
+                Payload::EOF => {}
+                Payload::Signal(_) => return,
+                Payload::Some(_) => {
+                    output_stream.write(message);
+                    if output_stream.is_closed() {
+                        input_stream.close();
+                        return;
+                    }
+                }
+            }
+        }
+        output_stream.write(DataMessage::eof());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{ExecutionService, NodeReader};
+    use crate::processor::SimpleMapper;
+
+    #[test]
+    fn can_merge_inputs() {
+        let union_node = UnionNode::new().inputs(3).build();
+        let mut service = ExecutionService::<DataFrame>::create();
+        for seq_no in 0..3 {
+            let source = ExecutionNode::from(SimpleMapper::<DataFrame>::identity());
+            for part in ["a", "b"] {
+                let key = format!("{}{}", seq_no, part);
+                let df = df!("key" => &[key.as_str()]).unwrap();
+                source.write_to_self(0, DataMessage::from(df));
+            }
+            source.write_to_self(0, DataMessage::eof());
+            union_node.subscribe_to_node(&source, seq_no);
+            service.add(source);
+        }
+        let reader_node = NodeReader::new(&union_node);
+        service.add(union_node);
+        service.run();
+
+        let mut records = vec![];
+        loop {
+            let message = reader_node.read();
+            if message.is_eof() {
+                break;
+            }
+            let df = message.datablock().data();
+            records.extend(
+                df.column("key")
+                    .unwrap()
+                    .utf8()
+                    .unwrap()
+                    .into_no_null_iter()
+                    .map(String::from),
+            );
+        }
+        service.join();
+        records.sort();
+        assert_eq!(records, vec!["0a", "0b", "1a", "1b", "2a", "2b"]);
+    }
+}
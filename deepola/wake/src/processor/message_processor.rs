@@ -1,4 +1,4 @@
-use crate::data::{DataMessage, Payload};
+use crate::data::{DataBlock, DataMessage, Payload};
 
 use super::StreamProcessor;
 
@@ -22,9 +22,18 @@ impl<T: Send, R: MessageProcessor<T> + Send> StreamProcessor<T> for R {
                 }
                 Payload::Some(data_block) => {
                     if let Some(df_acc) = self.process_msg(data_block.data()) {
-                        let message = DataMessage::from(df_acc);
+                        let message = DataMessage::from(DataBlock::new(
+                            df_acc,
+                            data_block.progress_metadata(),
+                        ));
                         output_stream.write(message);
                     }
+                    // Nobody needs more output (e.g., a limit has been reached), so tell
+                    // the upstream nodes to stop as well.
+                    if output_stream.is_closed() {
+                        input_stream.close();
+                        break;
+                    }
                 }
                 Payload::Signal(_) => {
                     break;
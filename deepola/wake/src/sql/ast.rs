@@ -0,0 +1,614 @@
+use std::error::Error;
+use std::fmt;
+
+/// A statement accepted by [crate::sql::parse_statement].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Query(Query),
+    /// `EXPLAIN [ANALYZE] SELECT ..`: describe the plan of the query instead of returning its
+    /// result. With ANALYZE, the query is run and the description includes what each operator
+    /// produced.
+    Explain {
+        analyze: bool,
+        query: Query,
+    },
+}
+
+impl Statement {
+    pub fn query(&self) -> &Query {
+        match self {
+            Statement::Query(query) | Statement::Explain { query, .. } => query,
+        }
+    }
+}
+
+/// A `SELECT` statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub projection: Vec<SelectItem>,
+    pub from: Vec<TableWithJoins>,
+    pub selection: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+    pub order_by: Vec<OrderByExpr>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectItem {
+    /// `*`
+    Wildcard,
+    /// An expression with an optional alias (`expr AS alias`).
+    Expr { expr: Expr, alias: Option<String> },
+}
+
+/// An item of the FROM clause: a table followed by zero or more `[INNER] JOIN ... ON`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableWithJoins {
+    pub relation: TableRef,
+    pub joins: Vec<Join>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRef {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl TableRef {
+    /// The name that qualifies the columns of this table in the query.
+    pub fn reference_name(&self) -> &str {
+        self.alias.as_ref().unwrap_or(&self.name)
+    }
+}
+
+/// An inner join.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub relation: TableRef,
+    pub on: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderByExpr {
+    pub expr: Expr,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    /// A `DATE 'YYYY-MM-DD'` literal. Dates are stored as text, so it compares as a string.
+    Date(String),
+    Boolean(bool),
+    Null,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    And,
+    Or,
+}
+
+impl BinaryOperator {
+    /// Binding strength; a higher value binds tighter.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOperator::Or => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::Eq
+            | BinaryOperator::NotEq
+            | BinaryOperator::Lt
+            | BinaryOperator::LtEq
+            | BinaryOperator::Gt
+            | BinaryOperator::GtEq => 4,
+            BinaryOperator::Plus | BinaryOperator::Minus => 5,
+            BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 6,
+        }
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        self.precedence() == 4
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Not,
+    Minus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// A column, optionally qualified with a table name or alias.
+    Column {
+        table: Option<String>,
+        name: String,
+    },
+    Literal(Literal),
+    BinaryOp {
+        left: Box<Expr>,
+        op: BinaryOperator,
+        right: Box<Expr>,
+    },
+    UnaryOp {
+        op: UnaryOperator,
+        expr: Box<Expr>,
+    },
+    /// `expr [NOT] BETWEEN low AND high`
+    Between {
+        expr: Box<Expr>,
+        negated: bool,
+        low: Box<Expr>,
+        high: Box<Expr>,
+    },
+    /// `expr [NOT] IN (list)`
+    InList {
+        expr: Box<Expr>,
+        negated: bool,
+        list: Vec<Expr>,
+    },
+    /// `expr [NOT] LIKE 'pattern'`, where `%` matches any sequence and `_` any character.
+    Like {
+        expr: Box<Expr>,
+        negated: bool,
+        pattern: String,
+    },
+    /// `expr IS [NOT] NULL`
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    /// `CASE [operand] WHEN .. THEN .. [ELSE ..] END`
+    Case {
+        operand: Option<Box<Expr>>,
+        when_then: Vec<(Expr, Expr)>,
+        else_result: Option<Box<Expr>>,
+    },
+    /// An aggregate function call. `arg` is None for `COUNT(*)`.
+    Aggregate {
+        func: AggregateFunction,
+        arg: Option<Box<Expr>>,
+    },
+}
+
+impl Expr {
+    pub fn column(name: &str) -> Expr {
+        Expr::Column {
+            table: None,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn binary(left: Expr, op: BinaryOperator, right: Expr) -> Expr {
+        Expr::BinaryOp {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        }
+    }
+
+    /// ANDs the predicates together; None if there are none.
+    pub fn conjunction(predicates: Vec<Expr>) -> Option<Expr> {
+        predicates
+            .into_iter()
+            .reduce(|acc, predicate| Expr::binary(acc, BinaryOperator::And, predicate))
+    }
+
+    /// Splits `a AND b AND c` into `[a, b, c]`.
+    pub fn split_conjunction(&self) -> Vec<Expr> {
+        match self {
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::And,
+                right,
+            } => {
+                let mut predicates = left.split_conjunction();
+                predicates.extend(right.split_conjunction());
+                predicates
+            }
+            _ => vec![self.clone()],
+        }
+    }
+
+    /// The direct sub-expressions.
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Column { .. } | Expr::Literal(_) => vec![],
+            Expr::BinaryOp { left, right, .. } => vec![left, right],
+            Expr::UnaryOp { expr, .. } | Expr::Like { expr, .. } | Expr::IsNull { expr, .. } => {
+                vec![expr]
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => vec![expr, low, high],
+            Expr::InList { expr, list, .. } => {
+                let mut children = vec![expr.as_ref()];
+                children.extend(list.iter());
+                children
+            }
+            Expr::Case {
+                operand,
+                when_then,
+                else_result,
+            } => {
+                let mut children: Vec<&Expr> = operand.iter().map(|x| x.as_ref()).collect();
+                for (when, then) in when_then {
+                    children.push(when);
+                    children.push(then);
+                }
+                children.extend(else_result.iter().map(|x| x.as_ref()));
+                children
+            }
+            Expr::Aggregate { arg, .. } => arg.iter().map(|x| x.as_ref()).collect(),
+        }
+    }
+
+    /// Calls `f` on this expression and all its sub-expressions, parents first.
+    pub fn visit(&self, f: &mut dyn FnMut(&Expr)) {
+        f(self);
+        for child in self.children() {
+            child.visit(f);
+        }
+    }
+
+    /// Rebuilds the expression top-down. Where `f` returns a replacement, the replacement is
+    /// used as is; otherwise the children are rewritten.
+    #[allow(clippy::type_complexity)]
+    pub fn rewrite(
+        &self,
+        f: &mut dyn FnMut(&Expr) -> Result<Option<Expr>, Box<dyn Error>>,
+    ) -> Result<Expr, Box<dyn Error>> {
+        if let Some(replacement) = f(self)? {
+            return Ok(replacement);
+        }
+        let mut rewrite_box =
+            |expr: &Expr| -> Result<Box<Expr>, Box<dyn Error>> { Ok(Box::new(expr.rewrite(f)?)) };
+        Ok(match self {
+            Expr::Column { .. } | Expr::Literal(_) => self.clone(),
+            Expr::BinaryOp { left, op, right } => Expr::BinaryOp {
+                left: rewrite_box(left)?,
+                op: *op,
+                right: rewrite_box(right)?,
+            },
+            Expr::UnaryOp { op, expr } => Expr::UnaryOp {
+                op: *op,
+                expr: rewrite_box(expr)?,
+            },
+            Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => Expr::Between {
+                expr: rewrite_box(expr)?,
+                negated: *negated,
+                low: rewrite_box(low)?,
+                high: rewrite_box(high)?,
+            },
+            Expr::InList {
+                expr,
+                negated,
+                list,
+            } => Expr::InList {
+                expr: rewrite_box(expr)?,
+                negated: *negated,
+                list: list
+                    .iter()
+                    .map(|x| Ok(*rewrite_box(x)?))
+                    .collect::<Result<_, Box<dyn Error>>>()?,
+            },
+            Expr::Like {
+                expr,
+                negated,
+                pattern,
+            } => Expr::Like {
+                expr: rewrite_box(expr)?,
+                negated: *negated,
+                pattern: pattern.clone(),
+            },
+            Expr::IsNull { expr, negated } => Expr::IsNull {
+                expr: rewrite_box(expr)?,
+                negated: *negated,
+            },
+            Expr::Case {
+                operand,
+                when_then,
+                else_result,
+            } => Expr::Case {
+                operand: operand.as_ref().map(|x| rewrite_box(x)).transpose()?,
+                when_then: when_then
+                    .iter()
+                    .map(|(when, then)| Ok((*rewrite_box(when)?, *rewrite_box(then)?)))
+                    .collect::<Result<_, Box<dyn Error>>>()?,
+                else_result: else_result.as_ref().map(|x| rewrite_box(x)).transpose()?,
+            },
+            Expr::Aggregate { func, arg } => Expr::Aggregate {
+                func: *func,
+                arg: arg.as_ref().map(|x| rewrite_box(x)).transpose()?,
+            },
+        })
+    }
+
+    /// Names of the columns referenced by the expression, in order of appearance.
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = vec![];
+        self.visit(&mut |expr| {
+            if let Expr::Column { name, .. } = expr {
+                if !columns.contains(name) {
+                    columns.push(name.clone());
+                }
+            }
+        });
+        columns
+    }
+
+    pub fn contains_aggregate(&self) -> bool {
+        let mut found = false;
+        self.visit(&mut |expr| found |= matches!(expr, Expr::Aggregate { .. }));
+        found
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::BinaryOp { op, .. } => op.precedence(),
+            Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                ..
+            } => 3,
+            Expr::Between { .. }
+            | Expr::InList { .. }
+            | Expr::Like { .. }
+            | Expr::IsNull { .. } => 4,
+            _ => 10,
+        }
+    }
+
+    /// Writes `expr`, parenthesized if it binds looser than `min_precedence`.
+    fn fmt_operand(f: &mut fmt::Formatter, expr: &Expr, min_precedence: u8) -> fmt::Result {
+        if expr.precedence() < min_precedence {
+            write!(f, "({})", expr)
+        } else {
+            write!(f, "{}", expr)
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Literal::Integer(value) => write!(f, "{}", value),
+            Literal::Float(value) => write!(f, "{:?}", value),
+            Literal::String(value) => write!(f, "'{}'", value.replace('\'', "''")),
+            Literal::Date(value) => write!(f, "date '{}'", value),
+            Literal::Boolean(value) => write!(f, "{}", value),
+            Literal::Null => write!(f, "null"),
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self {
+            BinaryOperator::Plus => "+",
+            BinaryOperator::Minus => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Modulo => "%",
+            BinaryOperator::Eq => "=",
+            BinaryOperator::NotEq => "<>",
+            BinaryOperator::Lt => "<",
+            BinaryOperator::LtEq => "<=",
+            BinaryOperator::Gt => ">",
+            BinaryOperator::GtEq => ">=",
+            BinaryOperator::And => "and",
+            BinaryOperator::Or => "or",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+impl fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AggregateFunction::Count => "count",
+            AggregateFunction::Sum => "sum",
+            AggregateFunction::Avg => "avg",
+            AggregateFunction::Min => "min",
+            AggregateFunction::Max => "max",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Formats the expression as SQL, e.g., `sum(l_extendedprice * (1 - l_discount))`. The text is
+/// also used to name computed columns that have no alias.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let not = |negated: &bool| if *negated { "not " } else { "" };
+        match self {
+            Expr::Column {
+                table: Some(table),
+                name,
+            } => write!(f, "{}.{}", table, name),
+            Expr::Column { table: None, name } => write!(f, "{}", name),
+            Expr::Literal(literal) => write!(f, "{}", literal),
+            Expr::BinaryOp { left, op, right } => {
+                // Left-associative: only the right operand needs parentheses on a tie.
+                Self::fmt_operand(f, left, op.precedence())?;
+                write!(f, " {} ", op)?;
+                Self::fmt_operand(f, right, op.precedence() + 1)
+            }
+            Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                expr,
+            } => {
+                write!(f, "not ")?;
+                Self::fmt_operand(f, expr, 3)
+            }
+            Expr::UnaryOp {
+                op: UnaryOperator::Minus,
+                expr,
+            } => {
+                write!(f, "-")?;
+                Self::fmt_operand(f, expr, 10)
+            }
+            Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => {
+                Self::fmt_operand(f, expr, 5)?;
+                write!(f, " {}between ", not(negated))?;
+                Self::fmt_operand(f, low, 5)?;
+                write!(f, " and ")?;
+                Self::fmt_operand(f, high, 5)
+            }
+            Expr::InList {
+                expr,
+                negated,
+                list,
+            } => {
+                Self::fmt_operand(f, expr, 5)?;
+                write!(f, " {}in (", not(negated))?;
+                for (i, item) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Like {
+                expr,
+                negated,
+                pattern,
+            } => {
+                Self::fmt_operand(f, expr, 5)?;
+                write!(
+                    f,
+                    " {}like {}",
+                    not(negated),
+                    Literal::String(pattern.clone())
+                )
+            }
+            Expr::IsNull { expr, negated } => {
+                Self::fmt_operand(f, expr, 5)?;
+                write!(f, " is {}null", not(negated))
+            }
+            Expr::Case {
+                operand,
+                when_then,
+                else_result,
+            } => {
+                write!(f, "case")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for (when, then) in when_then {
+                    write!(f, " when {} then {}", when, then)?;
+                }
+                if let Some(else_result) = else_result {
+                    write!(f, " else {}", else_result)?;
+                }
+                write!(f, " end")
+            }
+            Expr::Aggregate { func, arg: None } => write!(f, "{}(*)", func),
+            Expr::Aggregate {
+                func,
+                arg: Some(arg),
+            } => write!(f, "{}({})", func, arg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(value: i64) -> Expr {
+        Expr::Literal(Literal::Integer(value))
+    }
+
+    #[test]
+    fn can_display_expressions() {
+        // l_extendedprice * (1 - l_discount)
+        let disc_price = Expr::binary(
+            Expr::column("l_extendedprice"),
+            BinaryOperator::Multiply,
+            Expr::binary(lit(1), BinaryOperator::Minus, Expr::column("l_discount")),
+        );
+        assert_eq!(disc_price.to_string(), "l_extendedprice * (1 - l_discount)");
+        let sum = Expr::Aggregate {
+            func: AggregateFunction::Sum,
+            arg: Some(Box::new(disc_price)),
+        };
+        assert_eq!(sum.to_string(), "sum(l_extendedprice * (1 - l_discount))");
+
+        // (a - b) - c needs no parentheses, a - (b - c) does.
+        let a_b = Expr::binary(Expr::column("a"), BinaryOperator::Minus, Expr::column("b"));
+        let left = Expr::binary(a_b.clone(), BinaryOperator::Minus, Expr::column("c"));
+        let right = Expr::binary(Expr::column("c"), BinaryOperator::Minus, a_b);
+        assert_eq!(left.to_string(), "a - b - c");
+        assert_eq!(right.to_string(), "c - (a - b)");
+
+        let like = Expr::Like {
+            expr: Box::new(Expr::column("p_type")),
+            negated: true,
+            pattern: "PROMO%".into(),
+        };
+        assert_eq!(like.to_string(), "p_type not like 'PROMO%'");
+
+        let is_null = Expr::IsNull {
+            expr: Box::new(Expr::column("p_size")),
+            negated: true,
+        };
+        assert_eq!(is_null.to_string(), "p_size is not null");
+    }
+
+    #[test]
+    fn can_split_and_rewrite() {
+        let predicate = Expr::conjunction(vec![
+            Expr::binary(Expr::column("a"), BinaryOperator::Eq, Expr::column("b")),
+            Expr::binary(Expr::column("c"), BinaryOperator::Gt, lit(5)),
+            Expr::binary(Expr::column("a"), BinaryOperator::Lt, lit(3)),
+        ])
+        .unwrap();
+        assert_eq!(predicate.to_string(), "a = b and c > 5 and a < 3");
+        assert_eq!(predicate.split_conjunction().len(), 3);
+        assert_eq!(predicate.columns(), vec!["a", "b", "c"]);
+        assert!(Expr::conjunction(vec![]).is_none());
+
+        let renamed = predicate
+            .rewrite(&mut |expr| match expr {
+                Expr::Column { name, .. } if name == "a" => Ok(Some(Expr::column("x"))),
+                _ => Ok(None),
+            })
+            .unwrap();
+        assert_eq!(renamed.to_string(), "x = b and c > 5 and x < 3");
+        assert!(!renamed.contains_aggregate());
+    }
+}
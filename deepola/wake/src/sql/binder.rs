@@ -0,0 +1,699 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use crate::catalog::Catalog;
+use crate::data::Schema;
+
+use super::ast::*;
+use super::cost::CostModel;
+
+/// A query whose columns are resolved against the catalog, whose WHERE clause is split into
+/// join keys and filters, and whose aggregates are rewritten into sums. All column references
+/// are unqualified names of the columns flowing between nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BoundQuery {
+    /// Tables to scan, in join order.
+    pub scans: Vec<String>,
+    /// `joins[i]` joins `scans[i + 1]` (build side) to the result so far (probe side).
+    pub joins: Vec<JoinStep>,
+    /// Predicates evaluated after the joins.
+    pub filter: Option<Expr>,
+    pub aggregation: Option<Aggregation>,
+    /// Predicate over the aggregated rows.
+    pub having: Option<Expr>,
+    /// Output expressions and their names. With aggregation, these are evaluated on the
+    /// aggregated rows.
+    pub projection: Vec<(Expr, String)>,
+    /// Output columns to sort by, and whether the order is descending.
+    pub order_by: Vec<(String, bool)>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct JoinStep {
+    pub table: String,
+    pub left_on: Vec<String>,
+    pub right_on: Vec<String>,
+}
+
+/// Group keys and the per-group sums to accumulate. COUNT is a sum of ones over the non-null
+/// values and AVG is the ratio of two sums, so that partial results can be merged as new data
+/// arrives.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Aggregation {
+    pub keys: Vec<(Expr, String)>,
+    pub sums: Vec<(Expr, String)>,
+}
+
+struct BoundTable {
+    reference: String,
+    schema: Schema,
+}
+
+pub(crate) fn bind(query: &Query, catalog: &Catalog) -> Result<BoundQuery, Box<dyn Error>> {
+    let mut tables: Vec<BoundTable> = vec![];
+    let mut predicates = vec![];
+    if let Some(selection) = &query.selection {
+        predicates.push(selection.clone());
+    }
+    for item in &query.from {
+        let relations =
+            std::iter::once(&item.relation).chain(item.joins.iter().map(|j| &j.relation));
+        for relation in relations {
+            if tables.iter().any(|t| {
+                t.schema.table == relation.name || t.reference == relation.reference_name()
+            }) {
+                return Err(format!(
+                    "Table {} appears more than once; self-joins are not supported",
+                    relation.name
+                )
+                .into());
+            }
+            tables.push(BoundTable {
+                reference: relation.reference_name().to_string(),
+                schema: catalog.schema(&relation.name)?,
+            });
+        }
+        predicates.extend(item.joins.iter().map(|join| join.on.clone()));
+    }
+    let binder = Binder { tables };
+
+    // Split the predicates into equi-join keys between two tables and filters.
+    let mut edges: Vec<JoinEdge> = vec![];
+    let mut filters = vec![];
+    for predicate in predicates {
+        for conjunct in predicate.split_conjunction() {
+            if conjunct.contains_aggregate() {
+                return Err("Aggregates are not allowed in WHERE".into());
+            }
+            let conjunct = binder.resolve(&conjunct)?;
+            if let Expr::BinaryOp {
+                left,
+                op: BinaryOperator::Eq,
+                right,
+            } = &conjunct
+            {
+                if let (Expr::Column { name: l, .. }, Expr::Column { name: r, .. }) =
+                    (left.as_ref(), right.as_ref())
+                {
+                    let (l_table, r_table) = (binder.owner(l), binder.owner(r));
+                    if l_table != r_table {
+                        edges.push(((l_table, l.clone()), (r_table, r.clone())));
+                        continue;
+                    }
+                }
+            }
+            filters.push(conjunct);
+        }
+    }
+
+    // Estimated rows of each table after the filters that only use its columns.
+    let model = CostModel::new(catalog);
+    let rows: Vec<f64> = (0..binder.tables.len())
+        .map(|t| {
+            filters
+                .iter()
+                .filter(|f| {
+                    let columns = f.columns();
+                    !columns.is_empty() && columns.iter().all(|c| binder.owner(c) == t)
+                })
+                .fold(
+                    model.table_rows(&binder.tables[t].schema.table),
+                    |rows, f| rows * model.selectivity(f),
+                )
+        })
+        .collect();
+    let JoinOrder {
+        scans,
+        joins,
+        renamed,
+        filters: join_filters,
+    } = binder.order_joins(&edges, &rows, &model)?;
+    filters.extend(join_filters);
+    let substitute = |expr: &Expr| -> Result<Expr, Box<dyn Error>> {
+        expr.rewrite(&mut |e| match e {
+            Expr::Column { name, .. } => Ok(renamed.get(name).map(|n| Expr::column(n))),
+            _ => Ok(None),
+        })
+    };
+
+    // SELECT items, with the output name of each.
+    let mut select = vec![];
+    for item in &query.projection {
+        match item {
+            SelectItem::Wildcard => {
+                for table in &binder.tables {
+                    for column in &table.schema.columns {
+                        let expr = binder.resolve(&Expr::column(&column.name))?;
+                        select.push((expr, column.name.clone()));
+                    }
+                }
+            }
+            SelectItem::Expr { expr, alias } => {
+                let name = match (alias, expr) {
+                    (Some(alias), _) => alias.clone(),
+                    (None, Expr::Column { name, .. }) => name.clone(),
+                    (None, expr) => expr.to_string(),
+                };
+                select.push((binder.resolve(expr)?, name));
+            }
+        }
+    }
+    let mut names = HashSet::new();
+    for (_, name) in &select {
+        if !names.insert(name) {
+            return Err(format!("Duplicate output column: {}", name).into());
+        }
+    }
+
+    let having = query
+        .having
+        .as_ref()
+        .map(|h| binder.resolve(h))
+        .transpose()?;
+    let group_by = query
+        .group_by
+        .iter()
+        .map(|expr| binder.resolve(expr))
+        .collect::<Result<Vec<Expr>, _>>()?;
+    let aggregated = !group_by.is_empty()
+        || having.is_some()
+        || select.iter().any(|(expr, _)| expr.contains_aggregate());
+
+    let mut projection = vec![];
+    let mut aggregation = None;
+    let mut bound_having = None;
+    if aggregated {
+        let keys = group_by
+            .iter()
+            .map(|expr| {
+                let expr = substitute(expr)?;
+                let name = match &expr {
+                    Expr::Column { name, .. } => name.clone(),
+                    expr => expr.to_string(),
+                };
+                Ok((expr, name))
+            })
+            .collect::<Result<Vec<(Expr, String)>, Box<dyn Error>>>()?;
+        let mut sums: Vec<(Expr, String)> = vec![];
+        for (expr, name) in &select {
+            let expr = rewrite_aggregates(&substitute(expr)?, &keys, &mut sums)?;
+            projection.push((expr, name.clone()));
+        }
+        if let Some(having) = &having {
+            bound_having = Some(rewrite_aggregates(&substitute(having)?, &keys, &mut sums)?);
+        }
+        aggregation = Some(Aggregation { keys, sums });
+    } else {
+        for (expr, name) in &select {
+            projection.push((substitute(expr)?, name.clone()));
+        }
+    }
+
+    // ORDER BY an output name, a 1-based position or a SELECT expression.
+    let mut order_by = vec![];
+    for item in &query.order_by {
+        let name = match &item.expr {
+            Expr::Column { table: None, name } if select.iter().any(|(_, n)| n == name) => {
+                name.clone()
+            }
+            Expr::Literal(Literal::Integer(position))
+                if *position >= 1 && (*position as usize) <= select.len() =>
+            {
+                select[*position as usize - 1].1.clone()
+            }
+            expr => {
+                let resolved = binder.resolve(expr)?;
+                match select.iter().find(|(e, _)| *e == resolved) {
+                    Some((_, name)) => name.clone(),
+                    None => {
+                        return Err(format!("ORDER BY {} must be an output column", expr).into())
+                    }
+                }
+            }
+        };
+        order_by.push((name, item.descending));
+    }
+
+    let filter = Expr::conjunction(
+        filters
+            .iter()
+            .map(substitute)
+            .collect::<Result<Vec<Expr>, _>>()?,
+    );
+    Ok(BoundQuery {
+        scans,
+        joins,
+        filter,
+        aggregation,
+        having: bound_having,
+        projection,
+        order_by,
+        limit: query.limit,
+    })
+}
+
+/// Replaces the aggregates in `expr` with references to the accumulated sums (added to `sums`
+/// as needed), and the group-by expressions with references to the key columns.
+fn rewrite_aggregates(
+    expr: &Expr,
+    keys: &[(Expr, String)],
+    sums: &mut Vec<(Expr, String)>,
+) -> Result<Expr, Box<dyn Error>> {
+    let mut sum_of = |expr: Expr| -> Expr {
+        let name = match sums.iter().find(|(e, _)| *e == expr) {
+            Some((_, name)) => name.clone(),
+            None => {
+                let name = format!("agg_{}", sums.len());
+                sums.push((expr, name.clone()));
+                name
+            }
+        };
+        Expr::column(&name)
+    };
+    let one = || Expr::Literal(Literal::Integer(1));
+    // COUNT(x) and the denominator of AVG(x) only count the rows where x is not null.
+    let count_of = |arg: &Expr| Expr::Case {
+        operand: None,
+        when_then: vec![(
+            Expr::IsNull {
+                expr: Box::new(arg.clone()),
+                negated: true,
+            },
+            one(),
+        )],
+        else_result: Some(Box::new(Expr::Literal(Literal::Integer(0)))),
+    };
+    expr.rewrite(&mut |e| {
+        if let Some((_, name)) = keys.iter().find(|(key, _)| key == e) {
+            return Ok(Some(Expr::column(name)));
+        }
+        match e {
+            Expr::Aggregate {
+                func: AggregateFunction::Count,
+                arg: None,
+            } => Ok(Some(sum_of(one()))),
+            Expr::Aggregate {
+                func: AggregateFunction::Count,
+                arg: Some(arg),
+            } => Ok(Some(sum_of(count_of(arg)))),
+            Expr::Aggregate {
+                func: AggregateFunction::Sum,
+                arg: Some(arg),
+            } => Ok(Some(sum_of(*arg.clone()))),
+            Expr::Aggregate {
+                func: AggregateFunction::Avg,
+                arg: Some(arg),
+            } => {
+                let sum = sum_of(*arg.clone());
+                Ok(Some(Expr::binary(
+                    sum,
+                    BinaryOperator::Divide,
+                    sum_of(count_of(arg)),
+                )))
+            }
+            Expr::Aggregate { func, .. } => Err(format!(
+                "{} is not supported; only COUNT, SUM and AVG can be accumulated",
+                func
+            )
+            .into()),
+            Expr::Column { name, .. } => Err(format!(
+                "Column {} must appear in GROUP BY or be used in an aggregate",
+                name
+            )
+            .into()),
+            _ => Ok(None),
+        }
+    })
+}
+
+/// An equi-join predicate between two tables: (table index, column) on each side.
+type JoinEdge = ((usize, String), (usize, String));
+
+struct JoinOrder {
+    scans: Vec<String>,
+    joins: Vec<JoinStep>,
+    /// Build-side key columns dropped by the joins, and the probe-side columns replacing them.
+    renamed: HashMap<String, String>,
+    /// Join predicates that could not be used as keys.
+    filters: Vec<Expr>,
+}
+
+struct Binder {
+    tables: Vec<BoundTable>,
+}
+
+impl Binder {
+    /// Index of the table that defines the (resolved) column.
+    fn owner(&self, column: &str) -> usize {
+        self.tables
+            .iter()
+            .position(|t| t.schema.columns.iter().any(|c| c.name == column))
+            .unwrap()
+    }
+
+    /// Resolves column references to unqualified column names, checking that each refers to
+    /// exactly one table of the query.
+    fn resolve(&self, expr: &Expr) -> Result<Expr, Box<dyn Error>> {
+        expr.rewrite(&mut |e| {
+            let (table, name) = match e {
+                Expr::Column { table, name } => (table, name),
+                _ => return Ok(None),
+            };
+            let owners: Vec<&BoundTable> = self
+                .tables
+                .iter()
+                .filter(|t| t.schema.columns.iter().any(|c| &c.name == name))
+                .collect();
+            if let Some(table) = table {
+                if !self.tables.iter().any(|t| &t.reference == table) {
+                    return Err(format!("Unknown table: {}", table).into());
+                }
+                if !owners.iter().any(|t| &t.reference == table) {
+                    return Err(format!("Unknown column: {}.{}", table, name).into());
+                }
+            }
+            match owners.len() {
+                0 => Err(format!("Unknown column: {}", name).into()),
+                1 => Ok(Some(Expr::column(name))),
+                _ => Err(format!(
+                    "Column {} is defined in more than one table; joining tables with \
+                     overlapping column names is not supported",
+                    name
+                )
+                .into()),
+            }
+        })
+    }
+
+    /// Orders the joins left-deep. The table with the most (estimated) rows is the probe side
+    /// of all the joins, and the other tables are joined, as build sides, in the order that
+    /// keeps the estimated intermediate results smallest. Ties are broken by the smaller table
+    /// and then by FROM order. The join drops the key columns of the build side, so the
+    /// returned map renames them to the equal probe-side columns.
+    fn order_joins(
+        &self,
+        edges: &[JoinEdge],
+        rows: &[f64],
+        model: &CostModel,
+    ) -> Result<JoinOrder, Box<dyn Error>> {
+        let start =
+            (0..self.tables.len()).fold(0, |best, t| if rows[t] > rows[best] { t } else { best });
+        let mut joined = vec![start];
+        let mut joined_rows = rows[start];
+        let mut joins = vec![];
+        let mut renamed: HashMap<String, String> = HashMap::new();
+        let mut filters = vec![];
+        while joined.len() < self.tables.len() {
+            let mut best: Option<(usize, f64)> = None;
+            for t in (0..self.tables.len()).filter(|t| !joined.contains(t)) {
+                let mut left_keys = vec![];
+                let mut right_keys = vec![];
+                for ((l, l_col), (r, r_col)) in edges {
+                    let (probe, build) = if *r == t && joined.contains(l) {
+                        ((l, l_col), r_col)
+                    } else if *l == t && joined.contains(r) {
+                        ((r, r_col), l_col)
+                    } else {
+                        continue;
+                    };
+                    left_keys.push((self.tables[*probe.0].schema.table.clone(), probe.1.clone()));
+                    right_keys.push((self.tables[t].schema.table.clone(), build.clone()));
+                }
+                if right_keys.is_empty() {
+                    continue;
+                }
+                let output = model.join_rows(joined_rows, rows[t], &left_keys, &right_keys);
+                let better = match best {
+                    None => true,
+                    Some((b, b_output)) => {
+                        output < b_output || (output == b_output && rows[t] < rows[b])
+                    }
+                };
+                if better {
+                    best = Some((t, output));
+                }
+            }
+            let next = match best {
+                Some((next, output)) => {
+                    joined_rows = output;
+                    next
+                }
+                None => {
+                    let missing: Vec<&str> = (0..self.tables.len())
+                        .filter(|t| !joined.contains(t))
+                        .map(|t| self.tables[t].schema.table.as_str())
+                        .collect();
+                    return Err(format!(
+                        "No join condition for {}; cross joins are not supported",
+                        missing.join(", ")
+                    )
+                    .into());
+                }
+            };
+            let mut left_on: Vec<String> = vec![];
+            let mut right_on: Vec<String> = vec![];
+            for ((l, l_col), (r, r_col)) in edges {
+                let (probe, build) = if *r == next && joined.contains(l) {
+                    (l_col, r_col)
+                } else if *l == next && joined.contains(r) {
+                    (r_col, l_col)
+                } else {
+                    continue;
+                };
+                let probe = renamed.get(probe).unwrap_or(probe).clone();
+                match right_on.iter().position(|c| c == build) {
+                    // The build column is already a key, so the probe columns must be equal.
+                    Some(i) => filters.push(Expr::binary(
+                        Expr::column(&left_on[i]),
+                        BinaryOperator::Eq,
+                        Expr::column(&probe),
+                    )),
+                    None => {
+                        left_on.push(probe);
+                        right_on.push(build.clone());
+                    }
+                }
+            }
+            for (probe, build) in left_on.iter().zip(right_on.iter()) {
+                renamed.insert(build.clone(), probe.clone());
+            }
+            joins.push(JoinStep {
+                table: self.tables[next].schema.table.clone(),
+                left_on,
+                right_on,
+            });
+            joined.push(next);
+        }
+        Ok(JoinOrder {
+            scans: joined
+                .iter()
+                .map(|t| self.tables[*t].schema.table.clone())
+                .collect(),
+            joins,
+            renamed,
+            filters,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::result::Result;
+
+    use polars::prelude::*;
+
+    use super::*;
+    use crate::sql::parse_query;
+
+    fn tpch_catalog() -> Catalog {
+        Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap()
+    }
+
+    fn bind_sql(sql: &str) -> Result<BoundQuery, Box<dyn Error>> {
+        bind(&parse_query(sql)?, &tpch_catalog())
+    }
+
+    #[test]
+    fn can_bind_aggregation() {
+        let sql = std::fs::read_to_string("resources/tpc-h/queries/1.sql").unwrap();
+        let bound = bind_sql(&sql).unwrap();
+        assert_eq!(bound.scans, vec!["lineitem"]);
+        assert!(bound.joins.is_empty());
+        assert_eq!(
+            bound.filter.unwrap().to_string(),
+            "l_shipdate <= date '1998-09-02'"
+        );
+
+        let aggregation = bound.aggregation.unwrap();
+        let keys: Vec<&str> = aggregation.keys.iter().map(|(_, n)| n.as_str()).collect();
+        assert_eq!(keys, vec!["l_returnflag", "l_linestatus"]);
+        let sums: Vec<String> = aggregation
+            .sums
+            .iter()
+            .map(|(e, _)| e.to_string())
+            .collect();
+        assert_eq!(
+            sums,
+            vec![
+                "l_quantity",
+                "l_extendedprice",
+                "l_extendedprice * (1 - l_discount)",
+                "l_extendedprice * (1 - l_discount) * (1 + l_tax)",
+                "case when l_quantity is not null then 1 else 0 end",
+                "case when l_extendedprice is not null then 1 else 0 end",
+                "l_discount",
+                "case when l_discount is not null then 1 else 0 end",
+                "1",
+            ]
+        );
+
+        let projection: Vec<String> = bound
+            .projection
+            .iter()
+            .map(|(e, n)| format!("{} AS {}", e, n))
+            .collect();
+        assert_eq!(projection[0], "l_returnflag AS l_returnflag");
+        assert_eq!(projection[2], "agg_0 AS sum_qty");
+        assert_eq!(projection[6], "agg_0 / agg_4 AS avg_qty");
+        assert_eq!(projection[9], "agg_8 AS count_order");
+        assert_eq!(
+            bound.order_by,
+            vec![
+                ("l_returnflag".to_string(), false),
+                ("l_linestatus".to_string(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_only_non_null_values() {
+        let bound = bind_sql(
+            "SELECT count(*) AS parts, count(p_size) AS sizes, avg(p_size) AS avg_size FROM part",
+        )
+        .unwrap();
+        let input = df!("p_size" => &[Some(5i64), None, Some(20)]).unwrap();
+        let sums: Vec<Series> = bound
+            .aggregation
+            .unwrap()
+            .sums
+            .iter()
+            .map(|(expr, name)| {
+                let sum = expr.evaluate(&input).unwrap().sum::<f64>().unwrap();
+                Series::new(name, &[sum])
+            })
+            .collect();
+        let sums = DataFrame::new(sums).unwrap();
+        let results: Vec<f64> = bound
+            .projection
+            .iter()
+            .map(|(expr, _)| expr.evaluate(&sums).unwrap().f64().unwrap().get(0).unwrap())
+            .collect();
+        assert_eq!(results, vec![3.0, 2.0, 12.5]);
+    }
+
+    #[test]
+    fn can_bind_joins() {
+        let sql = std::fs::read_to_string("resources/cs511/queries/b.sql").unwrap();
+        let bound = bind_sql(&sql).unwrap();
+        assert_eq!(bound.scans, vec!["orders", "supplier", "nation", "region"]);
+        assert_eq!(
+            bound.joins[0],
+            JoinStep {
+                table: "supplier".into(),
+                left_on: vec!["o_custkey".into()],
+                right_on: vec!["s_suppkey".into()],
+            }
+        );
+        assert_eq!(bound.joins[2].left_on, vec!["n_regionkey".to_string()]);
+        assert_eq!(bound.filter.unwrap().to_string(), "r_name = 'EUROPE'");
+        assert_eq!(
+            bound.order_by,
+            vec![("total_order_value".to_string(), true)]
+        );
+    }
+
+    #[test]
+    fn orders_joins_by_estimated_rows() {
+        // lineitem is the largest table, and the filtered orders are joined before customer,
+        // which only joins with orders.
+        let sql = std::fs::read_to_string("resources/cs511/queries/c.sql").unwrap();
+        let bound = bind_sql(&sql).unwrap();
+        assert_eq!(bound.scans, vec!["lineitem", "orders", "customer"]);
+        assert_eq!(bound.joins[1].left_on, vec!["o_custkey".to_string()]);
+    }
+
+    #[test]
+    fn renames_dropped_join_keys() {
+        // nation is joined on c_nationkey = n_nationkey, which drops n_nationkey.
+        let bound = bind_sql(
+            "SELECT n.n_nationkey, c_name FROM supplier s JOIN nation n \
+             ON n.n_nationkey = s.s_nationkey, customer WHERE c_nationkey = n_nationkey",
+        )
+        .unwrap();
+        assert_eq!(bound.scans, vec!["customer", "nation", "supplier"]);
+        assert_eq!(bound.joins[1].left_on, vec!["c_nationkey".to_string()]);
+        assert_eq!(bound.joins[1].right_on, vec!["s_nationkey".to_string()]);
+        assert_eq!(
+            bound.projection[0],
+            (Expr::column("c_nationkey"), "n_nationkey".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_redundant_join_predicates() {
+        let bound = bind_sql(
+            "SELECT c_name FROM customer, supplier, nation \
+             WHERE c_nationkey = s_nationkey AND c_nationkey = n_nationkey \
+             AND n_nationkey = s_nationkey",
+        )
+        .unwrap();
+        assert_eq!(bound.scans, vec!["customer", "nation", "supplier"]);
+        assert_eq!(bound.joins[1].left_on, vec!["c_nationkey".to_string()]);
+        assert_eq!(bound.joins[1].right_on, vec!["s_nationkey".to_string()]);
+        assert_eq!(
+            bound.filter.unwrap().to_string(),
+            "c_nationkey = c_nationkey"
+        );
+    }
+
+    #[test]
+    fn can_order_by_position_and_expression() {
+        let bound = bind_sql(
+            "SELECT n_regionkey, count(*) FROM nation GROUP BY n_regionkey \
+             HAVING count(*) > 4 ORDER BY 2 DESC, n_regionkey",
+        )
+        .unwrap();
+        assert_eq!(
+            bound.order_by,
+            vec![
+                ("count(*)".to_string(), true),
+                ("n_regionkey".to_string(), false)
+            ]
+        );
+        assert_eq!(bound.having.unwrap().to_string(), "agg_0 > 4");
+
+        let bound = bind_sql("SELECT * FROM region ORDER BY r_name").unwrap();
+        assert_eq!(bound.projection.len(), 3);
+        assert!(bound.aggregation.is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        let invalid = [
+            "SELECT x FROM nation",
+            "SELECT n_name FROM nations",
+            "SELECT r.n_name FROM nation n",
+            "SELECT n_name, count(*) FROM nation",
+            "SELECT max(n_nationkey) FROM nation",
+            "SELECT n_name FROM nation, region",
+            "SELECT n_name FROM nation n1, nation n2 WHERE n1.n_nationkey = n2.n_nationkey",
+            "SELECT n_name, n_name FROM nation",
+            "SELECT n_name FROM nation WHERE count(*) > 1",
+            "SELECT n_name FROM nation ORDER BY n_comment",
+        ];
+        for sql in invalid {
+            assert!(bind_sql(sql).is_err(), "{}", sql);
+        }
+    }
+}
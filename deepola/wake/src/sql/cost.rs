@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use crate::catalog::Catalog;
+
+use super::ast::{BinaryOperator, Expr, Literal, UnaryOperator};
+use super::logical_plan::LogicalPlan;
+
+/// Fraction of rows kept by predicates whose selectivity cannot be estimated otherwise.
+const DEFAULT_SELECTIVITY: f64 = 0.5;
+const EQ_SELECTIVITY: f64 = 0.1;
+const RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+const BETWEEN_SELECTIVITY: f64 = 0.25;
+const LIKE_SELECTIVITY: f64 = 0.25;
+const IS_NULL_SELECTIVITY: f64 = 0.1;
+
+/// Estimates the number of rows produced by plans, from the row counts of the tables in a
+/// [Catalog] and fixed selectivities of the predicates.
+///
+/// A join whose keys on one side include the primary key of a table is assumed to be a
+/// foreign key join: each row of the other side matches at most one row of that table. Other
+/// joins are assumed to produce as many rows as their larger input.
+pub struct CostModel<'a> {
+    catalog: &'a Catalog,
+}
+
+impl<'a> CostModel<'a> {
+    pub fn new(catalog: &'a Catalog) -> Self {
+        CostModel { catalog }
+    }
+
+    /// Number of rows of a table, or 1 if the catalog does not know it.
+    pub fn table_rows(&self, table: &str) -> f64 {
+        self.catalog.total_records(table).max(1) as f64
+    }
+
+    /// Estimated fraction of rows for which the predicate is true.
+    pub fn selectivity(&self, predicate: &Expr) -> f64 {
+        match predicate {
+            Expr::BinaryOp { left, op, right } => match op {
+                BinaryOperator::And => self.selectivity(left) * self.selectivity(right),
+                BinaryOperator::Or => {
+                    let (l, r) = (self.selectivity(left), self.selectivity(right));
+                    l + r - l * r
+                }
+                BinaryOperator::Eq => EQ_SELECTIVITY,
+                BinaryOperator::NotEq => 1.0 - EQ_SELECTIVITY,
+                BinaryOperator::Lt
+                | BinaryOperator::LtEq
+                | BinaryOperator::Gt
+                | BinaryOperator::GtEq => RANGE_SELECTIVITY,
+                _ => DEFAULT_SELECTIVITY,
+            },
+            Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                expr,
+            } => 1.0 - self.selectivity(expr),
+            Expr::Between { negated, .. } => negate_if(*negated, BETWEEN_SELECTIVITY),
+            Expr::InList { negated, list, .. } => {
+                negate_if(*negated, (EQ_SELECTIVITY * list.len() as f64).min(1.0))
+            }
+            Expr::Like { negated, .. } => negate_if(*negated, LIKE_SELECTIVITY),
+            Expr::IsNull { negated, .. } => negate_if(*negated, IS_NULL_SELECTIVITY),
+            Expr::Literal(Literal::Boolean(value)) => {
+                if *value {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            _ => DEFAULT_SELECTIVITY,
+        }
+    }
+
+    /// Estimated rows of an equi-join of inputs with `left_rows` and `right_rows` rows. The
+    /// keys of each side are given as (table, column) pairs.
+    pub fn join_rows(
+        &self,
+        left_rows: f64,
+        right_rows: f64,
+        left_keys: &[(String, String)],
+        right_keys: &[(String, String)],
+    ) -> f64 {
+        // With a foreign key join, the rows of the other side are kept in the proportion of
+        // rows of the keyed table that are kept.
+        if let Some(table) = self.unique_key_table(right_keys) {
+            left_rows * (right_rows / self.table_rows(&table)).min(1.0)
+        } else if let Some(table) = self.unique_key_table(left_keys) {
+            right_rows * (left_rows / self.table_rows(&table)).min(1.0)
+        } else {
+            left_rows.max(right_rows)
+        }
+    }
+
+    /// A table whose primary key columns are all among `keys`.
+    fn unique_key_table(&self, keys: &[(String, String)]) -> Option<String> {
+        keys.iter().map(|(table, _)| table).find_map(|table| {
+            let schema = self.catalog.schema(table).ok()?;
+            let is_key = !schema.keys().is_empty()
+                && schema.keys().iter().all(|i| {
+                    let name = &schema.get_column_from_index(*i).name;
+                    keys.iter().any(|(t, c)| t == table && c == name)
+                });
+            if is_key {
+                Some(table.clone())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Estimated number of rows produced by `plan`.
+    pub fn estimate_rows(&self, plan: &LogicalPlan) -> f64 {
+        match plan {
+            LogicalPlan::Scan { table, .. } => self.table_rows(table),
+            LogicalPlan::Filter { input, predicate } => {
+                self.estimate_rows(input) * self.selectivity(predicate)
+            }
+            LogicalPlan::Join {
+                left,
+                right,
+                left_on,
+                right_on,
+            } => {
+                let tables = self.column_tables(plan);
+                let keys = |columns: &[String]| -> Vec<(String, String)> {
+                    columns
+                        .iter()
+                        .filter_map(|c| Some((tables.get(c)?.clone(), c.clone())))
+                        .collect()
+                };
+                self.join_rows(
+                    self.estimate_rows(left),
+                    self.estimate_rows(right),
+                    &keys(left_on),
+                    &keys(right_on),
+                )
+            }
+            // Without distinct counts, assume that a tenth of the rows are distinct groups.
+            LogicalPlan::Aggregate { input, keys, .. } => {
+                if keys.is_empty() {
+                    1.0
+                } else {
+                    (self.estimate_rows(input) * EQ_SELECTIVITY).max(1.0)
+                }
+            }
+            LogicalPlan::Limit { input, limit } => self.estimate_rows(input).min(*limit as f64),
+            LogicalPlan::Project { input, .. } | LogicalPlan::Sort { input, .. } => {
+                self.estimate_rows(input)
+            }
+        }
+    }
+
+    /// The table defining each column of the tables scanned by `plan`.
+    fn column_tables(&self, plan: &LogicalPlan) -> HashMap<String, String> {
+        let mut tables = HashMap::new();
+        if let LogicalPlan::Scan { table, .. } = plan {
+            if let Ok(schema) = self.catalog.schema(table) {
+                for column in schema.columns {
+                    tables.insert(column.name, table.clone());
+                }
+            }
+        }
+        for input in plan.inputs() {
+            tables.extend(self.column_tables(input));
+        }
+        tables
+    }
+
+    /// The plan as an indented tree of operators, with the estimated rows of each.
+    pub fn explain(&self, plan: &LogicalPlan) -> String {
+        plan.format_tree(&mut |node| format!("(rows={:.0})", self.estimate_rows(node)))
+    }
+}
+
+fn negate_if(negated: bool, selectivity: f64) -> f64 {
+    if negated {
+        1.0 - selectivity
+    } else {
+        selectivity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::parse_expr;
+
+    #[test]
+    fn can_estimate_selectivity() {
+        let catalog = Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        let model = CostModel::new(&catalog);
+        let cases = [
+            ("r_name = 'EUROPE'", 0.1),
+            ("a < 1 and b between 1 and 2", 0.25 / 3.0),
+            ("a = 1 or b = 2", 0.19),
+            ("a not in (1, 2, 3)", 0.7),
+            ("not a like 'x%'", 0.75),
+        ];
+        for (sql, expected) in cases {
+            let selectivity = model.selectivity(&parse_expr(sql).unwrap());
+            assert!((selectivity - expected).abs() < 1e-9, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn can_estimate_join_rows() {
+        let catalog = Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        let model = CostModel::new(&catalog);
+        let key = |table: &str, column: &str| vec![(table.to_string(), column.to_string())];
+        // Each lineitem matches one order; a third of the orders are kept.
+        let rows = model.join_rows(
+            6000000.0,
+            500000.0,
+            &key("lineitem", "l_orderkey"),
+            &key("orders", "o_orderkey"),
+        );
+        assert!((rows - 2000000.0).abs() < 1e-3);
+        // The orders key of lineitem is only a part of its primary key.
+        assert_eq!(
+            model.join_rows(
+                6000000.0,
+                1500000.0,
+                &key("lineitem", "l_partkey"),
+                &key("orders", "o_custkey")
+            ),
+            6000000.0
+        );
+    }
+}
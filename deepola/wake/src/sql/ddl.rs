@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use crate::data::{Column, DataType, Schema};
+
+use super::lexer::{Token, TokenStream};
+
+/// Parses the `CREATE TABLE` statements in `ddl` into schemas, keyed by table name. Other
+/// statements (e.g., `DROP TABLE`) are skipped. Unquoted names are lowercased.
+pub fn parse_create_tables(ddl: &str) -> Result<HashMap<String, Schema>, Box<dyn Error>> {
+    let mut tokens = TokenStream::from_sql(ddl)?;
+    let mut schemas = HashMap::new();
+    while !tokens.is_empty() {
+        if tokens.consume_keyword("create") {
+            tokens.consume_keyword("temporary");
+            tokens.consume_keyword("temp");
+            tokens.consume_keyword("unlogged");
+            if tokens.consume_keyword("table") {
+                let schema = parse_create_table(&mut tokens)?;
+                schemas.insert(schema.table.clone(), schema);
+                continue;
+            }
+        }
+        tokens.skip_statement();
+    }
+    Ok(schemas)
+}
+
+/// Parses the rest of a `CREATE TABLE` statement, after the `TABLE` keyword.
+fn parse_create_table(tokens: &mut TokenStream) -> Result<Schema, Box<dyn Error>> {
+    tokens.consume_keywords(&["if", "not", "exists"]);
+    let mut table = tokens.expect_identifier()?;
+    // Only the table name of a qualified name (e.g., public.part) is kept.
+    while tokens.consume(&Token::Period) {
+        table = tokens.expect_identifier()?;
+    }
+
+    tokens.expect(&Token::LParen)?;
+    let mut columns: Vec<(String, DataType)> = vec![];
+    let mut keys = HashSet::new();
+    loop {
+        if tokens.consume_keyword("constraint") {
+            tokens.expect_identifier()?;
+        }
+        if tokens.consume_keywords(&["primary", "key"]) {
+            keys.extend(parse_identifier_list(tokens)?);
+            skip_element(tokens)?;
+        } else if ["unique", "foreign", "check", "exclude"]
+            .iter()
+            .any(|keyword| tokens.is_keyword(keyword))
+        {
+            skip_element(tokens)?;
+        } else {
+            let name = tokens.expect_identifier()?;
+            let dtype = parse_data_type(tokens)?;
+            if parse_column_constraints(tokens)? {
+                keys.insert(name.clone());
+            }
+            columns.push((name, dtype));
+        }
+        if tokens.consume(&Token::RParen) {
+            break;
+        }
+        tokens.expect(&Token::Comma)?;
+    }
+    // Table options, if any.
+    tokens.skip_statement();
+
+    for key in &keys {
+        if !columns.iter().any(|(name, _)| name == key) {
+            return Err(format!("Primary key column {} not defined in {}", key, table).into());
+        }
+    }
+    let columns = columns
+        .into_iter()
+        .map(|(name, dtype)| {
+            if keys.contains(&name) {
+                Column::from_key_field(name, dtype)
+            } else {
+                Column::from_field(name, dtype)
+            }
+        })
+        .collect();
+    Ok(Schema::new(table, columns))
+}
+
+/// Parses `(a, b, ...)`.
+fn parse_identifier_list(tokens: &mut TokenStream) -> Result<Vec<String>, Box<dyn Error>> {
+    tokens.expect(&Token::LParen)?;
+    let mut identifiers = vec![tokens.expect_identifier()?];
+    while tokens.consume(&Token::Comma) {
+        identifiers.push(tokens.expect_identifier()?);
+    }
+    tokens.expect(&Token::RParen)?;
+    Ok(identifiers)
+}
+
+/// Maps a SQL type to the DataType used to store it. Dates and times are stored as Text.
+fn parse_data_type(tokens: &mut TokenStream) -> Result<DataType, Box<dyn Error>> {
+    let name = tokens.expect_identifier()?;
+    let dtype = match name.to_lowercase().as_str() {
+        "boolean" | "bool" => DataType::Boolean,
+        "smallint" | "integer" | "int" | "bigint" | "tinyint" | "int2" | "int4" | "int8"
+        | "smallserial" | "serial" | "bigserial" | "serial4" | "serial8" => DataType::Integer,
+        "decimal" | "numeric" | "real" | "float" | "float4" | "float8" | "double" => {
+            DataType::Float
+        }
+        "char" | "character" | "varchar" | "nchar" | "nvarchar" | "text" | "string" | "date"
+        | "time" | "timestamp" | "timestamptz" | "interval" => DataType::Text,
+        _ => return Err(format!("Unsupported SQL type: {}", name).into()),
+    };
+    // Multi-word types, e.g., DOUBLE PRECISION, CHARACTER VARYING(25), TIMESTAMP WITH TIME ZONE.
+    tokens.consume_keyword("precision");
+    tokens.consume_keyword("varying");
+    if tokens.consume(&Token::LParen) {
+        tokens.skip_until_closing_paren()?;
+    }
+    if !tokens.consume_keywords(&["with", "time", "zone"]) {
+        tokens.consume_keywords(&["without", "time", "zone"]);
+    }
+    Ok(dtype)
+}
+
+/// Skips the constraints of a column definition (NOT NULL, DEFAULT, REFERENCES, ...).
+/// Returns whether the column was declared as the PRIMARY KEY.
+fn parse_column_constraints(tokens: &mut TokenStream) -> Result<bool, Box<dyn Error>> {
+    let mut primary_key = false;
+    loop {
+        if tokens.consume_keywords(&["primary", "key"]) {
+            primary_key = true;
+            continue;
+        }
+        match tokens.peek() {
+            None | Some(Token::Comma) | Some(Token::RParen) => return Ok(primary_key),
+            Some(Token::LParen) => {
+                tokens.next();
+                tokens.skip_until_closing_paren()?;
+            }
+            Some(_) => {
+                tokens.next();
+            }
+        }
+    }
+}
+
+/// Skips a table element up to (but excluding) the next top-level comma or closing parenthesis.
+fn skip_element(tokens: &mut TokenStream) -> Result<(), Box<dyn Error>> {
+    loop {
+        match tokens.peek() {
+            None => return Err(tokens.unexpected(")")),
+            Some(Token::Comma) | Some(Token::RParen) => return Ok(()),
+            Some(Token::LParen) => {
+                tokens.next();
+                tokens.skip_until_closing_paren()?;
+            }
+            Some(_) => {
+                tokens.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_create_table() {
+        let ddl = "
+            DROP TABLE IF EXISTS t;
+            CREATE TABLE IF NOT EXISTS public.T (
+                id     BIGSERIAL PRIMARY KEY,
+                name   CHARACTER VARYING(25) NOT NULL DEFAULT 'x',
+                price  DOUBLE PRECISION CHECK (price > 0),
+                active BOOLEAN,
+                \"Created\" TIMESTAMP WITHOUT TIME ZONE,
+                other  INT REFERENCES other (id)
+            ) WITH (fillfactor = 70);
+            CREATE INDEX t_name ON t (name);";
+        let schemas = parse_create_tables(ddl).unwrap();
+        assert_eq!(schemas.len(), 1);
+        let schema = &schemas["t"];
+        assert_eq!(schema.table, "t");
+        let dtypes: Vec<DataType> = schema.columns.iter().map(|c| c.dtype.clone()).collect();
+        assert_eq!(
+            dtypes,
+            vec![
+                DataType::Integer,
+                DataType::Text,
+                DataType::Float,
+                DataType::Boolean,
+                DataType::Text,
+                DataType::Integer
+            ]
+        );
+        assert_eq!(schema.index("Created"), 4);
+        assert_eq!(schema.keys(), &vec![0usize]);
+    }
+
+    #[test]
+    fn can_parse_table_constraints() {
+        let ddl = "CREATE TABLE ps (
+                a INTEGER, b INTEGER, c DECIMAL(15, 2),
+                CONSTRAINT ps_pk PRIMARY KEY (b, a),
+                FOREIGN KEY (a) REFERENCES p (a),
+                UNIQUE (c)
+            )";
+        let schemas = parse_create_tables(ddl).unwrap();
+        assert_eq!(schemas["ps"].keys(), &vec![0usize, 1]);
+        assert_eq!(schemas["ps"].dtype("c"), DataType::Float);
+    }
+
+    #[test]
+    fn rejects_invalid_ddl() {
+        assert!(parse_create_tables("CREATE TABLE t (a BLOB)").is_err());
+        assert!(parse_create_tables("CREATE TABLE t (a INTEGER").is_err());
+        assert!(parse_create_tables("CREATE TABLE t (a INTEGER, PRIMARY KEY (b))").is_err());
+    }
+}
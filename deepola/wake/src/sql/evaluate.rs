@@ -0,0 +1,409 @@
+use std::error::Error;
+use std::result::Result;
+
+use polars::prelude::*;
+
+use super::ast::*;
+
+impl Expr {
+    /// Evaluates the expression on every row of `df`. The result is named after the
+    /// expression (see [std::fmt::Display] for [Expr]).
+    ///
+    /// Arithmetic is done on Int64 unless an operand is a float, and `/` always yields
+    /// Float64. Aggregates cannot be evaluated per row.
+    pub fn evaluate(&self, df: &DataFrame) -> Result<Series, Box<dyn Error>> {
+        let mut series = evaluate(self, df)?;
+        series.rename(&self.to_string());
+        Ok(series)
+    }
+
+    /// Evaluates a predicate into a mask that can be passed to [DataFrame::filter].
+    pub fn evaluate_mask(&self, df: &DataFrame) -> Result<BooleanChunked, Box<dyn Error>> {
+        evaluate_bool(self, df)
+    }
+}
+
+fn evaluate(expr: &Expr, df: &DataFrame) -> Result<Series, Box<dyn Error>> {
+    match expr {
+        Expr::Column { name, .. } => Ok(df.column(name)?.clone()),
+        Expr::Literal(literal) => Ok(literal_series(literal, df.height())),
+        Expr::BinaryOp { left, op, right } => {
+            binary(&evaluate(left, df)?, *op, &evaluate(right, df)?)
+        }
+        Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            expr,
+        } => Ok((!evaluate_bool(expr, df)?).into_series()),
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus,
+            expr,
+        } => {
+            let minus_one = literal_series(&Literal::Integer(-1), df.height());
+            binary(&minus_one, BinaryOperator::Multiply, &evaluate(expr, df)?)
+        }
+        Expr::Between {
+            expr,
+            negated,
+            low,
+            high,
+        } => {
+            let value = evaluate(expr, df)?;
+            let low = binary(&value, BinaryOperator::GtEq, &evaluate(low, df)?)?;
+            let high = binary(&value, BinaryOperator::LtEq, &evaluate(high, df)?)?;
+            let mask = low.bool()? & high.bool()?;
+            Ok(negate_if(mask, *negated).into_series())
+        }
+        Expr::InList {
+            expr,
+            negated,
+            list,
+        } => {
+            let value = evaluate(expr, df)?;
+            let mut mask = BooleanChunked::full("", false, df.height());
+            for item in list {
+                let equal = binary(&value, BinaryOperator::Eq, &evaluate(item, df)?)?;
+                mask = &mask | equal.bool()?;
+            }
+            Ok(negate_if(mask, *negated).into_series())
+        }
+        Expr::Like {
+            expr,
+            negated,
+            pattern,
+        } => {
+            let value = evaluate(expr, df)?;
+            let pattern: Vec<char> = pattern.chars().collect();
+            let mask: BooleanChunked = value
+                .utf8()?
+                .into_iter()
+                .map(|v| v.map(|v| like(&v.chars().collect::<Vec<char>>(), &pattern)))
+                .collect();
+            Ok(negate_if(mask, *negated).into_series())
+        }
+        Expr::IsNull { expr, negated } => {
+            let mask = evaluate(expr, df)?.is_null();
+            Ok(negate_if(mask, *negated).into_series())
+        }
+        Expr::Case {
+            operand,
+            when_then,
+            else_result,
+        } => {
+            let operand = operand.as_ref().map(|x| evaluate(x, df)).transpose()?;
+            let mut branches = vec![];
+            for (when, then) in when_then {
+                let mask = match &operand {
+                    Some(operand) => binary(operand, BinaryOperator::Eq, &evaluate(when, df)?)?
+                        .bool()?
+                        .clone(),
+                    None => evaluate_bool(when, df)?,
+                };
+                branches.push((mask, evaluate(then, df)?));
+            }
+            let mut result = match else_result {
+                Some(else_result) => evaluate(else_result, df)?,
+                None => Series::full_null("", df.height(), branches[0].1.dtype()),
+            };
+            // The first matching branch wins, so apply them from the last one.
+            for (mask, then) in branches.iter().rev() {
+                let (then, otherwise) = coerce(then, &result)?;
+                result = then.zip_with(mask, &otherwise)?;
+            }
+            Ok(result)
+        }
+        Expr::Aggregate { .. } => {
+            Err(format!("Aggregates cannot be evaluated per row: {}", expr).into())
+        }
+    }
+}
+
+fn evaluate_bool(expr: &Expr, df: &DataFrame) -> Result<BooleanChunked, Box<dyn Error>> {
+    let series = evaluate(expr, df)?;
+    match series.bool() {
+        Ok(mask) => Ok(mask.clone()),
+        Err(_) => Err(format!("Expected a predicate, found {}: {:?}", expr, series.dtype()).into()),
+    }
+}
+
+fn negate_if(mask: BooleanChunked, negated: bool) -> BooleanChunked {
+    if negated {
+        !mask
+    } else {
+        mask
+    }
+}
+
+/// A column holding `literal` in each of the `height` rows.
+fn literal_series(literal: &Literal, height: usize) -> Series {
+    match literal {
+        Literal::Integer(value) => Series::new("", vec![*value; height]),
+        Literal::Float(value) => Series::new("", vec![*value; height]),
+        Literal::String(value) | Literal::Date(value) => {
+            Series::new("", vec![value.as_str(); height])
+        }
+        Literal::Boolean(value) => Series::new("", vec![*value; height]),
+        Literal::Null => Series::full_null("", height, &DataType::Int64),
+    }
+}
+
+fn binary(left: &Series, op: BinaryOperator, right: &Series) -> Result<Series, Box<dyn Error>> {
+    let result = match op {
+        BinaryOperator::And => (left.bool()? & right.bool()?).into_series(),
+        BinaryOperator::Or => (left.bool()? | right.bool()?).into_series(),
+        BinaryOperator::Eq
+        | BinaryOperator::NotEq
+        | BinaryOperator::Lt
+        | BinaryOperator::LtEq
+        | BinaryOperator::Gt
+        | BinaryOperator::GtEq => {
+            let (left, right) = coerce(left, right)?;
+            let mask = match op {
+                BinaryOperator::Eq => left.equal(&right)?,
+                BinaryOperator::NotEq => left.not_equal(&right)?,
+                BinaryOperator::Lt => left.lt(&right)?,
+                BinaryOperator::LtEq => left.lt_eq(&right)?,
+                BinaryOperator::Gt => left.gt(&right)?,
+                _ => left.gt_eq(&right)?,
+            };
+            mask.into_series()
+        }
+        BinaryOperator::Divide => {
+            let left = left.cast(&DataType::Float64)?;
+            let right = right.cast(&DataType::Float64)?;
+            &left / &right
+        }
+        BinaryOperator::Plus
+        | BinaryOperator::Minus
+        | BinaryOperator::Multiply
+        | BinaryOperator::Modulo => {
+            if !is_numeric(left.dtype()) || !is_numeric(right.dtype()) {
+                return Err(format!(
+                    "Arithmetic on non-numeric values: {:?} {} {:?}",
+                    left.dtype(),
+                    op,
+                    right.dtype()
+                )
+                .into());
+            }
+            let (left, right) = coerce(left, right)?;
+            match op {
+                BinaryOperator::Plus => &left + &right,
+                BinaryOperator::Minus => &left - &right,
+                BinaryOperator::Multiply => &left * &right,
+                _ => &left % &right,
+            }
+        }
+    };
+    Ok(result)
+}
+
+fn is_numeric(dtype: &DataType) -> bool {
+    matches!(
+        dtype,
+        DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Float32
+            | DataType::Float64
+    )
+}
+
+/// Casts both operands to a common type: Float64 if either is a float, otherwise Int64 for
+/// numbers. An all-null operand takes the type of the other one.
+fn coerce(left: &Series, right: &Series) -> Result<(Series, Series), Box<dyn Error>> {
+    if left.dtype() == right.dtype() {
+        return Ok((left.clone(), right.clone()));
+    }
+    if left.null_count() == left.len() {
+        return Ok((left.cast(right.dtype())?, right.clone()));
+    }
+    if right.null_count() == right.len() {
+        return Ok((left.clone(), right.cast(left.dtype())?));
+    }
+    if is_numeric(left.dtype()) && is_numeric(right.dtype()) {
+        let dtype = if matches!(left.dtype(), DataType::Float32 | DataType::Float64)
+            || matches!(right.dtype(), DataType::Float32 | DataType::Float64)
+        {
+            DataType::Float64
+        } else {
+            DataType::Int64
+        };
+        return Ok((left.cast(&dtype)?, right.cast(&dtype)?));
+    }
+    Err(format!(
+        "Incompatible types: {:?} and {:?}",
+        left.dtype(),
+        right.dtype()
+    )
+    .into())
+}
+
+/// SQL LIKE: `%` matches any sequence of characters and `_` matches exactly one.
+fn like(value: &[char], pattern: &[char]) -> bool {
+    // Backtracking over the last `%` only; linear in practice.
+    let (mut v, mut p) = (0, 0);
+    let mut last_percent: Option<(usize, usize)> = None;
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '_' || pattern[p] == value[v]) {
+            v += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == '%' {
+            last_percent = Some((p, v));
+            p += 1;
+        } else if let Some((percent_p, percent_v)) = last_percent {
+            // Let the last % absorb one more character.
+            p = percent_p + 1;
+            v = percent_v + 1;
+            last_percent = Some((percent_p, percent_v + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '%')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::parse_expr;
+
+    fn example_df() -> DataFrame {
+        df!(
+            "p_type" => &["PROMO BRUSHED", "STANDARD TIN", "PROMO TIN"],
+            "p_size" => &[5i64, 12, 20],
+            "p_retailprice" => &[1000.5, 901.0, 1200.0],
+        )
+        .unwrap()
+    }
+
+    fn evaluate_sql(sql: &str) -> Series {
+        parse_expr(sql).unwrap().evaluate(&example_df()).unwrap()
+    }
+
+    fn mask_sql(sql: &str) -> Vec<bool> {
+        parse_expr(sql)
+            .unwrap()
+            .evaluate_mask(&example_df())
+            .unwrap()
+            .into_no_null_iter()
+            .collect()
+    }
+
+    #[test]
+    fn can_evaluate_arithmetic() {
+        let series = evaluate_sql("p_size * 2 + 1");
+        assert_eq!(series.name(), "p_size * 2 + 1");
+        assert_eq!(
+            series
+                .i64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<i64>>(),
+            vec![11, 25, 41]
+        );
+
+        let series = evaluate_sql("p_retailprice * (1 - 0.5)");
+        assert_eq!(
+            series
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<f64>>(),
+            vec![500.25, 450.5, 600.0]
+        );
+        let series = evaluate_sql("p_size / 2");
+        assert_eq!(
+            series
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<f64>>(),
+            vec![2.5, 6.0, 10.0]
+        );
+        let series = evaluate_sql("-p_size");
+        assert_eq!(
+            series
+                .i64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<i64>>(),
+            vec![-5, -12, -20]
+        );
+    }
+
+    #[test]
+    fn can_evaluate_predicates() {
+        assert_eq!(mask_sql("p_retailprice > 1000"), vec![true, false, true]);
+        assert_eq!(mask_sql("p_size in (5, 20)"), vec![true, false, true]);
+        assert_eq!(
+            mask_sql("p_size not between 10 and 20"),
+            vec![true, false, false]
+        );
+        assert_eq!(mask_sql("p_type like 'PROMO%'"), vec![true, false, true]);
+        assert_eq!(mask_sql("p_type like '%TIN'"), vec![false, true, true]);
+        assert_eq!(
+            mask_sql("p_type not like '_TANDARD%'"),
+            vec![true, false, true]
+        );
+        assert_eq!(
+            mask_sql("p_type <> 'STANDARD TIN' and not p_size = 20 or p_size > 15"),
+            vec![true, false, true]
+        );
+        assert!(parse_expr("p_size + 1")
+            .unwrap()
+            .evaluate_mask(&example_df())
+            .is_err());
+        assert!(parse_expr("p_type > 5")
+            .unwrap()
+            .evaluate(&example_df())
+            .is_err());
+    }
+
+    #[test]
+    fn can_evaluate_case() {
+        let series = evaluate_sql("case when p_type like 'PROMO%' then p_retailprice else 0 end");
+        assert_eq!(
+            series
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<f64>>(),
+            vec![1000.5, 0.0, 1200.0]
+        );
+        let series = evaluate_sql("case p_size when 5 then 'small' when 12 then 'medium' end");
+        assert_eq!(
+            series
+                .utf8()
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<Option<&str>>>(),
+            vec![Some("small"), Some("medium"), None]
+        );
+    }
+
+    #[test]
+    fn can_match_like_patterns() {
+        let matches = |value: &str, pattern: &str| {
+            like(
+                &value.chars().collect::<Vec<char>>(),
+                &pattern.chars().collect::<Vec<char>>(),
+            )
+        };
+        assert!(matches("Manufacturer#1", "Manufacturer#1%"));
+        assert!(matches("abc", "%"));
+        assert!(matches("abc", "a%c"));
+        assert!(matches("abcbc", "%bc"));
+        assert!(matches("abc", "_b_"));
+        assert!(matches("", "%"));
+        assert!(!matches("abc", "a%d"));
+        assert!(!matches("abc", "ab"));
+        assert!(!matches("ab", "abc"));
+        assert!(matches("aXbXc", "a%b%c"));
+        assert!(!matches("aXbX", "a%b%c"));
+    }
+}
@@ -0,0 +1,403 @@
+use std::error::Error;
+use std::fmt;
+
+/// An identifier or a keyword. Unquoted words are case-insensitive and stored in lowercase.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word {
+    pub value: String,
+    pub quoted: bool,
+}
+
+impl Word {
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        !self.quoted && self.value.eq_ignore_ascii_case(keyword)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Word(Word),
+    Number(String),
+    String(String),
+    LParen,
+    RParen,
+    Comma,
+    Semicolon,
+    Period,
+    Star,
+    Plus,
+    Minus,
+    Slash,
+    Percent,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Word(w) if w.quoted => write!(f, "\"{}\"", w.value),
+            Token::Word(w) => write!(f, "{}", w.value),
+            Token::Number(n) => write!(f, "{}", n),
+            Token::String(s) => write!(f, "'{}'", s),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+            Token::Semicolon => write!(f, ";"),
+            Token::Period => write!(f, "."),
+            Token::Star => write!(f, "*"),
+            Token::Plus => write!(f, "+"),
+            Token::Minus => write!(f, "-"),
+            Token::Slash => write!(f, "/"),
+            Token::Percent => write!(f, "%"),
+            Token::Eq => write!(f, "="),
+            Token::NotEq => write!(f, "<>"),
+            Token::Lt => write!(f, "<"),
+            Token::LtEq => write!(f, "<="),
+            Token::Gt => write!(f, ">"),
+            Token::GtEq => write!(f, ">="),
+        }
+    }
+}
+
+/// Splits SQL text into tokens, dropping whitespace and comments (`-- ...` and `/* ... */`).
+pub fn tokenize(sql: &str) -> Result<Vec<Token>, Box<dyn Error>> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '-' if next == Some('-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err("Unterminated comment".into());
+                }
+                i += 2;
+            }
+            '\'' => {
+                // 'It''s' is the string It's.
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err("Unterminated string literal".into()),
+                        Some('\'') if chars.get(i + 1) == Some(&'\'') => {
+                            value.push('\'');
+                            i += 2;
+                        }
+                        Some('\'') => {
+                            i += 1;
+                            break;
+                        }
+                        Some(ch) => {
+                            value.push(*ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(Token::String(value));
+            }
+            '"' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && chars[i] != '"' {
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err("Unterminated quoted identifier".into());
+                }
+                tokens.push(Token::Word(Word {
+                    value: chars[start..i].iter().collect(),
+                    quoted: true,
+                }));
+                i += 1;
+            }
+            _ if c.is_ascii_digit()
+                || (c == '.' && matches!(next, Some(n) if n.is_ascii_digit())) =>
+            {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push(Token::Number(chars[start..i].iter().collect()));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '#')
+                {
+                    i += 1;
+                }
+                let value: String = chars[start..i].iter().collect();
+                tokens.push(Token::Word(Word {
+                    value: value.to_lowercase(),
+                    quoted: false,
+                }));
+            }
+            _ => {
+                let (token, len) = match (c, next) {
+                    ('<', Some('=')) => (Token::LtEq, 2),
+                    ('>', Some('=')) => (Token::GtEq, 2),
+                    ('<', Some('>')) => (Token::NotEq, 2),
+                    ('!', Some('=')) => (Token::NotEq, 2),
+                    ('<', _) => (Token::Lt, 1),
+                    ('>', _) => (Token::Gt, 1),
+                    ('=', _) => (Token::Eq, 1),
+                    ('(', _) => (Token::LParen, 1),
+                    (')', _) => (Token::RParen, 1),
+                    (',', _) => (Token::Comma, 1),
+                    (';', _) => (Token::Semicolon, 1),
+                    ('.', _) => (Token::Period, 1),
+                    ('*', _) => (Token::Star, 1),
+                    ('+', _) => (Token::Plus, 1),
+                    ('-', _) => (Token::Minus, 1),
+                    ('/', _) => (Token::Slash, 1),
+                    ('%', _) => (Token::Percent, 1),
+                    _ => return Err(format!("Unexpected character: {}", c).into()),
+                };
+                tokens.push(token);
+                i += len;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// A cursor over tokens with the helpers shared by the SQL parsers.
+pub struct TokenStream {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl TokenStream {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        TokenStream {
+            tokens,
+            position: 0,
+        }
+    }
+
+    pub fn from_sql(sql: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(tokenize(sql)?))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    pub fn peek(&self) -> Option<&Token> {
+        self.peek_nth(0)
+    }
+
+    pub fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.position + n)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        if token.is_some() {
+            self.position += 1;
+        }
+        token
+    }
+
+    /// Whether the n-th next token is the given keyword.
+    pub fn is_keyword_nth(&self, n: usize, keyword: &str) -> bool {
+        matches!(self.peek_nth(n), Some(Token::Word(w)) if w.is_keyword(keyword))
+    }
+
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.is_keyword_nth(0, keyword)
+    }
+
+    /// Consumes the next token if it is the given keyword.
+    pub fn consume_keyword(&mut self, keyword: &str) -> bool {
+        if self.is_keyword(keyword) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the keywords if the next tokens are exactly these keywords.
+    pub fn consume_keywords(&mut self, keywords: &[&str]) -> bool {
+        let matched = keywords
+            .iter()
+            .enumerate()
+            .all(|(n, keyword)| self.is_keyword_nth(n, keyword));
+        if matched {
+            self.position += keywords.len();
+        }
+        matched
+    }
+
+    pub fn expect_keyword(&mut self, keyword: &str) -> Result<(), Box<dyn Error>> {
+        if self.consume_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&keyword.to_uppercase()))
+        }
+    }
+
+    /// Consumes the next token if it equals the given token.
+    pub fn consume(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect(&mut self, token: &Token) -> Result<(), Box<dyn Error>> {
+        if self.consume(token) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&token.to_string()))
+        }
+    }
+
+    pub fn expect_identifier(&mut self) -> Result<String, Box<dyn Error>> {
+        match self.peek() {
+            Some(Token::Word(w)) => {
+                let value = w.value.clone();
+                self.position += 1;
+                Ok(value)
+            }
+            _ => Err(self.unexpected("identifier")),
+        }
+    }
+
+    /// Skips tokens up to the matching closing parenthesis. The opening parenthesis must
+    /// have been consumed already.
+    pub fn skip_until_closing_paren(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(Token::LParen) => depth += 1,
+                Some(Token::RParen) => depth -= 1,
+                Some(_) => {}
+                None => return Err("Missing closing parenthesis".into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips the rest of the current statement, including the terminating semicolon.
+    pub fn skip_statement(&mut self) {
+        while let Some(token) = self.next() {
+            if token == Token::Semicolon {
+                break;
+            }
+        }
+    }
+
+    /// An error describing the next token, which is not what the parser expected.
+    pub fn unexpected(&self, expected: &str) -> Box<dyn Error> {
+        match self.peek() {
+            Some(token) => format!("Expected {}, found {}", expected, token).into(),
+            None => format!("Expected {}, found end of input", expected).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(value: &str) -> Token {
+        Token::Word(Word {
+            value: value.to_string(),
+            quoted: false,
+        })
+    }
+
+    #[test]
+    fn can_tokenize() {
+        let tokens = tokenize(
+            "SELECT sum(p_retailprice) AS \"Total\" -- comment\n\
+             FROM part /* multi\n line */ WHERE p_size <> 5 AND p_mfgr LIKE 'Manu''s#1%';",
+        )
+        .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                word("select"),
+                word("sum"),
+                Token::LParen,
+                word("p_retailprice"),
+                Token::RParen,
+                word("as"),
+                Token::Word(Word {
+                    value: "Total".to_string(),
+                    quoted: true
+                }),
+                word("from"),
+                word("part"),
+                word("where"),
+                word("p_size"),
+                Token::NotEq,
+                Token::Number("5".to_string()),
+                word("and"),
+                word("p_mfgr"),
+                word("like"),
+                Token::String("Manu's#1%".to_string()),
+                Token::Semicolon,
+            ]
+        );
+    }
+
+    #[test]
+    fn can_tokenize_numbers_and_operators() {
+        let tokens = tokenize("100.00*(1-l_discount)>=.5").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number("100.00".to_string()),
+                Token::Star,
+                Token::LParen,
+                Token::Number("1".to_string()),
+                Token::Minus,
+                word("l_discount"),
+                Token::RParen,
+                Token::GtEq,
+                Token::Number(".5".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert!(tokenize("SELECT 'abc").is_err());
+        assert!(tokenize("SELECT \"abc").is_err());
+        assert!(tokenize("SELECT /* abc").is_err());
+        assert!(tokenize("SELECT a ? b").is_err());
+    }
+
+    #[test]
+    fn can_consume_keywords() {
+        let mut tokens = TokenStream::from_sql("group BY a").unwrap();
+        assert!(!tokens.consume_keywords(&["order", "by"]));
+        assert!(tokens.consume_keywords(&["group", "by"]));
+        assert_eq!(tokens.expect_identifier().unwrap(), "a");
+        assert!(tokens.is_empty());
+        assert!(tokens.expect_keyword("from").is_err());
+    }
+}
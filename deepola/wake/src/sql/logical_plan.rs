@@ -0,0 +1,328 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::catalog::Catalog;
+use crate::polars_operations::format_sort_keys;
+
+use super::ast::Expr;
+use super::binder::BoundQuery;
+
+/// A tree of relational operators. Column references in expressions are the unqualified names
+/// of the columns produced by the input of the operator.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogicalPlan {
+    /// Reads `columns` of a table, in schema order.
+    Scan { table: String, columns: Vec<String> },
+    Filter {
+        input: Box<LogicalPlan>,
+        predicate: Expr,
+    },
+    /// Computes the named output expressions.
+    Project {
+        input: Box<LogicalPlan>,
+        exprs: Vec<(Expr, String)>,
+    },
+    /// Inner equi-join of `left` (probe side) with `right` (build side). The key columns of the
+    /// build side are dropped from the output.
+    Join {
+        left: Box<LogicalPlan>,
+        right: Box<LogicalPlan>,
+        left_on: Vec<String>,
+        right_on: Vec<String>,
+    },
+    /// Groups the input by `keys` and sums each of `sums` per group. Every output message is
+    /// the result over all the input read so far.
+    Aggregate {
+        input: Box<LogicalPlan>,
+        keys: Vec<(Expr, String)>,
+        sums: Vec<(Expr, String)>,
+    },
+    /// Sorts by the given columns; `true` means descending.
+    Sort {
+        input: Box<LogicalPlan>,
+        by: Vec<(String, bool)>,
+    },
+    Limit {
+        input: Box<LogicalPlan>,
+        limit: usize,
+    },
+}
+
+impl LogicalPlan {
+    /// Builds the plan of a bound query: scans joined in join order, filtered after the joins,
+    /// then aggregated, projected, sorted and limited.
+    pub(crate) fn from_query(
+        query: &BoundQuery,
+        catalog: &Catalog,
+    ) -> Result<LogicalPlan, Box<dyn Error>> {
+        let scan = |table: &str| -> Result<LogicalPlan, Box<dyn Error>> {
+            Ok(LogicalPlan::Scan {
+                table: table.to_string(),
+                columns: catalog
+                    .schema(table)?
+                    .columns
+                    .iter()
+                    .map(|c| c.name.clone())
+                    .collect(),
+            })
+        };
+
+        let mut plan = scan(&query.scans[0])?;
+        for join in &query.joins {
+            plan = LogicalPlan::Join {
+                left: Box::new(plan),
+                right: Box::new(scan(&join.table)?),
+                left_on: join.left_on.clone(),
+                right_on: join.right_on.clone(),
+            };
+        }
+        if let Some(filter) = &query.filter {
+            plan = plan.filter(filter.clone());
+        }
+        if let Some(aggregation) = &query.aggregation {
+            plan = LogicalPlan::Aggregate {
+                input: Box::new(plan),
+                keys: aggregation.keys.clone(),
+                sums: aggregation.sums.clone(),
+            };
+            if let Some(having) = &query.having {
+                plan = plan.filter(having.clone());
+            }
+        }
+        plan = LogicalPlan::Project {
+            input: Box::new(plan),
+            exprs: query.projection.clone(),
+        };
+        if !query.order_by.is_empty() {
+            plan = LogicalPlan::Sort {
+                input: Box::new(plan),
+                by: query.order_by.clone(),
+            };
+        }
+        if let Some(limit) = query.limit {
+            plan = LogicalPlan::Limit {
+                input: Box::new(plan),
+                limit,
+            };
+        }
+        Ok(plan)
+    }
+
+    /// Wraps the plan in a Filter node.
+    pub fn filter(self, predicate: Expr) -> LogicalPlan {
+        LogicalPlan::Filter {
+            input: Box::new(self),
+            predicate,
+        }
+    }
+
+    /// Names of the columns produced by this plan, in order.
+    pub fn output_columns(&self) -> Vec<String> {
+        match self {
+            LogicalPlan::Scan { columns, .. } => columns.clone(),
+            LogicalPlan::Project { exprs, .. } => exprs.iter().map(|(_, n)| n.clone()).collect(),
+            LogicalPlan::Join {
+                left,
+                right,
+                right_on,
+                ..
+            } => {
+                let mut columns = left.output_columns();
+                columns.extend(
+                    right
+                        .output_columns()
+                        .into_iter()
+                        .filter(|c| !right_on.contains(c)),
+                );
+                columns
+            }
+            LogicalPlan::Aggregate { keys, sums, .. } => keys
+                .iter()
+                .chain(sums.iter())
+                .map(|(_, n)| n.clone())
+                .collect(),
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. } => input.output_columns(),
+        }
+    }
+
+    pub fn inputs(&self) -> Vec<&LogicalPlan> {
+        match self {
+            LogicalPlan::Scan { .. } => vec![],
+            LogicalPlan::Join { left, right, .. } => vec![left, right],
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Project { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. } => vec![input],
+        }
+    }
+
+    /// Rebuilds the plan with each input replaced by `f(input)`.
+    pub fn map_inputs(self, f: &mut dyn FnMut(LogicalPlan) -> LogicalPlan) -> LogicalPlan {
+        match self {
+            LogicalPlan::Scan { .. } => self,
+            LogicalPlan::Filter { input, predicate } => LogicalPlan::Filter {
+                input: Box::new(f(*input)),
+                predicate,
+            },
+            LogicalPlan::Project { input, exprs } => LogicalPlan::Project {
+                input: Box::new(f(*input)),
+                exprs,
+            },
+            LogicalPlan::Join {
+                left,
+                right,
+                left_on,
+                right_on,
+            } => LogicalPlan::Join {
+                left: Box::new(f(*left)),
+                right: Box::new(f(*right)),
+                left_on,
+                right_on,
+            },
+            LogicalPlan::Aggregate { input, keys, sums } => LogicalPlan::Aggregate {
+                input: Box::new(f(*input)),
+                keys,
+                sums,
+            },
+            LogicalPlan::Sort { input, by } => LogicalPlan::Sort {
+                input: Box::new(f(*input)),
+                by,
+            },
+            LogicalPlan::Limit { input, limit } => LogicalPlan::Limit {
+                input: Box::new(f(*input)),
+                limit,
+            },
+        }
+    }
+
+    /// Whether each output message is the complete result so far (as after an aggregation),
+    /// rather than a part of the result.
+    pub fn emits_snapshots(&self) -> bool {
+        match self {
+            LogicalPlan::Scan { .. } => false,
+            // A sort emits complete results: sorted snapshots, or everything at once at EOF.
+            LogicalPlan::Aggregate { .. } | LogicalPlan::Sort { .. } => true,
+            // The output of a join follows its probe side.
+            LogicalPlan::Join { left, .. } => left.emits_snapshots(),
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Project { input, .. }
+            | LogicalPlan::Limit { input, .. } => input.emits_snapshots(),
+        }
+    }
+
+    /// A one-line description of the operator, e.g., `Filter: r_name = 'EUROPE'`.
+    pub fn describe(&self) -> String {
+        match self {
+            LogicalPlan::Scan { table, columns } => {
+                format!("Scan: {} [{}]", table, columns.join(", "))
+            }
+            LogicalPlan::Filter { predicate, .. } => format!("Filter: {}", predicate),
+            LogicalPlan::Project { exprs, .. } => format!("Project: {}", format_named(exprs)),
+            LogicalPlan::Join {
+                left_on, right_on, ..
+            } => {
+                let keys: Vec<String> = left_on
+                    .iter()
+                    .zip(right_on.iter())
+                    .map(|(l, r)| format!("{} = {}", l, r))
+                    .collect();
+                format!("HashJoin: {}", keys.join(", "))
+            }
+            LogicalPlan::Aggregate { keys, sums, .. } => {
+                format!(
+                    "Aggregate: keys=[{}], sums=[{}]",
+                    format_named(keys),
+                    format_named(sums)
+                )
+            }
+            LogicalPlan::Sort { by, .. } => format!("Sort: {}", format_sort_keys(by)),
+            LogicalPlan::Limit { limit, .. } => format!("Limit: {}", limit),
+        }
+    }
+
+    /// The plan as a tree with one operator per line, inputs indented below their consumer.
+    /// `annotate` adds text after the description of each operator.
+    pub fn format_tree(&self, annotate: &mut dyn FnMut(&LogicalPlan) -> String) -> String {
+        let mut lines = vec![];
+        self.format_lines(0, annotate, &mut lines);
+        lines.join("\n")
+    }
+
+    fn format_lines(
+        &self,
+        depth: usize,
+        annotate: &mut dyn FnMut(&LogicalPlan) -> String,
+        lines: &mut Vec<String>,
+    ) {
+        let annotation = annotate(self);
+        let mut line = format!("{}{}", "  ".repeat(depth), self.describe());
+        if !annotation.is_empty() {
+            line.push_str("  ");
+            line.push_str(&annotation);
+        }
+        lines.push(line);
+        for input in self.inputs() {
+            input.format_lines(depth + 1, annotate, lines);
+        }
+    }
+}
+
+/// Named expressions as `expr AS name, ..`, omitting the names of columns that keep their name.
+pub(crate) fn format_named(exprs: &[(Expr, String)]) -> String {
+    exprs
+        .iter()
+        .map(|(expr, name)| match expr {
+            Expr::Column { name: column, .. } if column == name => name.clone(),
+            expr => format!("{} AS {}", expr, name),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl fmt::Display for LogicalPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format_tree(&mut |_| String::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::binder::bind;
+    use crate::sql::parse_query;
+
+    #[test]
+    fn can_build_plan_from_query() {
+        let catalog = Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        let query = parse_query(
+            "SELECT r_name, count(*) AS nations FROM nation, region \
+             WHERE n_regionkey = r_regionkey GROUP BY r_name ORDER BY nations DESC LIMIT 2",
+        )
+        .unwrap();
+        let plan = LogicalPlan::from_query(&bind(&query, &catalog).unwrap(), &catalog).unwrap();
+        assert_eq!(plan.output_columns(), vec!["r_name", "nations"]);
+        assert!(plan.emits_snapshots());
+
+        // Limit -> Sort -> Project -> Aggregate -> Join
+        let mut node = &plan;
+        for _ in 0..4 {
+            node = node.inputs()[0];
+        }
+        assert!(matches!(node, LogicalPlan::Join { .. }));
+        assert!(!node.emits_snapshots());
+        assert_eq!(
+            node.output_columns(),
+            vec![
+                "n_nationkey",
+                "n_name",
+                "n_regionkey",
+                "n_comment",
+                "r_name",
+                "r_comment"
+            ]
+        );
+    }
+}
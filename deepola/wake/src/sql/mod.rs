@@ -0,0 +1,24 @@
+//! A small SQL frontend. Queries are parsed into an AST ([Query]), bound against the tables of
+//! a [crate::catalog::Catalog], turned into a [LogicalPlan] that is rewritten by [optimize], and
+//! compiled into an execution graph by [SqlPlanner]. Schemas can also be loaded from
+//! `CREATE TABLE` statements.
+
+mod ast;
+mod binder;
+mod cost;
+mod ddl;
+mod evaluate;
+mod lexer;
+mod logical_plan;
+mod optimizer;
+mod parser;
+mod planner;
+
+pub use ast::*;
+pub use cost::*;
+pub use ddl::*;
+pub use lexer::*;
+pub use logical_plan::*;
+pub use optimizer::*;
+pub use parser::*;
+pub use planner::*;
@@ -0,0 +1,235 @@
+use super::ast::Expr;
+use super::logical_plan::LogicalPlan;
+
+/// Rewrites a plan into an equivalent one that moves less data between nodes. Filters are
+/// pushed below joins, into the side that defines all the columns they use, and each scan
+/// only reads the columns used by the nodes above it.
+pub fn optimize(plan: LogicalPlan) -> LogicalPlan {
+    let plan = push_down_filters(plan, vec![]);
+    let required = plan.output_columns();
+    prune_columns(plan, &required)
+}
+
+/// Moves `predicates` (which apply to the output of `plan`) and the filters of `plan` as far
+/// down as possible.
+fn push_down_filters(plan: LogicalPlan, mut predicates: Vec<Expr>) -> LogicalPlan {
+    match plan {
+        LogicalPlan::Filter { input, predicate } => {
+            predicates.extend(predicate.split_conjunction());
+            push_down_filters(*input, predicates)
+        }
+        LogicalPlan::Join {
+            left,
+            right,
+            left_on,
+            right_on,
+        } => {
+            let left_columns = left.output_columns();
+            let right_columns = right.output_columns();
+            let mut left_predicates = vec![];
+            let mut right_predicates = vec![];
+            let mut remaining = vec![];
+            for predicate in predicates {
+                let columns = predicate.columns();
+                if columns.iter().all(|c| left_columns.contains(c)) {
+                    left_predicates.push(predicate);
+                } else if columns.iter().all(|c| right_columns.contains(c)) {
+                    right_predicates.push(predicate);
+                } else {
+                    remaining.push(predicate);
+                }
+            }
+            let join = LogicalPlan::Join {
+                left: Box::new(push_down_filters(*left, left_predicates)),
+                right: Box::new(push_down_filters(*right, right_predicates)),
+                left_on,
+                right_on,
+            };
+            with_filter(join, remaining)
+        }
+        // Filters above an aggregation (HAVING) or a projection refer to computed columns,
+        // so they stay where they are.
+        plan => with_filter(
+            plan.map_inputs(&mut |input| push_down_filters(input, vec![])),
+            predicates,
+        ),
+    }
+}
+
+fn with_filter(plan: LogicalPlan, predicates: Vec<Expr>) -> LogicalPlan {
+    match Expr::conjunction(predicates) {
+        Some(predicate) => plan.filter(predicate),
+        None => plan,
+    }
+}
+
+/// Removes the columns that are not in `required` (or used by a node above the scans) from
+/// the scans of `plan`.
+fn prune_columns(plan: LogicalPlan, required: &[String]) -> LogicalPlan {
+    match plan {
+        LogicalPlan::Scan { table, columns } => {
+            let mut projected: Vec<String> = columns
+                .iter()
+                .filter(|c| required.contains(c))
+                .cloned()
+                .collect();
+            // A scan reads at least one column, e.g., for `SELECT count(*)`.
+            if projected.is_empty() {
+                projected.push(columns[0].clone());
+            }
+            LogicalPlan::Scan {
+                table,
+                columns: projected,
+            }
+        }
+        LogicalPlan::Filter { input, predicate } => {
+            let mut required = required.to_vec();
+            for column in predicate.columns() {
+                if !required.contains(&column) {
+                    required.push(column);
+                }
+            }
+            LogicalPlan::Filter {
+                input: Box::new(prune_columns(*input, &required)),
+                predicate,
+            }
+        }
+        LogicalPlan::Project { input, exprs } => {
+            let required = input_columns(exprs.iter());
+            LogicalPlan::Project {
+                input: Box::new(prune_columns(*input, &required)),
+                exprs,
+            }
+        }
+        LogicalPlan::Join {
+            left,
+            right,
+            left_on,
+            right_on,
+        } => {
+            let mut left_required = required.to_vec();
+            left_required.extend(left_on.iter().cloned());
+            let mut right_required = required.to_vec();
+            right_required.extend(right_on.iter().cloned());
+            LogicalPlan::Join {
+                left: Box::new(prune_columns(*left, &left_required)),
+                right: Box::new(prune_columns(*right, &right_required)),
+                left_on,
+                right_on,
+            }
+        }
+        LogicalPlan::Aggregate { input, keys, sums } => {
+            let required = input_columns(keys.iter().chain(sums.iter()));
+            LogicalPlan::Aggregate {
+                input: Box::new(prune_columns(*input, &required)),
+                keys,
+                sums,
+            }
+        }
+        LogicalPlan::Sort { input, by } => {
+            let mut sort_required = required.to_vec();
+            sort_required.extend(by.iter().map(|(c, _)| c.clone()));
+            LogicalPlan::Sort {
+                input: Box::new(prune_columns(*input, &sort_required)),
+                by,
+            }
+        }
+        LogicalPlan::Limit { .. } => plan.map_inputs(&mut |input| prune_columns(input, required)),
+    }
+}
+
+/// The input columns used by named expressions.
+fn input_columns<'a>(exprs: impl Iterator<Item = &'a (Expr, String)>) -> Vec<String> {
+    let mut columns: Vec<String> = vec![];
+    for column in exprs.flat_map(|(e, _)| e.columns()) {
+        if !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Catalog;
+    use crate::sql::binder::bind;
+    use crate::sql::parse_query;
+
+    fn optimized_plan(sql: &str) -> LogicalPlan {
+        let catalog = Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        let query = bind(&parse_query(sql).unwrap(), &catalog).unwrap();
+        optimize(LogicalPlan::from_query(&query, &catalog).unwrap())
+    }
+
+    /// Scanned tables and their columns, in plan order.
+    fn scans(plan: &LogicalPlan) -> Vec<(String, Vec<String>)> {
+        match plan {
+            LogicalPlan::Scan { table, columns } => vec![(table.clone(), columns.clone())],
+            plan => plan.inputs().into_iter().flat_map(scans).collect(),
+        }
+    }
+
+    #[test]
+    fn pushes_filters_below_joins() {
+        let plan = optimized_plan(
+            "SELECT s_name, n_name FROM supplier, nation, region \
+             WHERE s_nationkey = n_nationkey AND n_regionkey = r_regionkey \
+             AND r_name = 'EUROPE' AND s_acctbal > 0 AND s_name <> r_name",
+        );
+        // Project -> Filter -> Join(Join(Filter -> Scan supplier, Scan nation), Filter -> Scan region)
+        let filter = plan.inputs()[0];
+        match filter {
+            LogicalPlan::Filter { predicate, .. } => {
+                assert_eq!(predicate.to_string(), "s_name <> r_name")
+            }
+            plan => panic!("Expected a filter, found {:?}", plan),
+        }
+        let join = filter.inputs()[0];
+        let (probe, build) = (join.inputs()[0], join.inputs()[1]);
+        match build {
+            LogicalPlan::Filter { predicate, .. } => {
+                assert_eq!(predicate.to_string(), "r_name = 'EUROPE'")
+            }
+            plan => panic!("Expected a filter, found {:?}", plan),
+        }
+        match probe.inputs()[0] {
+            LogicalPlan::Filter { predicate, .. } => {
+                assert_eq!(predicate.to_string(), "s_acctbal > 0")
+            }
+            plan => panic!("Expected a filter, found {:?}", plan),
+        }
+    }
+
+    #[test]
+    fn prunes_scanned_columns() {
+        let plan = optimized_plan(
+            "SELECT r_name, sum(s_acctbal) AS balance FROM supplier, nation, region \
+             WHERE s_nationkey = n_nationkey AND n_regionkey = r_regionkey \
+             GROUP BY r_name ORDER BY balance DESC",
+        );
+        assert_eq!(
+            scans(&plan),
+            vec![
+                (
+                    "supplier".to_string(),
+                    vec!["s_nationkey".to_string(), "s_acctbal".to_string()]
+                ),
+                (
+                    "nation".to_string(),
+                    vec!["n_nationkey".to_string(), "n_regionkey".to_string()]
+                ),
+                (
+                    "region".to_string(),
+                    vec!["r_regionkey".to_string(), "r_name".to_string()]
+                ),
+            ]
+        );
+
+        let plan = optimized_plan("SELECT count(*) FROM lineitem");
+        assert_eq!(
+            scans(&plan),
+            vec![("lineitem".to_string(), vec!["l_orderkey".to_string()])]
+        );
+    }
+}
@@ -0,0 +1,702 @@
+use std::error::Error;
+
+use super::ast::*;
+use super::lexer::{Token, TokenStream};
+
+/// Keywords that end an expression or a clause, and so cannot be used as an implicit alias.
+const RESERVED: &[&str] = &[
+    "select", "from", "where", "group", "having", "order", "by", "join", "inner", "on", "as",
+    "and", "or", "not", "limit", "union", "asc", "desc", "when", "then", "else", "end", "left",
+    "right", "full", "outer", "cross", "natural",
+];
+
+/// Parses a single `SELECT` statement.
+///
+/// Supported: `SELECT [*|expr [AS alias], ..] FROM table [alias] [[INNER] JOIN table ON ..], ..
+/// [WHERE ..] [GROUP BY ..] [HAVING ..] [ORDER BY expr [ASC|DESC], ..] [LIMIT n]`. Expressions may use
+/// arithmetic, comparisons, AND/OR/NOT, BETWEEN, IN, LIKE, IS NULL, CASE,
+/// COUNT/SUM/AVG/MIN/MAX, and `DATE '..' +/- INTERVAL 'n' DAY|MONTH|YEAR`, which is folded into
+/// a date literal.
+pub fn parse_query(sql: &str) -> Result<Query, Box<dyn Error>> {
+    let mut parser = Parser {
+        tokens: TokenStream::from_sql(sql)?,
+    };
+    parser.parse_statement_query()
+}
+
+/// Parses a `SELECT` statement, optionally preceded by `EXPLAIN [ANALYZE]`.
+pub fn parse_statement(sql: &str) -> Result<Statement, Box<dyn Error>> {
+    let mut parser = Parser {
+        tokens: TokenStream::from_sql(sql)?,
+    };
+    if parser.tokens.consume_keyword("explain") {
+        let analyze = parser.tokens.consume_keyword("analyze");
+        let query = parser.parse_statement_query()?;
+        Ok(Statement::Explain { analyze, query })
+    } else {
+        Ok(Statement::Query(parser.parse_statement_query()?))
+    }
+}
+
+/// Parses a standalone expression, e.g., `l_shipdate <= date '1998-09-02'`.
+pub fn parse_expr(sql: &str) -> Result<Expr, Box<dyn Error>> {
+    let mut parser = Parser {
+        tokens: TokenStream::from_sql(sql)?,
+    };
+    let expr = parser.parse_expr()?;
+    if !parser.tokens.is_empty() {
+        return Err(parser.tokens.unexpected("end of expression"));
+    }
+    Ok(expr)
+}
+
+struct Parser {
+    tokens: TokenStream,
+}
+
+impl Parser {
+    /// Parses a query followed by an optional semicolon and the end of the input.
+    fn parse_statement_query(&mut self) -> Result<Query, Box<dyn Error>> {
+        let query = self.parse_query()?;
+        self.tokens.consume(&Token::Semicolon);
+        if !self.tokens.is_empty() {
+            return Err(self.tokens.unexpected("end of query"));
+        }
+        Ok(query)
+    }
+
+    fn parse_query(&mut self) -> Result<Query, Box<dyn Error>> {
+        self.tokens.expect_keyword("select")?;
+        let projection = self.parse_comma_separated(Self::parse_select_item)?;
+        self.tokens.expect_keyword("from")?;
+        let from = self.parse_comma_separated(Self::parse_table_with_joins)?;
+        let selection = if self.tokens.consume_keyword("where") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        let group_by = if self.tokens.consume_keywords(&["group", "by"]) {
+            self.parse_comma_separated(Self::parse_expr)?
+        } else {
+            vec![]
+        };
+        let having = if self.tokens.consume_keyword("having") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        let order_by = if self.tokens.consume_keywords(&["order", "by"]) {
+            self.parse_comma_separated(Self::parse_order_by_expr)?
+        } else {
+            vec![]
+        };
+        let limit = if self.tokens.consume_keyword("limit") {
+            let limit = match self.tokens.peek() {
+                Some(Token::Number(n)) => n
+                    .parse()
+                    .map_err(|_| format!("LIMIT must be a non-negative integer: {}", n))?,
+                _ => return Err(self.tokens.unexpected("row count")),
+            };
+            self.tokens.next();
+            Some(limit)
+        } else {
+            None
+        };
+        Ok(Query {
+            projection,
+            from,
+            selection,
+            group_by,
+            having,
+            order_by,
+            limit,
+        })
+    }
+
+    fn parse_comma_separated<T>(
+        &mut self,
+        f: fn(&mut Self) -> Result<T, Box<dyn Error>>,
+    ) -> Result<Vec<T>, Box<dyn Error>> {
+        let mut items = vec![f(self)?];
+        while self.tokens.consume(&Token::Comma) {
+            items.push(f(self)?);
+        }
+        Ok(items)
+    }
+
+    fn parse_select_item(&mut self) -> Result<SelectItem, Box<dyn Error>> {
+        if self.tokens.consume(&Token::Star) {
+            return Ok(SelectItem::Wildcard);
+        }
+        let expr = self.parse_expr()?;
+        let alias = self.parse_alias()?;
+        Ok(SelectItem::Expr { expr, alias })
+    }
+
+    /// `[AS] alias`
+    fn parse_alias(&mut self) -> Result<Option<String>, Box<dyn Error>> {
+        if self.tokens.consume_keyword("as") {
+            return Ok(Some(self.tokens.expect_identifier()?));
+        }
+        match self.tokens.peek() {
+            Some(Token::Word(w)) if w.quoted || !RESERVED.contains(&w.value.as_str()) => {
+                Ok(Some(self.tokens.expect_identifier()?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_table_with_joins(&mut self) -> Result<TableWithJoins, Box<dyn Error>> {
+        let relation = self.parse_table_ref()?;
+        let mut joins = vec![];
+        loop {
+            if self.tokens.consume_keywords(&["inner", "join"])
+                || self.tokens.consume_keyword("join")
+            {
+                let relation = self.parse_table_ref()?;
+                self.tokens.expect_keyword("on")?;
+                let on = self.parse_expr()?;
+                joins.push(Join { relation, on });
+            } else if ["left", "right", "full", "outer", "cross", "natural"]
+                .iter()
+                .any(|keyword| self.tokens.is_keyword(keyword))
+            {
+                return Err("Only inner joins are supported".into());
+            } else {
+                break;
+            }
+        }
+        Ok(TableWithJoins { relation, joins })
+    }
+
+    fn parse_table_ref(&mut self) -> Result<TableRef, Box<dyn Error>> {
+        if self.tokens.is_empty() || self.tokens.peek() == Some(&Token::LParen) {
+            return Err(self.tokens.unexpected("table name"));
+        }
+        let mut name = self.tokens.expect_identifier()?;
+        while self.tokens.consume(&Token::Period) {
+            name = self.tokens.expect_identifier()?;
+        }
+        let alias = self.parse_alias()?;
+        Ok(TableRef { name, alias })
+    }
+
+    fn parse_order_by_expr(&mut self) -> Result<OrderByExpr, Box<dyn Error>> {
+        let expr = self.parse_expr()?;
+        let descending = if self.tokens.consume_keyword("desc") {
+            true
+        } else {
+            self.tokens.consume_keyword("asc");
+            false
+        };
+        Ok(OrderByExpr { expr, descending })
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, Box<dyn Error>> {
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let mut expr = self.parse_and()?;
+        while self.tokens.consume_keyword("or") {
+            expr = Expr::binary(expr, BinaryOperator::Or, self.parse_and()?);
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let mut expr = self.parse_not()?;
+        while self.tokens.consume_keyword("and") {
+            expr = Expr::binary(expr, BinaryOperator::And, self.parse_not()?);
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, Box<dyn Error>> {
+        if self.tokens.consume_keyword("not") {
+            Ok(Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                expr: Box::new(self.parse_not()?),
+            })
+        } else {
+            self.parse_predicate()
+        }
+    }
+
+    /// Comparisons, BETWEEN, IN, LIKE and IS NULL.
+    fn parse_predicate(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let expr = self.parse_additive()?;
+        if self.tokens.consume_keyword("is") {
+            let negated = self.tokens.consume_keyword("not");
+            self.tokens.expect_keyword("null")?;
+            return Ok(Expr::IsNull {
+                expr: Box::new(expr),
+                negated,
+            });
+        }
+        let op = match self.tokens.peek() {
+            Some(Token::Eq) => Some(BinaryOperator::Eq),
+            Some(Token::NotEq) => Some(BinaryOperator::NotEq),
+            Some(Token::Lt) => Some(BinaryOperator::Lt),
+            Some(Token::LtEq) => Some(BinaryOperator::LtEq),
+            Some(Token::Gt) => Some(BinaryOperator::Gt),
+            Some(Token::GtEq) => Some(BinaryOperator::GtEq),
+            _ => None,
+        };
+        if let Some(op) = op {
+            self.tokens.next();
+            return Ok(Expr::binary(expr, op, self.parse_additive()?));
+        }
+
+        let negated = self.tokens.consume_keyword("not");
+        if self.tokens.consume_keyword("between") {
+            let low = self.parse_additive()?;
+            self.tokens.expect_keyword("and")?;
+            let high = self.parse_additive()?;
+            Ok(Expr::Between {
+                expr: Box::new(expr),
+                negated,
+                low: Box::new(low),
+                high: Box::new(high),
+            })
+        } else if self.tokens.consume_keyword("in") {
+            self.tokens.expect(&Token::LParen)?;
+            if self.tokens.is_keyword("select") {
+                return Err("Subqueries are not supported".into());
+            }
+            let list = self.parse_comma_separated(Self::parse_expr)?;
+            self.tokens.expect(&Token::RParen)?;
+            Ok(Expr::InList {
+                expr: Box::new(expr),
+                negated,
+                list,
+            })
+        } else if self.tokens.consume_keyword("like") {
+            match self.tokens.next() {
+                Some(Token::String(pattern)) => Ok(Expr::Like {
+                    expr: Box::new(expr),
+                    negated,
+                    pattern,
+                }),
+                _ => Err("LIKE expects a string pattern".into()),
+            }
+        } else if negated {
+            Err(self.tokens.unexpected("BETWEEN, IN or LIKE"))
+        } else {
+            Ok(expr)
+        }
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let mut expr = self.parse_multiplicative()?;
+        loop {
+            let op = match self.tokens.peek() {
+                Some(Token::Plus) => BinaryOperator::Plus,
+                Some(Token::Minus) => BinaryOperator::Minus,
+                _ => break,
+            };
+            self.tokens.next();
+            if self.tokens.consume_keyword("interval") {
+                expr = self.parse_date_arithmetic(expr, op)?;
+            } else {
+                expr = Expr::binary(expr, op, self.parse_multiplicative()?);
+            }
+        }
+        Ok(expr)
+    }
+
+    /// Folds `DATE 'yyyy-mm-dd' +/- INTERVAL 'n' unit` into a date literal. The INTERVAL
+    /// keyword has been consumed.
+    fn parse_date_arithmetic(
+        &mut self,
+        date: Expr,
+        op: BinaryOperator,
+    ) -> Result<Expr, Box<dyn Error>> {
+        let date = match date {
+            Expr::Literal(Literal::Date(date)) => date,
+            _ => return Err("Intervals can only be added to date literals".into()),
+        };
+        let amount: i64 = match self.tokens.next() {
+            Some(Token::String(amount)) | Some(Token::Number(amount)) => amount.trim().parse()?,
+            _ => return Err("INTERVAL expects an integer amount".into()),
+        };
+        let amount = if op == BinaryOperator::Minus {
+            -amount
+        } else {
+            amount
+        };
+        let unit = self.tokens.expect_identifier()?;
+        let (year, month, day) = parse_date(&date)?;
+        let (year, month, day) = match unit.as_str() {
+            "day" | "days" => civil_from_days(days_from_civil(year, month, day) + amount),
+            "month" | "months" => add_months(year, month, day, amount),
+            "year" | "years" => add_months(year, month, day, amount * 12),
+            _ => return Err(format!("Unsupported interval unit: {}", unit).into()),
+        };
+        Ok(Expr::Literal(Literal::Date(format!(
+            "{:04}-{:02}-{:02}",
+            year, month, day
+        ))))
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            let op = match self.tokens.peek() {
+                Some(Token::Star) => BinaryOperator::Multiply,
+                Some(Token::Slash) => BinaryOperator::Divide,
+                Some(Token::Percent) => BinaryOperator::Modulo,
+                _ => break,
+            };
+            self.tokens.next();
+            expr = Expr::binary(expr, op, self.parse_unary()?);
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, Box<dyn Error>> {
+        if self.tokens.consume(&Token::Plus) {
+            return self.parse_unary();
+        }
+        if self.tokens.consume(&Token::Minus) {
+            return Ok(match self.parse_unary()? {
+                Expr::Literal(Literal::Integer(value)) => Expr::Literal(Literal::Integer(-value)),
+                Expr::Literal(Literal::Float(value)) => Expr::Literal(Literal::Float(-value)),
+                expr => Expr::UnaryOp {
+                    op: UnaryOperator::Minus,
+                    expr: Box::new(expr),
+                },
+            });
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let token = match self.tokens.next() {
+            Some(token) => token,
+            None => return Err("Expected expression, found end of input".into()),
+        };
+        match token {
+            Token::Number(number) => Ok(Expr::Literal(parse_number(&number)?)),
+            Token::String(value) => Ok(Expr::Literal(Literal::String(value))),
+            Token::LParen => {
+                if self.tokens.is_keyword("select") {
+                    return Err("Subqueries are not supported".into());
+                }
+                let expr = self.parse_expr()?;
+                self.tokens.expect(&Token::RParen)?;
+                Ok(expr)
+            }
+            Token::Word(word) if word.quoted => self.parse_column(word.value),
+            Token::Word(word) => match word.value.as_str() {
+                "true" => Ok(Expr::Literal(Literal::Boolean(true))),
+                "false" => Ok(Expr::Literal(Literal::Boolean(false))),
+                "null" => Ok(Expr::Literal(Literal::Null)),
+                "date" if matches!(self.tokens.peek(), Some(Token::String(_))) => {
+                    match self.tokens.next() {
+                        Some(Token::String(date)) => {
+                            parse_date(&date)?;
+                            Ok(Expr::Literal(Literal::Date(date)))
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                "case" => self.parse_case(),
+                "exists" => Err("Subqueries are not supported".into()),
+                _ if self.tokens.peek() == Some(&Token::LParen) => self.parse_function(word.value),
+                _ if RESERVED.contains(&word.value.as_str()) => {
+                    Err(format!("Expected expression, found {}", word.value).into())
+                }
+                _ => self.parse_column(word.value),
+            },
+            token => Err(format!("Expected expression, found {}", token).into()),
+        }
+    }
+
+    /// `name` or `table.name`; the first identifier has been consumed.
+    fn parse_column(&mut self, first: String) -> Result<Expr, Box<dyn Error>> {
+        if self.tokens.consume(&Token::Period) {
+            Ok(Expr::Column {
+                table: Some(first),
+                name: self.tokens.expect_identifier()?,
+            })
+        } else {
+            Ok(Expr::Column {
+                table: None,
+                name: first,
+            })
+        }
+    }
+
+    fn parse_function(&mut self, name: String) -> Result<Expr, Box<dyn Error>> {
+        let func = match name.as_str() {
+            "count" => AggregateFunction::Count,
+            "sum" => AggregateFunction::Sum,
+            "avg" => AggregateFunction::Avg,
+            "min" => AggregateFunction::Min,
+            "max" => AggregateFunction::Max,
+            _ => return Err(format!("Unsupported function: {}", name).into()),
+        };
+        self.tokens.expect(&Token::LParen)?;
+        if self.tokens.is_keyword("distinct") {
+            return Err(format!("{}(DISTINCT ..) is not supported", name).into());
+        }
+        let arg = if func == AggregateFunction::Count && self.tokens.consume(&Token::Star) {
+            None
+        } else {
+            let arg = self.parse_expr()?;
+            if arg.contains_aggregate() {
+                return Err("Aggregate functions cannot be nested".into());
+            }
+            Some(Box::new(arg))
+        };
+        self.tokens.expect(&Token::RParen)?;
+        Ok(Expr::Aggregate { func, arg })
+    }
+
+    /// The CASE keyword has been consumed.
+    fn parse_case(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let operand = if self.tokens.is_keyword("when") {
+            None
+        } else {
+            Some(Box::new(self.parse_expr()?))
+        };
+        let mut when_then = vec![];
+        while self.tokens.consume_keyword("when") {
+            let when = self.parse_expr()?;
+            self.tokens.expect_keyword("then")?;
+            let then = self.parse_expr()?;
+            when_then.push((when, then));
+        }
+        if when_then.is_empty() {
+            return Err(self.tokens.unexpected("WHEN"));
+        }
+        let else_result = if self.tokens.consume_keyword("else") {
+            Some(Box::new(self.parse_expr()?))
+        } else {
+            None
+        };
+        self.tokens.expect_keyword("end")?;
+        Ok(Expr::Case {
+            operand,
+            when_then,
+            else_result,
+        })
+    }
+}
+
+fn parse_number(number: &str) -> Result<Literal, Box<dyn Error>> {
+    if number.contains('.') {
+        Ok(Literal::Float(number.parse()?))
+    } else {
+        Ok(Literal::Integer(number.parse()?))
+    }
+}
+
+fn parse_date(date: &str) -> Result<(i64, i64, i64), Box<dyn Error>> {
+    let parts: Vec<&str> = date.split('-').collect();
+    if parts.len() == 3 {
+        if let (Ok(year), Ok(month), Ok(day)) = (
+            parts[0].parse::<i64>(),
+            parts[1].parse::<i64>(),
+            parts[2].parse::<i64>(),
+        ) {
+            if (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month) {
+                return Ok((year, month, day));
+            }
+        }
+    }
+    Err(format!("Invalid date: {}", date).into())
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Adds months, clamping the day to the length of the resulting month.
+fn add_months(year: i64, month: i64, day: i64, months: i64) -> (i64, i64, i64) {
+    let total = year * 12 + (month - 1) + months;
+    let (year, month) = (total.div_euclid(12), total.rem_euclid(12) + 1);
+    (year, month, day.min(days_in_month(year, month)))
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of [days_from_civil].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_tpch_q1() {
+        let sql = std::fs::read_to_string("resources/tpc-h/queries/1.sql").unwrap();
+        let query = parse_query(&sql).unwrap();
+        assert_eq!(query.projection.len(), 10);
+        assert_eq!(query.from.len(), 1);
+        assert_eq!(query.from[0].relation.name, "lineitem");
+        assert_eq!(
+            query.selection.unwrap().to_string(),
+            "l_shipdate <= date '1998-09-02'"
+        );
+        assert_eq!(query.group_by.len(), 2);
+        assert_eq!(query.order_by.len(), 2);
+        match &query.projection[4] {
+            SelectItem::Expr { expr, alias } => {
+                assert_eq!(expr.to_string(), "sum(l_extendedprice * (1 - l_discount))");
+                assert_eq!(alias.as_deref(), Some("sum_disc_price"));
+            }
+            _ => panic!("Expected an expression"),
+        }
+    }
+
+    #[test]
+    fn can_parse_cs511_queries() {
+        for query in ["a", "b", "c", "d"] {
+            let path = format!("resources/cs511/queries/{}.sql", query);
+            let sql = std::fs::read_to_string(path).unwrap();
+            assert!(parse_query(&sql).is_ok(), "Failed to parse {}.sql", query);
+        }
+        let sql = std::fs::read_to_string("resources/cs511/queries/b.sql").unwrap();
+        let query = parse_query(&sql).unwrap();
+        assert_eq!(query.from.len(), 4);
+        assert_eq!(query.selection.unwrap().split_conjunction().len(), 4);
+        assert!(query.order_by[0].descending);
+    }
+
+    #[test]
+    fn can_parse_expressions() {
+        let expr = parse_expr(
+            "100.00 * sum(case when p_type like 'PROMO%' then l_extendedprice * (1 - l_discount) \
+             else 0 end) / sum(l_extendedprice * (1 - l_discount))",
+        )
+        .unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "100.0 * sum(case when p_type like 'PROMO%' then l_extendedprice * (1 - l_discount) \
+             else 0 end) / sum(l_extendedprice * (1 - l_discount))"
+        );
+        let expr = parse_expr("l_discount between .06 - 0.01 and .06 + 0.01").unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "l_discount between 0.06 - 0.01 and 0.06 + 0.01"
+        );
+        let expr = parse_expr("not p_size in (5, -10) or p.p_container <> 'JUMBO JAR'").unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "not p_size in (5, -10) or p.p_container <> 'JUMBO JAR'"
+        );
+        let expr = parse_expr("count(*) > 2 and -x < 3").unwrap();
+        assert_eq!(expr.to_string(), "count(*) > 2 and -x < 3");
+        let expr = parse_expr("x + 1 IS NOT NULL or y is null").unwrap();
+        assert_eq!(expr.to_string(), "x + 1 is not null or y is null");
+    }
+
+    #[test]
+    fn can_fold_date_intervals() {
+        let cases = [
+            ("date '1998-12-01' - interval '90' day", "1998-09-02"),
+            ("date '1995-09-01' + interval '1' month", "1995-10-01"),
+            ("date '1994-01-01' + interval '1' year", "1995-01-01"),
+            ("date '1993-10-01' + interval '3' month", "1994-01-01"),
+            ("date '2000-02-28' + interval '1' day", "2000-02-29"),
+            ("date '1996-01-31' + interval '1' month", "1996-02-29"),
+            ("date '1970-01-01' - interval '1' day", "1969-12-31"),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(
+                parse_expr(sql).unwrap(),
+                Expr::Literal(Literal::Date(expected.to_string())),
+                "{}",
+                sql
+            );
+        }
+        assert!(parse_expr("date '1995-13-01'").is_err());
+        assert!(parse_expr("l_shipdate + interval '1' day").is_err());
+    }
+
+    #[test]
+    fn can_parse_joins_and_aliases() {
+        let query = parse_query(
+            "SELECT n.n_name name, count(*) FROM nation n JOIN region AS r \
+             ON n.n_regionkey = r.r_regionkey GROUP BY n.n_name ORDER BY 2 DESC, name",
+        )
+        .unwrap();
+        assert_eq!(query.from[0].relation.reference_name(), "n");
+        assert_eq!(query.from[0].joins[0].relation.name, "region");
+        assert_eq!(query.from[0].joins[0].relation.reference_name(), "r");
+        assert_eq!(
+            query.projection[0],
+            SelectItem::Expr {
+                expr: Expr::Column {
+                    table: Some("n".into()),
+                    name: "n_name".into()
+                },
+                alias: Some("name".into())
+            }
+        );
+        assert!(query.order_by[0].descending);
+        assert!(!query.order_by[1].descending);
+        assert_eq!(query.limit, None);
+
+        let query = parse_query("SELECT n_name FROM nation ORDER BY n_name LIMIT 10").unwrap();
+        assert_eq!(query.limit, Some(10));
+        assert!(parse_query("SELECT n_name FROM nation LIMIT 1.5").is_err());
+    }
+
+    #[test]
+    fn can_parse_explain() {
+        let statement = parse_statement("EXPLAIN SELECT n_name FROM nation;").unwrap();
+        assert!(matches!(
+            statement,
+            Statement::Explain { analyze: false, .. }
+        ));
+        assert_eq!(statement.query().from[0].relation.name, "nation");
+        assert!(matches!(
+            parse_statement("explain analyze SELECT n_name FROM nation").unwrap(),
+            Statement::Explain { analyze: true, .. }
+        ));
+        assert!(matches!(
+            parse_statement("SELECT n_name FROM nation").unwrap(),
+            Statement::Query(_)
+        ));
+        assert!(parse_query("EXPLAIN SELECT n_name FROM nation").is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_queries() {
+        assert!(parse_query("SELECT a FROM t LEFT JOIN u ON a = b").is_err());
+        assert!(parse_query("SELECT count(distinct a) FROM t").is_err());
+        assert!(parse_query("SELECT a FROM t WHERE a IN (SELECT b FROM u)").is_err());
+        assert!(parse_query("SELECT substring(a, 1, 2) FROM t").is_err());
+        assert!(parse_query("SELECT sum(sum(a)) FROM t").is_err());
+        assert!(parse_query("SELECT a FROM t WHERE").is_err());
+        assert!(parse_query("SELECT a FROM t extra tokens").is_err());
+    }
+}
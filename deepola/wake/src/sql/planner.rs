@@ -0,0 +1,429 @@
+use std::error::Error;
+use std::result::Result;
+
+use polars::prelude::*;
+
+use crate::catalog::Catalog;
+use crate::graph::{ExecutionNode, ExecutionService, NodeReader};
+use crate::polars_operations::*;
+
+use super::binder::bind;
+use super::parser::{parse_query, parse_statement};
+use super::{optimize, CostModel, Expr, LogicalPlan, Query};
+
+/// Compiles SQL queries over the tables of a [Catalog] into execution graphs made of CSVReader,
+/// HashJoin, Filter, Project, Sort, TopK, Appender and Accumulator nodes.
+///
+/// Example:
+/// ```no_run
+/// use wake::catalog::Catalog;
+/// use wake::graph::NodeReader;
+/// use wake::sql::SqlPlanner;
+///
+/// let catalog = Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+/// let mut output_reader = NodeReader::empty();
+/// let mut service = SqlPlanner::new(&catalog)
+///     .plan("SELECT r_name FROM region", &mut output_reader)
+///     .unwrap();
+/// service.run();
+/// loop {
+///     let message = output_reader.read();
+///     if message.is_eof() {
+///         break;
+///     }
+///     println!("{:?}", message.datablock().data());
+/// }
+/// service.join();
+/// ```
+///
+/// Joins are hash joins ordered by [CostModel] estimates (see [SqlPlanner::explain]), with the
+/// table being joined as the build side. The [LogicalPlan] of the query is optimized before it
+/// is compiled, so filters run below the joins where possible and the CSVReaders only read the
+/// columns the query uses. Aggregates are accumulated as sums, so each output message is the
+/// up-to-date result over the input read so far. `ORDER BY` sorts each of these results, or
+/// the whole input at EOF if there is no aggregate; with `LIMIT`, only the first rows are kept
+/// while reading the input. Without `ORDER BY` and aggregates, the scans stop reading once
+/// `LIMIT` rows have been produced.
+pub struct SqlPlanner<'a> {
+    catalog: &'a Catalog,
+}
+
+impl<'a> SqlPlanner<'a> {
+    pub fn new(catalog: &'a Catalog) -> Self {
+        SqlPlanner { catalog }
+    }
+
+    /// The optimized logical plan of `sql`, which may be an `EXPLAIN` statement.
+    pub fn logical_plan(&self, sql: &str) -> Result<LogicalPlan, Box<dyn Error>> {
+        self.optimized_plan(parse_statement(sql)?.query())
+    }
+
+    /// The execution graph of `sql` (with or without `EXPLAIN`) as a tree with one node per
+    /// line, along with the rows each operator is estimated to produce.
+    pub fn explain(&self, sql: &str) -> Result<String, Box<dyn Error>> {
+        let plan = self.logical_plan(sql)?;
+        Ok(self.build(&plan, &mut NodeReader::empty()).explain())
+    }
+
+    /// Runs `sql` (with or without `EXPLAIN ANALYZE`) to completion, then describes its
+    /// execution graph as [SqlPlanner::explain] does, with the rows, messages and time of
+    /// each node.
+    pub fn explain_analyze(&self, sql: &str) -> Result<String, Box<dyn Error>> {
+        let plan = self.logical_plan(sql)?;
+        let mut output_reader = NodeReader::empty();
+        let mut service = self.build(&plan, &mut output_reader);
+        service.run();
+        while !output_reader.read().is_eof() {}
+        service.join();
+        Ok(service.explain_analyze())
+    }
+
+    fn optimized_plan(&self, query: &Query) -> Result<LogicalPlan, Box<dyn Error>> {
+        let query = bind(query, self.catalog)?;
+        Ok(optimize(LogicalPlan::from_query(&query, self.catalog)?))
+    }
+
+    /// Compiles `sql` into a service whose output is read by `output_reader`.
+    pub fn plan(
+        &self,
+        sql: &str,
+        output_reader: &mut NodeReader<DataFrame>,
+    ) -> Result<ExecutionService<DataFrame>, Box<dyn Error>> {
+        let plan = self.optimized_plan(&parse_query(sql)?)?;
+        Ok(self.build(&plan, output_reader))
+    }
+
+    /// Compiles a logical plan into a service whose output is read by `output_reader`.
+    pub fn build(
+        &self,
+        plan: &LogicalPlan,
+        output_reader: &mut NodeReader<DataFrame>,
+    ) -> ExecutionService<DataFrame> {
+        let mut service = ExecutionService::<DataFrame>::create();
+        let last = self.lower(plan, &mut service);
+        output_reader.subscribe_to_node(&last, 0);
+        service.add(last);
+        service
+    }
+
+    /// Creates the nodes of `plan`, adding all of them but the last one to `service`.
+    fn lower(
+        &self,
+        plan: &LogicalPlan,
+        service: &mut ExecutionService<DataFrame>,
+    ) -> ExecutionNode<DataFrame> {
+        // ORDER BY .. LIMIT keeps only the first rows while reading the input.
+        if let LogicalPlan::Limit { input, limit } = plan {
+            if let LogicalPlan::Sort { input, by } = input.as_ref() {
+                let mut last = self.lower(input, service);
+                let node = TopKNode::new()
+                    .by(by.clone())
+                    .k(*limit)
+                    .snapshots(input.emits_snapshots())
+                    .build();
+                node.set_label(&self.label(&node.label(), plan));
+                chain(service, &mut last, node);
+                return last;
+            }
+        }
+
+        let inputs: Vec<ExecutionNode<DataFrame>> = plan
+            .inputs()
+            .into_iter()
+            .map(|input| self.lower(input, service))
+            .collect();
+        let label = self.label(&plan.describe(), plan);
+        let node = match plan {
+            LogicalPlan::Scan { table, columns } => {
+                let columns: Vec<&str> = columns.iter().map(|c| c.as_str()).collect();
+                self.catalog.build_csv_reader_node(table, Some(&columns))
+            }
+            LogicalPlan::Filter { predicate, .. } => {
+                FilterNode::new().predicate(predicate.clone()).build()
+            }
+            LogicalPlan::Project { exprs, .. } => {
+                ProjectNode::new().expressions(exprs.clone()).build()
+            }
+            LogicalPlan::Join {
+                left_on, right_on, ..
+            } => HashJoinBuilder::new()
+                .left_on(left_on.clone())
+                .right_on(right_on.clone())
+                .build(),
+            LogicalPlan::Aggregate { keys, sums, .. } => {
+                let nodes = aggregate_nodes(keys, sums);
+                nodes[1].set_label(&label);
+                let mut last = inputs.into_iter().next().unwrap();
+                for node in nodes {
+                    chain(service, &mut last, node);
+                }
+                return last;
+            }
+            LogicalPlan::Sort { input, by } => {
+                let mode = if input.emits_snapshots() {
+                    SortMode::PerSnapshot
+                } else {
+                    SortMode::AtEof
+                };
+                SortNode::new().by(by.clone()).mode(mode).build()
+            }
+            LogicalPlan::Limit { input, limit } => LimitNode::new()
+                .limit(*limit)
+                .snapshots(input.emits_snapshots())
+                .build(),
+        };
+        node.set_label(&label);
+        for (channel, input) in inputs.into_iter().enumerate() {
+            node.subscribe_to_node(&input, channel);
+            service.add(input);
+        }
+        node
+    }
+
+    /// Labels a node with its description and the estimated rows of `plan`.
+    fn label(&self, description: &str, plan: &LogicalPlan) -> String {
+        format!(
+            "{}  (rows={:.0})",
+            description,
+            CostModel::new(self.catalog).estimate_rows(plan)
+        )
+    }
+}
+
+fn map_node<F>(mapper: F) -> ExecutionNode<DataFrame>
+where
+    F: Fn(&DataFrame) -> DataFrame + 'static,
+{
+    AppenderNode::<DataFrame, MapAppender>::new()
+        .appender(MapAppender::new(Box::new(mapper)))
+        .build()
+}
+
+/// Subscribes `node` to `last` and makes it the new last node.
+fn chain(
+    service: &mut ExecutionService<DataFrame>,
+    last: &mut ExecutionNode<DataFrame>,
+    node: ExecutionNode<DataFrame>,
+) {
+    node.subscribe_to_node(last, 0);
+    service.add(std::mem::replace(last, node));
+}
+
+/// Computes the group keys and the values to sum, accumulates the sums (in the second node),
+/// and names the output columns after the keys and sums.
+fn aggregate_nodes(
+    keys: &[(Expr, String)],
+    sums: &[(Expr, String)],
+) -> Vec<ExecutionNode<DataFrame>> {
+    let mut expressions = keys.to_vec();
+    expressions.extend(sums.iter().cloned());
+    let pre_aggregation_node = ProjectNode::new().expressions(expressions).build();
+
+    let mut sum_accumulator = SumAccumulator::new();
+    sum_accumulator
+        .set_group_key(keys.iter().map(|(_, n)| n.clone()).collect())
+        .set_aggregates(
+            sums.iter()
+                .map(|(_, n)| (n.clone(), vec!["sum".to_string()]))
+                .collect(),
+        );
+    let groupby_node = AccumulatorNode::<DataFrame, SumAccumulator>::new()
+        .accumulator(sum_accumulator)
+        .build();
+
+    let mut nodes = vec![pre_aggregation_node, groupby_node];
+    // The accumulator suffixes the summed columns with _sum if there are group keys.
+    if !keys.is_empty() {
+        let renames: Vec<(String, String)> = sums
+            .iter()
+            .map(|(_, n)| (format!("{}_sum", n), n.clone()))
+            .collect();
+        let description: Vec<String> = renames
+            .iter()
+            .map(|(from, to)| format!("{} AS {}", from, to))
+            .collect();
+        let rename_node = map_node(move |df| {
+            let mut df = df.clone();
+            for (from, to) in &renames {
+                df.rename(from, to).unwrap();
+            }
+            df
+        });
+        rename_node.set_label(&format!("Rename: {}", description.join(", ")));
+        nodes.push(rename_node);
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_sql(sql: &str) -> DataFrame {
+        let mut catalog =
+            Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        catalog.set_base_dir("resources/tpc-h/data/scale=0.05/partition=5");
+        let planner = SqlPlanner::new(&catalog);
+        // Without aggregation, the result is the union of all the messages.
+        let snapshots = planner.logical_plan(sql).unwrap().emits_snapshots();
+        let mut output_reader = NodeReader::empty();
+        let mut service = planner.plan(sql, &mut output_reader).unwrap();
+        service.run();
+        let mut result = DataFrame::default();
+        loop {
+            let message = output_reader.read();
+            if message.is_eof() {
+                break;
+            }
+            let data = message.datablock().data();
+            result = if snapshots || result.width() == 0 {
+                data.clone()
+            } else {
+                result.vstack(data).unwrap()
+            };
+        }
+        service.join();
+        result
+    }
+
+    #[test]
+    fn can_run_aggregation() {
+        let result = run_sql("SELECT count(*) AS parts, avg(p_size) AS avg_size FROM part");
+        assert_eq!(result.get_column_names(), vec!["parts", "avg_size"]);
+        assert_eq!(
+            result.column("parts").unwrap().i64().unwrap().get(0),
+            Some(10000)
+        );
+    }
+
+    #[test]
+    fn can_run_join_with_group_by() {
+        let result = run_sql(
+            "SELECT r_name, count(*) AS nations \
+             FROM nation, region WHERE n_regionkey = r_regionkey AND n_nationkey < 100 \
+             GROUP BY r_name ORDER BY r_name",
+        );
+        let regions: Vec<&str> = result
+            .column("r_name")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(
+            regions,
+            vec!["AFRICA", "AMERICA", "ASIA", "EUROPE", "MIDDLE EAST"]
+        );
+        let nations: Vec<i64> = result
+            .column("nations")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(nations, vec![5, 5, 5, 5, 5]);
+    }
+
+    #[test]
+    fn can_run_projection() {
+        let result = run_sql(
+            "SELECT n_name, n_nationkey * 10 AS key10 FROM nation \
+             WHERE n_name LIKE 'A%' ORDER BY n_name DESC",
+        );
+        assert_eq!(result.height(), 2);
+        assert_eq!(
+            result.column("n_name").unwrap().utf8().unwrap().get(0),
+            Some("ARGENTINA")
+        );
+        assert_eq!(
+            result.column("key10").unwrap().i64().unwrap().get(0),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn can_run_limit() {
+        let result = run_sql("SELECT p_partkey FROM part WHERE p_size > 10 LIMIT 25");
+        assert_eq!(result.height(), 25);
+
+        let result = run_sql(
+            "SELECT n_regionkey, count(*) AS nations FROM nation GROUP BY n_regionkey \
+             ORDER BY n_regionkey DESC LIMIT 2",
+        );
+        let regions: Vec<i64> = result
+            .column("n_regionkey")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(regions, vec![4, 3]);
+
+        // The first rows are kept across all the messages read from the table.
+        let result =
+            run_sql("SELECT n_nationkey, n_name FROM nation ORDER BY n_nationkey DESC LIMIT 2");
+        let nations: Vec<&str> = result
+            .column("n_name")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(nations, vec!["UNITED STATES", "UNITED KINGDOM"]);
+    }
+
+    #[test]
+    fn can_explain_query() {
+        let catalog = Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        let explained = SqlPlanner::new(&catalog)
+            .explain(
+                "EXPLAIN SELECT r_name, count(*) AS nations FROM nation, region \
+                 WHERE n_regionkey = r_regionkey AND r_name LIKE 'A%' GROUP BY r_name",
+            )
+            .unwrap();
+        assert_eq!(
+            explained,
+            [
+                "Project: r_name, agg_0 AS nations  (rows=1)",
+                "  Rename: agg_0_sum AS agg_0",
+                "    Aggregate: keys=[r_name], sums=[1 AS agg_0]  (rows=1)",
+                "      Project: r_name, 1 AS agg_0",
+                "        HashJoin: n_regionkey = r_regionkey  (rows=6)",
+                "          Scan: nation [n_regionkey]  (rows=25)",
+                "          Filter: r_name like 'A%'  (rows=1)",
+                "            Scan: region [r_regionkey, r_name]  (rows=5)",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn can_explain_analyze_query() {
+        let mut catalog =
+            Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        catalog.set_base_dir("resources/tpc-h/data/scale=0.05/partition=5");
+        let explained = SqlPlanner::new(&catalog)
+            .explain_analyze("EXPLAIN ANALYZE SELECT n_name FROM nation WHERE n_regionkey = 1")
+            .unwrap();
+        let lines: Vec<&str> = explained.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Project: n_name  (rows=2)  actual: rows=5 ("));
+        assert!(lines[1].starts_with("  Filter: n_regionkey = 1  (rows=2)  actual: rows=5 ("));
+        assert!(lines[2]
+            .starts_with("    Scan: nation [n_name, n_regionkey]  (rows=25)  actual: rows=25 ("));
+    }
+
+    #[test]
+    fn rejects_invalid_sql() {
+        let catalog = Catalog::from_manifest_file("resources/tpc-h/tpch-manifest.toml").unwrap();
+        let mut output_reader = NodeReader::empty();
+        let planner = SqlPlanner::new(&catalog);
+        assert!(planner.plan("SELECT", &mut output_reader).is_err());
+        assert!(planner
+            .plan("EXPLAIN SELECT r_name FROM region", &mut output_reader)
+            .is_err());
+        assert!(planner
+            .plan("SELECT x FROM part", &mut output_reader)
+            .is_err());
+    }
+}